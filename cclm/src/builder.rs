@@ -0,0 +1,231 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{Audience, Claims, NumericDate};
+use idk::{common::ErrorType, error::Error};
+use serde_json::Value;
+use std::{collections::BTreeMap, time::Duration};
+
+/// A fluent builder of [`Claims`] that checks declared constraints when
+/// the claims are built.
+///
+/// The constraints are the claims that must be present
+/// ([`ClaimsBuilder::require`]), the values a claim may take
+/// ([`ClaimsBuilder::allow_values`]) and the maximum length of string
+/// claims ([`ClaimsBuilder::max_length`]). They apply to registered and
+/// custom claims alike.
+///
+/// # Example
+///
+/// ```
+/// use cclm::{ClaimsBuilder, NumericDate};
+/// use std::time::Duration;
+///
+/// let claims = ClaimsBuilder::new()
+///     .issuer("https://issuer.example")
+///     .subject("alice")
+///     .audience(vec!["api", "web"])
+///     .issued_at(NumericDate::from_seconds(1_700_000_000))
+///     .expires_in(Duration::from_secs(900))
+///     .custom("role", "admin")
+///     .require("sub")
+///     .allow_values("role", ["admin", "user"])
+///     .max_length("sub", 64)
+///     .build()
+///     .unwrap();
+/// assert_eq!(claims.expiration().unwrap().seconds(), 1_700_000_900);
+///
+/// let err = ClaimsBuilder::new()
+///     .custom("role", "root")
+///     .require("sub")
+///     .allow_values("role", ["admin", "user"])
+///     .build()
+///     .unwrap_err();
+/// assert_eq!(err.error_type.name, "argument");
+/// assert!(err.message.contains("sub is required"));
+/// assert!(err.message.contains("role must be one of"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ClaimsBuilder {
+    claims: Claims,
+    expires_in: Option<Duration>,
+    required: Vec<String>,
+    allowed_values: BTreeMap<String, Vec<Value>>,
+    max_lengths: BTreeMap<String, usize>,
+}
+
+impl ClaimsBuilder {
+    /// Creates a builder with no claims and no constraints.
+    pub fn new() -> ClaimsBuilder {
+        ClaimsBuilder::default()
+    }
+
+    /// Sets the `iss` (Issuer) claim.
+    pub fn issuer(mut self, issuer: impl Into<String>) -> ClaimsBuilder {
+        self.claims.set_issuer(issuer);
+        self
+    }
+
+    /// Sets the `sub` (Subject) claim.
+    pub fn subject(mut self, subject: impl Into<String>) -> ClaimsBuilder {
+        self.claims.set_subject(subject);
+        self
+    }
+
+    /// Sets the `aud` (Audience) claim to a single value or an array.
+    pub fn audience(mut self, audience: impl Into<Audience>) -> ClaimsBuilder {
+        self.claims.set_audience(audience);
+        self
+    }
+
+    /// Sets the `iat` (Issued At) claim.
+    pub fn issued_at(mut self, iat: impl Into<NumericDate>) -> ClaimsBuilder {
+        self.claims.set_issued_at(iat);
+        self
+    }
+
+    /// Sets the `exp` (Expiration Time) claim to `duration` after the
+    /// `iat` claim, or after the current time when `iat` is not set,
+    /// once the claims are built.
+    pub fn expires_in(mut self, duration: Duration) -> ClaimsBuilder {
+        self.expires_in = Some(duration);
+        self
+    }
+
+    /// Sets the `nbf` (Not Before) claim.
+    pub fn not_before(mut self, nbf: impl Into<NumericDate>) -> ClaimsBuilder {
+        self.claims.set_not_before(nbf);
+        self
+    }
+
+    /// Sets the `jti` (JWT ID) claim.
+    pub fn jwt_id(mut self, jti: impl Into<String>) -> ClaimsBuilder {
+        self.claims.set_jwt_id(jti);
+        self
+    }
+
+    /// Sets a custom claim to a JSON value.
+    pub fn custom(mut self, key: &str, value: impl Into<Value>) -> ClaimsBuilder {
+        self.claims.set_claim_value(key, value);
+        self
+    }
+
+    /// Requires the claim `key` to be present.
+    pub fn require(mut self, key: &str) -> ClaimsBuilder {
+        if !self.required.iter().any(|required| required == key) {
+            self.required.push(key.to_string());
+        }
+        self
+    }
+
+    /// Restricts the claim `key`, when present, to one of `values`.
+    /// Every element of an array claim such as `aud` must be allowed.
+    pub fn allow_values<I, V>(mut self, key: &str, values: I) -> ClaimsBuilder
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        self.allowed_values.insert(
+            key.to_string(),
+            values.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    /// Limits the string claim `key`, or each string of an array claim,
+    /// to `max` characters.
+    pub fn max_length(mut self, key: &str, max: usize) -> ClaimsBuilder {
+        self.max_lengths.insert(key.to_string(), max);
+        self
+    }
+
+    /// Returns every constraint violated by the claims built so far.
+    pub fn violations(&self) -> Vec<String> {
+        self.check(&self.claims())
+    }
+
+    /// Builds the claims after checking every constraint.
+    ///
+    /// Returns an error of type `argument` whose message lists every
+    /// violated constraint.
+    pub fn build(self) -> Result<Claims, Error> {
+        let claims = self.claims();
+        let violations = self.check(&claims);
+        if violations.is_empty() {
+            return Ok(claims);
+        }
+        Err(Error::new(
+            &format!(
+                "{} claim constraint(s) violated: {}",
+                violations.len(),
+                violations.join("; ")
+            ),
+            ErrorType::new("argument"),
+        ))
+    }
+
+    /// Returns the claims, with `exp` set when `expires_in` was called.
+    fn claims(&self) -> Claims {
+        let mut claims = self.claims.clone();
+        if let Some(duration) = self.expires_in {
+            let start = claims.issued_at().unwrap_or_else(NumericDate::now);
+            let seconds = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+            claims.set_expiration(start.seconds().saturating_add(seconds));
+        }
+        claims
+    }
+
+    /// Returns every constraint violated by `claims`.
+    fn check(&self, claims: &Claims) -> Vec<String> {
+        let values = match serde_json::to_value(claims) {
+            Ok(Value::Object(values)) => values,
+            _ => Default::default(),
+        };
+        let mut violations = Vec::new();
+
+        for key in &self.required {
+            if !values.contains_key(key) {
+                violations.push(format!("{key} is required"));
+            }
+        }
+
+        for (key, allowed) in &self.allowed_values {
+            let Some(value) = values.get(key) else {
+                continue;
+            };
+            let elements = match value {
+                Value::Array(elements) if !allowed.contains(value) => elements.iter().collect(),
+                _ => vec![value],
+            };
+            if let Some(element) = elements.iter().find(|element| !allowed.contains(element)) {
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                violations.push(format!(
+                    "{key} must be one of [{}], found {element}",
+                    allowed.join(", ")
+                ));
+            }
+        }
+
+        for (key, max) in &self.max_lengths {
+            let Some(value) = values.get(key) else {
+                continue;
+            };
+            let strings = match value {
+                Value::Array(elements) => elements.iter().filter_map(Value::as_str).collect(),
+                _ => value.as_str().into_iter().collect::<Vec<_>>(),
+            };
+            if let Some(len) = strings
+                .iter()
+                .map(|string| string.chars().count())
+                .find(|len| len > max)
+            {
+                violations.push(format!(
+                    "{key} must be at most {max} characters, found {len}"
+                ));
+            }
+        }
+
+        violations
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use serde::Serialize;
+use serde_json::{Number, Value};
+use std::fmt::Write;
+
+/// Serializes `value` with the JSON Canonicalization Scheme (JCS,
+/// RFC 8785).
+///
+/// Object members are sorted by the UTF-16 code units of their names,
+/// numbers are written as ECMAScript does, strings use the shortest
+/// escapes and no whitespace is emitted, so equal values always produce
+/// identical bytes. As in ECMAScript, numbers are IEEE 754 doubles:
+/// integers beyond 2^53 lose precision.
+///
+/// Returns an error if `value` cannot be represented as JSON.
+///
+/// # Example
+///
+/// ```
+/// use cclm::canonical::to_canonical_json;
+/// use serde_json::json;
+///
+/// let value = json!({"b": [1.50, 1e30], "a": "\u{20ac}\n"});
+/// assert_eq!(
+///     to_canonical_json(&value).unwrap(),
+///     r#"{"a":"€\n","b":[1.5,1e+30]}"#
+/// );
+/// ```
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String, serde_json::Error> {
+    let mut json = String::new();
+    write_value(&mut json, &serde_json::to_value(value)?);
+    Ok(json)
+}
+
+/// Appends the canonical form of `value` to `json`.
+fn write_value(json: &mut String, value: &Value) {
+    match value {
+        Value::Null => json.push_str("null"),
+        Value::Bool(value) => json.push_str(if *value { "true" } else { "false" }),
+        Value::Number(number) => write_number(json, number),
+        Value::String(string) => write_string(json, string),
+        Value::Array(values) => {
+            json.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_value(json, value);
+            }
+            json.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<(&String, &Value)> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            json.push('{');
+            for (i, (name, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_string(json, name);
+                json.push(':');
+                write_value(json, value);
+            }
+            json.push('}');
+        }
+    }
+}
+
+/// Appends `string` as a JSON string, escaping only `"`, `\` and the
+/// control characters.
+fn write_string(json: &mut String, string: &str) {
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\u{8}' => json.push_str("\\b"),
+            '\t' => json.push_str("\\t"),
+            '\n' => json.push_str("\\n"),
+            '\u{c}' => json.push_str("\\f"),
+            '\r' => json.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(json, "\\u{:04x}", u32::from(c));
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Appends `number` formatted as the ECMAScript `Number.prototype.toString`
+/// method does (ECMA-262, section 7.1.12.1).
+fn write_number(json: &mut String, number: &Number) {
+    let value = number.as_f64().unwrap_or_default();
+    if value == 0.0 || !value.is_finite() {
+        json.push('0');
+        return;
+    }
+    if value < 0.0 {
+        json.push('-');
+    }
+
+    let (digits, exponent) = shortest_digits(value.abs());
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    if k <= n && n <= 21 {
+        json.push_str(&digits);
+        json.extend(std::iter::repeat('0').take((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        let (integer, fraction) = digits.split_at(n as usize);
+        let _ = write!(json, "{integer}.{fraction}");
+    } else if -6 < n && n <= 0 {
+        json.push_str("0.");
+        json.extend(std::iter::repeat('0').take(-n as usize));
+        json.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        json.push_str(first);
+        if !rest.is_empty() {
+            let _ = write!(json, ".{rest}");
+        }
+        let sign = if n > 0 { '+' } else { '-' };
+        let _ = write!(json, "e{sign}{}", (n - 1).abs());
+    }
+}
+
+/// Returns the shortest digits that round-trip to `value`, and the
+/// exponent of the first digit.
+///
+/// When two candidates are equally close to `value`, Rust picks the
+/// larger one where ECMAScript picks the one ending in an even digit.
+fn shortest_digits(value: f64) -> (String, i32) {
+    let (digits, exponent) = split_scientific(&format!("{value:e}"));
+
+    // A double has at most 767 significant decimal digits
+    let (exact, exact_exponent) = split_scientific(&format!("{value:.800e}"));
+    let exact = exact.trim_end_matches('0');
+    if exact.len() == digits.len() + 1 && exact.ends_with('5') {
+        let lower = exact[..digits.len()].trim_end_matches('0');
+        let even = lower.ends_with(['0', '2', '4', '6', '8']);
+        let round_trips = format!("0.{lower}e{}", exact_exponent + 1).parse::<f64>() == Ok(value);
+        if even && round_trips {
+            return (lower.to_string(), exact_exponent);
+        }
+    }
+    (digits, exponent)
+}
+
+/// Splits a number formatted with `{:e}` into its digits and exponent.
+fn split_scientific(scientific: &str) -> (String, i32) {
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((scientific, "0"));
+    (
+        mantissa.replace('.', ""),
+        exponent.parse().unwrap_or_default(),
+    )
+}
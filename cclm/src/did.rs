@@ -0,0 +1,223 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use idk::{common::ErrorType, error::Error};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// The claim holding the Decentralized Identifier of the subject.
+pub const DID_CLAIM: &str = "did";
+
+/// A Decentralized Identifier (DID) or DID URL, as defined by the W3C
+/// DID Core specification.
+///
+/// A DID is made of a method and a method-specific id
+/// (`did:example:123456`). A DID URL adds a path, a query and a
+/// fragment to it (`did:example:123456/path?versionId=1#keys-1`).
+/// Parsing checks the syntax of every part, so that a `Did` is always
+/// valid.
+///
+/// # Example
+///
+/// ```
+/// use cclm::Did;
+///
+/// let did: Did = "did:example:123456/path?versionId=1#keys-1".parse().unwrap();
+/// assert_eq!(did.method(), "example");
+/// assert_eq!(did.method_specific_id(), "123456");
+/// assert_eq!(did.path(), Some("/path"));
+/// assert_eq!(did.query(), Some("versionId=1"));
+/// assert_eq!(did.fragment(), Some("keys-1"));
+/// assert_eq!(did.did(), "did:example:123456");
+///
+/// assert!(Did::parse("did:Example:123456").is_err());
+/// assert!(Did::parse("did:example:").is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Did {
+    method: String,
+    method_specific_id: String,
+    path: Option<String>,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+impl Did {
+    /// Parses and validates a DID or DID URL.
+    ///
+    /// Returns an error of type `format` describing the first part of
+    /// `value` that is not valid.
+    pub fn parse(value: &str) -> Result<Did, Error> {
+        let invalid = |reason: &str| {
+            Error::new(
+                &format!("Invalid DID {value}: {reason}"),
+                ErrorType::new("format"),
+            )
+        };
+        let Some(rest) = value.strip_prefix("did:") else {
+            return Err(invalid("it must start with did:"));
+        };
+        let Some((method, rest)) = rest.split_once(':') else {
+            return Err(invalid("it has no method-specific id"));
+        };
+        if method.is_empty()
+            || !method
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        {
+            return Err(invalid(
+                "the method must be made of lowercase letters and digits",
+            ));
+        }
+
+        // The DID ends at the first character that is not allowed in a
+        // method-specific id, where the path, query or fragment starts
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (rest, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (id, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], Some(&rest[i..])),
+            None => (rest, None),
+        };
+
+        if id.is_empty() || id.ends_with(':') {
+            return Err(invalid("the method-specific id must not be empty"));
+        }
+        if !is_valid(id, |b| b.is_ascii_alphanumeric() || b".-_:".contains(&b)) {
+            return Err(invalid("the method-specific id has an invalid character"));
+        }
+        if !path.map_or(true, |path| is_valid(path, |b| b == b'/' || is_pchar(b))) {
+            return Err(invalid("the path has an invalid character"));
+        }
+        let is_query_char = |b: u8| b == b'/' || b == b'?' || is_pchar(b);
+        if !query.map_or(true, |query| is_valid(query, is_query_char)) {
+            return Err(invalid("the query has an invalid character"));
+        }
+        if !fragment.map_or(true, |fragment| is_valid(fragment, is_query_char)) {
+            return Err(invalid("the fragment has an invalid character"));
+        }
+
+        Ok(Did {
+            method: method.to_string(),
+            method_specific_id: id.to_string(),
+            path: path.map(str::to_string),
+            query: query.map(str::to_string),
+            fragment: fragment.map(str::to_string),
+        })
+    }
+
+    /// Returns the DID method, such as `key` or `web`.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the method-specific id.
+    pub fn method_specific_id(&self) -> &str {
+        &self.method_specific_id
+    }
+
+    /// Returns the path of a DID URL, starting with `/`.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the query of a DID URL, without the leading `?`.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// Returns the fragment of a DID URL, without the leading `#`.
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    /// Returns `true` if this is a DID URL with a path, query or
+    /// fragment.
+    pub fn is_url(&self) -> bool {
+        self.path.is_some() || self.query.is_some() || self.fragment.is_some()
+    }
+
+    /// Returns the DID without the path, query and fragment of a DID
+    /// URL.
+    pub fn did(&self) -> String {
+        format!("did:{}:{}", self.method, self.method_specific_id)
+    }
+
+    /// Returns the DID URL of `fragment` within this DID, such as a
+    /// verification method.
+    ///
+    /// Returns an error of type `format` if `fragment` is not valid.
+    pub fn with_fragment(&self, fragment: &str) -> Result<Did, Error> {
+        Did::parse(&format!("{}#{fragment}", self.did()))
+    }
+}
+
+impl FromStr for Did {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Did::parse(value)
+    }
+}
+
+impl fmt::Display for Did {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "did:{}:{}", self.method, self.method_specific_id)?;
+        if let Some(path) = &self.path {
+            write!(f, "{path}")?;
+        }
+        if let Some(query) = &self.query {
+            write!(f, "?{query}")?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{fragment}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Did {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Did {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Did::parse(&value).map_err(|err| de::Error::custom(err.message))
+    }
+}
+
+/// Returns `true` if every character of `value` is either allowed by
+/// `allowed` or part of a percent-encoded octet.
+fn is_valid(value: &str, allowed: impl Fn(u8) -> bool) -> bool {
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3);
+            if !hex.map_or(false, |hex| hex.iter().all(u8::is_ascii_hexdigit)) {
+                return false;
+            }
+            i += 3;
+        } else if allowed(bytes[i]) {
+            i += 1;
+        } else {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns `true` if `b` is an RFC 3986 `pchar` other than a
+/// percent-encoded octet.
+fn is_pchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&b)
+}
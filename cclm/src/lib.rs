@@ -23,9 +23,39 @@
 //! Web Signature (JWS) for Rust.
 //!
 //! The [**`Claims`**](./struct.Claims.html) type is provided to hold
-//! the claims of a JWT. The claims are stored in a `HashMap` and can be
-//! accessed using the `get_claim`, `set_claim`, `remove_claim`, and
-//! `has_claim` methods.
+//! the claims of a JWT. The claims are stored in a `HashMap` of JSON
+//! values and can be accessed using the `get_claim`, `set_claim`,
+//! `remove_claim`, and `has_claim` methods. Nested objects such as the
+//! `vc` and `vp` claims are set with `set_claim_value` or `set_claim_as`
+//! and read back with `get_claim_as::<T>()`.
+//!
+//! The registered claims defined by RFC 7519 are also available with
+//! their proper types through accessors such as `issuer`,
+//! `set_audience` and `expiration`. `exp`, `nbf` and `iat` are held as
+//! a [`NumericDate`](./struct.NumericDate.html) and `aud` as an
+//! [`Audience`](./enum.Audience.html), so they serialize as JSON
+//! numbers and arrays. Setting a registered claim by name with
+//! `set_claim` or `set_claim_value` stores it in the same typed form.
+//!
+//! Claims can also be assembled with the fluent
+//! [`ClaimsBuilder`](./struct.ClaimsBuilder.html), which checks
+//! required claims, allowed values and maximum lengths when the claims
+//! are built and reports every violation at once.
+//!
+//! [`Claims::to_canonical_json`](./struct.Claims.html#method.to_canonical_json)
+//! serializes the claims with the JSON Canonicalization Scheme (JCS,
+//! RFC 8785), which sorts members and normalizes numbers so that equal
+//! claims always produce identical bytes.
+//!
+//! A [`Did`](./struct.Did.html) parses and validates the syntax of a
+//! Decentralized Identifier or DID URL, and is read from the `did`
+//! claim with `did` and written with `set_did`.
+//!
+//! The [`vc`](./vc/index.html) module models W3C verifiable credentials
+//! and presentations, with their issuer, credential subject,
+//! `issuanceDate`, `credentialStatus` and proof. They convert to and
+//! from the claims of a JWT-VC or JWT-VP, and `check_claims` confirms
+//! that `iss` and `sub` match the credential issuer and subject.
 //!
 //! ## Features
 //!
@@ -36,7 +66,7 @@
 //! | --- | --- |
 //! | `aud` (Audience) | Identifies the recipients that the JWT is intended for. |
 //! | `custom` (Custom) | Custom claims are used to share information between parties that agree on using them and are neither registered or public claims. |
-//! | `did` (Decentralized Identifier) | A string value that uniquely identifies a subject. |
+//! | `did` (Decentralized Identifier) | A DID that uniquely identifies a subject, parsed and validated as a [`Did`](./struct.Did.html). |
 //! | `exp` (Expiration Time) | Identifies the expiration time on or after which the JWT MUST NOT be accepted for processing. |
 //! | `iat` (Issued At) | Identifies the time at which the JWT was issued. |
 //! | `iss` (Issuer) | Identifies the principal that issued the JWT. |
@@ -81,7 +111,7 @@
 //! let is_empty = claims.is_empty(); // returns true
 //!
 //! // Get the claims as a HashMap
-//! let claims_map: &HashMap<String, String> = claims.get_claims();
+//! let claims_map: &HashMap<String, serde_json::Value> = claims.get_claims();
 //!
 //! ```
 //!
@@ -105,12 +135,37 @@
 #![crate_name = "cclm"]
 #![crate_type = "lib"]
 
+extern crate idk;
 extern crate serde;
-use serde::{Deserialize, Serialize};
+extern crate serde_json;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 
 use std::collections::HashMap;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+/// The `registered` module contains the typed RFC 7519 registered
+/// claims.
+pub mod registered;
+pub use registered::{Audience, NumericDate, RegisteredClaims};
+
+/// The `canonical` module contains the JSON Canonicalization Scheme
+/// (JCS) serializer.
+pub mod canonical;
+
+/// The `builder` module contains a fluent builder of claims with schema
+/// constraints.
+pub mod builder;
+pub use builder::ClaimsBuilder;
+
+/// The `did` module contains the Decentralized Identifier (DID) type.
+pub mod did;
+pub use did::{Did, DID_CLAIM};
+
+/// The `vc` module contains the W3C Verifiable Credentials Data Model
+/// and its JWT encoding.
+pub mod vc;
+
+#[derive(Clone, Serialize, Debug)]
 /// The Claims struct holds the claims of a JSON Web Token (JWT).
 ///
 /// A JWT is a compact, URL-safe means of representing claims to be
@@ -122,11 +177,23 @@ use std::collections::HashMap;
 /// expiration time of the token, and more.
 ///
 /// The Claims struct provides a convenient way to manipulate the claims
-/// of a JWT in Rust. It stores the claims as a HashMap<String, String>,
-/// allowing for fast and efficient access to each claim.
+/// of a JWT in Rust. The RFC 7519 registered claims are held with their
+/// proper types in [`RegisteredClaims`], and every other claim is
+/// stored as a JSON value in a HashMap<String, Value>, so nested
+/// objects such as the `vc` and `vp` claims can be represented. Both
+/// are flattened into a single JSON object when serialized.
+///
+/// Deserializing sets every claim as `set_claim_value` does, so a
+/// registered claim whose value is not valid for it, such as a date
+/// string for `exp`, is kept in the free-form map and every set of
+/// claims that serializes can be read back.
 pub struct Claims {
-    /// The claims of the JWT as a HashMap.
-    pub claims: HashMap<String, String>,
+    /// The typed registered claims of the JWT.
+    #[serde(flatten)]
+    pub registered: RegisteredClaims,
+    /// The free-form claims of the JWT as a HashMap.
+    #[serde(flatten)]
+    pub claims: HashMap<String, Value>,
 }
 
 impl Claims {
@@ -142,25 +209,89 @@ impl Claims {
     /// ```
     pub fn new() -> Claims {
         Claims {
+            registered: RegisteredClaims::default(),
             claims: HashMap::new(),
         }
     }
     /// Adds or updates a claim in the `Claims` struct with the given key and value.
     ///
+    /// A registered claim is stored with its proper type, as with
+    /// `set_claim_value`, so `set_claim("sub", ..)` is read back by
+    /// `subject()` and `set_claim("exp", "1516239022")` by
+    /// `expiration()`. Use `set_claim_value` or `set_claim_as` for other
+    /// JSON values.
+    ///
     /// # Example
     ///
     /// ```
     /// use self::cclm::Claims;
     /// let mut claims = Claims::new();
     /// claims.set_claim("sub", "1234567890");
-    /// assert_eq!(claims.get_claim("sub"), Some(&String::from("1234567890")));
+    /// assert_eq!(claims.get_claim("sub").unwrap(), "1234567890");
+    /// assert_eq!(claims.subject(), Some("1234567890"));
     /// ```
     pub fn set_claim(&mut self, key: &str, value: &str) {
-        self.claims.insert(key.to_string(), value.to_string());
+        self.set_claim_value(key, value);
+    }
+    /// Adds or updates a claim in the `Claims` struct with the given
+    /// key and JSON value.
+    ///
+    /// The registered claims (`iss`, `sub`, `aud`, `exp`, `nbf`, `iat`
+    /// and `jti`) are parsed into [`RegisteredClaims`], so the typed
+    /// accessors return them; a value that is not valid for its
+    /// registered claim, such as a date string for `exp`, is kept as it
+    /// is in the free-form map instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::Claims;
+    /// use serde_json::json;
+    /// let mut claims = Claims::new();
+    /// claims.set_claim_value("vc", json!({ "type": ["VerifiableCredential"] }));
+    /// assert_eq!(claims.get_claim_value("vc").unwrap()["type"][0], "VerifiableCredential");
+    /// ```
+    pub fn set_claim_value(&mut self, key: &str, value: impl Into<Value>) {
+        match self.registered.set(key, value.into()) {
+            Ok(()) => {
+                self.claims.remove(key);
+            }
+            Err(value) => {
+                self.registered.remove(key);
+                self.claims.insert(key.to_string(), value);
+            }
+        }
+    }
+    /// Serializes `value` to JSON and stores it as the claim with the
+    /// given key.
+    ///
+    /// Returns an error if `value` cannot be represented as JSON, in
+    /// which case the claims are left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::Claims;
+    /// let mut claims = Claims::new();
+    /// claims.set_claim_as("roles", &vec!["admin", "user"]).unwrap();
+    /// assert_eq!(claims.get_claim_as::<Vec<String>>("roles").unwrap().unwrap(), ["admin", "user"]);
+    /// ```
+    pub fn set_claim_as<T: Serialize + ?Sized>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        let value = serde_json::to_value(value)?;
+        self.set_claim_value(key, value);
+        Ok(())
     }
     /// Gets a claim from the `Claims` struct with the given key.
     ///
-    /// Returns `None` if the key does not exist in the `Claims`.
+    /// Returns `None` if the key does not exist in the `Claims` or if
+    /// its value is not a JSON string. The typed `iss`, `sub`, `jti`
+    /// and single-valued `aud` claims are also returned; use
+    /// `get_claim_value`, `get_claim_as` or the typed accessors for
+    /// other values.
     ///
     /// # Example
     ///
@@ -168,14 +299,54 @@ impl Claims {
     /// use self::cclm::Claims;
     /// let mut claims = Claims::new();
     /// claims.set_claim("sub", "1234567890");
-    /// assert_eq!(claims.get_claim("sub"), Some(&String::from("1234567890")));
+    /// assert_eq!(claims.get_claim("sub").unwrap(), "1234567890");
     /// ```
     pub fn get_claim(&self, key: &str) -> Option<&String> {
+        match self.claims.get(key) {
+            Some(Value::String(value)) => Some(value),
+            Some(_) => None,
+            None => self.registered.get(key),
+        }
+    }
+    /// Gets the JSON value of a claim from the free-form claims with the
+    /// given key.
+    ///
+    /// Registered claims held in their typed form are not returned; use
+    /// `get_claim_as` to read any claim.
+    pub fn get_claim_value(&self, key: &str) -> Option<&Value> {
         self.claims.get(key)
     }
+    /// Deserializes the claim with the given key into `T`.
+    ///
+    /// Returns `Ok(None)` if the claim does not exist, and an error if
+    /// its value cannot be deserialized into `T`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::Claims;
+    /// let mut claims = Claims::new();
+    /// claims.set_expiration(1_516_239_022);
+    /// assert_eq!(claims.get_claim_as::<i64>("exp").unwrap(), Some(1_516_239_022));
+    /// ```
+    pub fn get_claim_as<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, serde_json::Error> {
+        match self.claims.get(key) {
+            Some(value) => T::deserialize(value).map(Some),
+            None => match self.registered.get_value(key) {
+                Some(value) => serde_json::from_value(value).map(Some),
+                None => Ok(None),
+            },
+        }
+    }
     //// Removes a claim from the `Claims` struct with the given key.
     ///
-    /// Returns the value of the claim that was removed, if any.
+    /// Returns the value of the claim that was removed, if any, as a
+    /// string: a JSON string is returned as it is, and any other JSON
+    /// value as its JSON text. Use `remove_claim_value` to get the JSON
+    /// value back.
     ///
     /// # Example
     ///
@@ -183,10 +354,33 @@ impl Claims {
     /// use self::cclm::Claims;
     /// let mut claims = Claims::new();
     /// claims.set_claim("sub", "1234567890");
-    /// assert_eq!(claims.remove_claim("sub"), Some("1234567890".to_owned()));
+    /// claims.set_expiration(1_516_239_022);
+    /// assert_eq!(claims.remove_claim("sub").unwrap(), "1234567890");
+    /// assert_eq!(claims.remove_claim("exp").unwrap(), "1516239022");
     /// ```
     pub fn remove_claim(&mut self, key: &str) -> Option<String> {
-        self.claims.remove(key)
+        self.remove_claim_value(key).map(|value| match value {
+            Value::String(value) => value,
+            value => value.to_string(),
+        })
+    }
+    /// Removes a claim from the `Claims` struct with the given key.
+    ///
+    /// Returns the JSON value of the claim that was removed, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::Claims;
+    /// use serde_json::json;
+    /// let mut claims = Claims::new();
+    /// claims.set_claim_value("roles", json!(["admin"]));
+    /// assert_eq!(claims.remove_claim_value("roles"), Some(json!(["admin"])));
+    /// ```
+    pub fn remove_claim_value(&mut self, key: &str) -> Option<Value> {
+        self.claims
+            .remove(key)
+            .or_else(|| self.registered.remove(key))
     }
     /// Clears all claims from the `Claims` struct.
     ///
@@ -200,6 +394,7 @@ impl Claims {
     /// assert!(claims.claims.is_empty());
     /// ```
     pub fn clear_claims(&mut self) {
+        self.registered.clear();
         self.claims.clear();
     }
     /// Checks if a claim with the given key exists in the `Claims` struct.
@@ -213,7 +408,7 @@ impl Claims {
     /// assert!(claims.has_claim("sub"));
     /// ```
     pub fn has_claim(&self, key: &str) -> bool {
-        self.claims.contains_key(key)
+        self.claims.contains_key(key) || self.registered.has(key)
     }
     /// Get the number of claims in the `Claims` struct.
     ///
@@ -226,7 +421,7 @@ impl Claims {
     /// assert_eq!(claims.len(), 1);
     /// ```
     pub fn len(&self) -> usize {
-        self.claims.len()
+        self.registered.len() + self.claims.len()
     }
     /// Checks if the `Claims` struct is empty.
     ///
@@ -240,11 +435,14 @@ impl Claims {
     /// assert!(!claims.is_empty());
     /// ```
     pub fn is_empty(&self) -> bool {
-        self.claims.is_empty()
+        self.registered.is_empty() && self.claims.is_empty()
     }
     /// Get a reference to the HashMap of claims in the `Claims` struct.
     /// This is useful if you need to iterate over the claims.
     ///
+    /// The map only holds the free-form claims; registered claims are
+    /// available through [`Claims::registered`].
+    ///
     /// # Example
     ///
     /// ```
@@ -256,30 +454,179 @@ impl Claims {
     ///    println!("{}: {}", key, value);
     /// }
     /// ```
-    pub fn get_claims(&self) -> &HashMap<String, String> {
+    pub fn get_claims(&self) -> &HashMap<String, Value> {
         &self.claims
     }
+    /// Returns the `iss` (Issuer) claim.
+    pub fn issuer(&self) -> Option<&str> {
+        self.registered.iss.as_deref()
+    }
+    /// Sets the `iss` (Issuer) claim.
+    pub fn set_issuer(&mut self, issuer: impl Into<String>) {
+        self.claims.remove("iss");
+        self.registered.iss = Some(issuer.into());
+    }
+    /// Returns the `sub` (Subject) claim.
+    pub fn subject(&self) -> Option<&str> {
+        self.registered.sub.as_deref()
+    }
+    /// Sets the `sub` (Subject) claim.
+    pub fn set_subject(&mut self, subject: impl Into<String>) {
+        self.claims.remove("sub");
+        self.registered.sub = Some(subject.into());
+    }
+    /// Returns the `aud` (Audience) claim.
+    pub fn audience(&self) -> Option<&Audience> {
+        self.registered.aud.as_ref()
+    }
+    /// Sets the `aud` (Audience) claim to a single value or an array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::{Audience, Claims};
+    /// let mut claims = Claims::new();
+    /// claims.set_audience(vec!["api", "web"]);
+    /// assert!(claims.audience().unwrap().contains("api"));
+    /// ```
+    pub fn set_audience(&mut self, audience: impl Into<Audience>) {
+        self.claims.remove("aud");
+        self.registered.aud = Some(audience.into());
+    }
+    /// Returns the `exp` (Expiration Time) claim.
+    pub fn expiration(&self) -> Option<NumericDate> {
+        self.registered.exp
+    }
+    /// Sets the `exp` (Expiration Time) claim.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::{Claims, NumericDate};
+    /// let mut claims = Claims::new();
+    /// claims.set_expiration(NumericDate::from_seconds(1_516_239_022));
+    /// assert_eq!(claims.expiration().unwrap().seconds(), 1_516_239_022);
+    /// ```
+    pub fn set_expiration(&mut self, exp: impl Into<NumericDate>) {
+        self.claims.remove("exp");
+        self.registered.exp = Some(exp.into());
+    }
+    /// Returns the `nbf` (Not Before) claim.
+    pub fn not_before(&self) -> Option<NumericDate> {
+        self.registered.nbf
+    }
+    /// Sets the `nbf` (Not Before) claim.
+    pub fn set_not_before(&mut self, nbf: impl Into<NumericDate>) {
+        self.claims.remove("nbf");
+        self.registered.nbf = Some(nbf.into());
+    }
+    /// Returns the `iat` (Issued At) claim.
+    pub fn issued_at(&self) -> Option<NumericDate> {
+        self.registered.iat
+    }
+    /// Sets the `iat` (Issued At) claim.
+    pub fn set_issued_at(&mut self, iat: impl Into<NumericDate>) {
+        self.claims.remove("iat");
+        self.registered.iat = Some(iat.into());
+    }
+    /// Returns the `jti` (JWT ID) claim.
+    pub fn jwt_id(&self) -> Option<&str> {
+        self.registered.jti.as_deref()
+    }
+    /// Sets the `jti` (JWT ID) claim.
+    pub fn set_jwt_id(&mut self, jti: impl Into<String>) {
+        self.claims.remove("jti");
+        self.registered.jti = Some(jti.into());
+    }
+    /// Returns the `did` (Decentralized Identifier) claim, parsed and
+    /// validated.
+    ///
+    /// Returns `Ok(None)` if the claim does not exist, and an error of
+    /// type `format` if it is not a valid DID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::{Claims, Did};
+    /// let mut claims = Claims::new();
+    /// claims.set_did(&Did::parse("did:example:123456").unwrap());
+    /// assert_eq!(claims.did().unwrap().unwrap().method(), "example");
+    ///
+    /// claims.set_claim("did", "not a DID");
+    /// assert!(claims.did().is_err());
+    /// ```
+    pub fn did(&self) -> Result<Option<Did>, idk::error::Error> {
+        match self.claims.get(DID_CLAIM) {
+            Some(Value::String(did)) => Did::parse(did).map(Some),
+            Some(_) => Err(idk::error::Error::new(
+                "The did claim is not a string",
+                idk::common::ErrorType::new("format"),
+            )),
+            None => Ok(None),
+        }
+    }
+    /// Sets the `did` (Decentralized Identifier) claim.
+    pub fn set_did(&mut self, did: &Did) {
+        self.set_claim_value(DID_CLAIM, did.to_string());
+    }
+    /// Serializes the claims with the JSON Canonicalization Scheme
+    /// (RFC 8785), so equal claims always produce identical bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use self::cclm::Claims;
+    /// let mut claims = Claims::new();
+    /// claims.set_claim_value("z", 1.0);
+    /// claims.set_subject("alice");
+    /// claims.set_claim("a", "b");
+    /// assert_eq!(
+    ///     claims.to_canonical_json().unwrap(),
+    ///     r#"{"a":"b","sub":"alice","z":1}"#
+    /// );
+    /// ```
+    pub fn to_canonical_json(&self) -> Result<String, serde_json::Error> {
+        canonical::to_canonical_json(self)
+    }
 }
 
 /// Implement the `Display` trait for `Claims`.
 impl std::fmt::Display for Claims {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Sort the free-form claims so the output is deterministic
+        let mut custom: Vec<(&String, &Value)> = self.claims.iter().collect();
+        custom.sort_by_key(|(k, _)| *k);
         let claims: Vec<String> = self
-            .claims
-            .iter()
+            .registered
+            .entries()
+            .into_iter()
             .map(|(k, v)| format!("{k}: {v}"))
+            .chain(custom.into_iter().map(|(k, v)| match v.as_str() {
+                Some(v) => format!("{k}: {v}"),
+                None => format!("{k}: {v}"),
+            }))
             .collect();
 
         write!(f, "Claims {{ {} }}", claims.join(", "))
     }
 }
 
+/// Implement the `Deserialize` trait for `Claims`.
+impl<'de> Deserialize<'de> for Claims {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = serde_json::Map::<String, Value>::deserialize(deserializer)?;
+        let mut claims = Claims::new();
+        for (key, value) in entries {
+            claims.set_claim_value(&key, value);
+        }
+        Ok(claims)
+    }
+}
+
 /// Implement the `Default` trait for `Claims`.
 impl Default for Claims {
     /// Create a new instance of `Claims`.
     fn default() -> Self {
-        Claims {
-            claims: HashMap::new(),
-        }
+        Claims::new()
     }
 }
@@ -0,0 +1,398 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::Value;
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The names of the registered claims defined by RFC 7519, section 4.1.
+pub const REGISTERED_CLAIM_NAMES: [&str; 7] = ["iss", "sub", "aud", "exp", "nbf", "iat", "jti"];
+
+/// A JSON numeric value representing the number of seconds from
+/// 1970-01-01T00:00:00Z UTC until the specified UTC date/time, ignoring
+/// leap seconds (RFC 7519, section 2).
+///
+/// `NumericDate` serializes to a JSON number. Non-integer values and
+/// strings holding a whole number of seconds are accepted on input.
+///
+/// # Example
+///
+/// ```
+/// use cclm::NumericDate;
+/// let date = NumericDate::from_seconds(1_516_239_022);
+/// assert_eq!(date.seconds(), 1_516_239_022);
+/// assert_eq!(date.to_string(), "1516239022");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NumericDate(i64);
+
+impl NumericDate {
+    /// Creates a new `NumericDate` from a number of seconds since the
+    /// Unix epoch.
+    pub fn from_seconds(seconds: i64) -> NumericDate {
+        NumericDate(seconds)
+    }
+
+    /// Creates a new `NumericDate` holding the current system time.
+    pub fn now() -> NumericDate {
+        NumericDate::from(SystemTime::now())
+    }
+
+    /// Returns the number of seconds since the Unix epoch.
+    pub fn seconds(&self) -> i64 {
+        self.0
+    }
+
+    /// Creates a new `NumericDate` holding midnight UTC of a date of the
+    /// proleptic Gregorian calendar. `month` and `day` are not checked
+    /// and are expected to range from 1 to 12 and 1 to 31.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cclm::NumericDate;
+    /// assert_eq!(NumericDate::from_ymd(2023, 1, 1).seconds(), 1_672_531_200);
+    /// assert_eq!(NumericDate::from_ymd(1969, 12, 31).seconds(), -86_400);
+    /// ```
+    pub fn from_ymd(year: i64, month: i64, day: i64) -> NumericDate {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        NumericDate((era * 146_097 + day_of_era - 719_468) * 86_400)
+    }
+}
+
+impl From<i64> for NumericDate {
+    /// Converts a number of seconds since the Unix epoch into a
+    /// `NumericDate`.
+    fn from(seconds: i64) -> Self {
+        NumericDate(seconds)
+    }
+}
+
+impl From<SystemTime> for NumericDate {
+    /// Converts a `SystemTime` into a `NumericDate`, truncating to
+    /// whole seconds.
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => NumericDate(elapsed.as_secs() as i64),
+            Err(err) => NumericDate(-(err.duration().as_secs() as i64)),
+        }
+    }
+}
+
+impl From<NumericDate> for Value {
+    /// Converts a `NumericDate` into a JSON number.
+    fn from(date: NumericDate) -> Self {
+        Value::from(date.0)
+    }
+}
+
+impl fmt::Display for NumericDate {
+    /// Formats the `NumericDate` as a number of seconds.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for NumericDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for NumericDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NumericDateVisitor;
+
+        impl Visitor<'_> for NumericDateVisitor {
+            type Value = NumericDate;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a JSON number of seconds since the epoch")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<NumericDate, E> {
+                Ok(NumericDate(value))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<NumericDate, E> {
+                i64::try_from(value)
+                    .map(NumericDate)
+                    .map_err(|_| E::custom("NumericDate is out of range"))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<NumericDate, E> {
+                if value.is_finite() {
+                    Ok(NumericDate(value.trunc() as i64))
+                } else {
+                    Err(E::custom("NumericDate must be finite"))
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<NumericDate, E> {
+                value
+                    .trim()
+                    .parse::<i64>()
+                    .map(NumericDate)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(NumericDateVisitor)
+    }
+}
+
+/// The `aud` (Audience) claim. Per RFC 7519, section 4.1.3, the
+/// audience is either a single case-sensitive string or an array of
+/// such strings.
+///
+/// # Example
+///
+/// ```
+/// use cclm::Audience;
+/// let aud = Audience::from(vec!["api", "web"]);
+/// assert!(aud.contains("web"));
+/// assert!(!aud.contains("admin"));
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    /// A single audience value, serialized as a JSON string.
+    Single(String),
+    /// Multiple audience values, serialized as a JSON array.
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    /// Returns `true` if the given value is one of the audiences.
+    pub fn contains(&self, value: &str) -> bool {
+        self.iter().any(|aud| aud == value)
+    }
+
+    /// Returns an iterator over the audience values.
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        match self {
+            Audience::Single(aud) => std::slice::from_ref(aud).iter(),
+            Audience::Multiple(auds) => auds.iter(),
+        }
+    }
+}
+
+impl From<&str> for Audience {
+    fn from(value: &str) -> Self {
+        Audience::Single(value.to_owned())
+    }
+}
+
+impl From<String> for Audience {
+    fn from(value: String) -> Self {
+        Audience::Single(value)
+    }
+}
+
+impl From<Vec<String>> for Audience {
+    fn from(values: Vec<String>) -> Self {
+        Audience::Multiple(values)
+    }
+}
+
+impl From<Vec<&str>> for Audience {
+    fn from(values: Vec<&str>) -> Self {
+        Audience::Multiple(values.into_iter().map(str::to_owned).collect())
+    }
+}
+
+impl From<&Audience> for Value {
+    /// Converts an `Audience` into a JSON string or array.
+    fn from(audience: &Audience) -> Self {
+        match audience {
+            Audience::Single(aud) => Value::from(aud.as_str()),
+            Audience::Multiple(auds) => Value::from(auds.clone()),
+        }
+    }
+}
+
+impl fmt::Display for Audience {
+    /// Formats the audience as a comma-separated list.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<&str> = self.iter().map(String::as_str).collect();
+        write!(f, "{}", values.join(", "))
+    }
+}
+
+/// The registered claims defined by RFC 7519, section 4.1, held with
+/// their proper types.
+///
+/// All fields are optional and omitted from the serialized JSON when
+/// unset.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RegisteredClaims {
+    /// The `iss` (Issuer) claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// The `sub` (Subject) claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// The `aud` (Audience) claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    /// The `exp` (Expiration Time) claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<NumericDate>,
+    /// The `nbf` (Not Before) claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<NumericDate>,
+    /// The `iat` (Issued At) claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<NumericDate>,
+    /// The `jti` (JWT ID) claim.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+impl RegisteredClaims {
+    /// Returns `true` if `key` is the name of a registered claim.
+    pub fn is_registered(key: &str) -> bool {
+        REGISTERED_CLAIM_NAMES.contains(&key)
+    }
+
+    /// Returns a reference to the string value of a registered claim.
+    ///
+    /// Only `iss`, `sub`, `jti` and a single-valued `aud` have a string
+    /// representation; use the typed fields for the others.
+    pub fn get(&self, key: &str) -> Option<&String> {
+        match key {
+            "iss" => self.iss.as_ref(),
+            "sub" => self.sub.as_ref(),
+            "jti" => self.jti.as_ref(),
+            "aud" => match &self.aud {
+                Some(Audience::Single(aud)) => Some(aud),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the JSON value of the registered claim `key`.
+    pub fn get_value(&self, key: &str) -> Option<Value> {
+        match key {
+            "iss" => self.iss.clone().map(Value::from),
+            "sub" => self.sub.clone().map(Value::from),
+            "aud" => self.aud.as_ref().map(Value::from),
+            "exp" => self.exp.map(Value::from),
+            "nbf" => self.nbf.map(Value::from),
+            "iat" => self.iat.map(Value::from),
+            "jti" => self.jti.clone().map(Value::from),
+            _ => None,
+        }
+    }
+
+    /// Sets the registered claim `key` from a JSON value, parsed as it
+    /// is when deserializing claims, so a `NumericDate` is accepted as a
+    /// number or a string holding a whole number of seconds.
+    ///
+    /// Gives `value` back if `key` is not a registered claim or `value`
+    /// is not valid for it, leaving the claims unchanged.
+    pub fn set(&mut self, key: &str, value: Value) -> Result<(), Value> {
+        fn parse<T: serde::de::DeserializeOwned>(value: &Value) -> Option<T> {
+            T::deserialize(value).ok()
+        }
+        let parsed = match key {
+            "iss" => parse(&value).map(|iss| self.iss = Some(iss)),
+            "sub" => parse(&value).map(|sub| self.sub = Some(sub)),
+            "aud" => parse(&value).map(|aud| self.aud = Some(aud)),
+            "exp" => parse(&value).map(|exp| self.exp = Some(exp)),
+            "nbf" => parse(&value).map(|nbf| self.nbf = Some(nbf)),
+            "iat" => parse(&value).map(|iat| self.iat = Some(iat)),
+            "jti" => parse(&value).map(|jti| self.jti = Some(jti)),
+            _ => None,
+        };
+        parsed.ok_or(value)
+    }
+
+    /// Returns `true` if the registered claim `key` is set.
+    pub fn has(&self, key: &str) -> bool {
+        match key {
+            "iss" => self.iss.is_some(),
+            "sub" => self.sub.is_some(),
+            "aud" => self.aud.is_some(),
+            "exp" => self.exp.is_some(),
+            "nbf" => self.nbf.is_some(),
+            "iat" => self.iat.is_some(),
+            "jti" => self.jti.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Unsets the registered claim `key`, returning its JSON value.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let value = self.get_value(key);
+        match key {
+            "iss" => self.iss = None,
+            "sub" => self.sub = None,
+            "aud" => self.aud = None,
+            "exp" => self.exp = None,
+            "nbf" => self.nbf = None,
+            "iat" => self.iat = None,
+            "jti" => self.jti = None,
+            _ => {}
+        }
+        value
+    }
+
+    /// Returns the registered claims that are set, as name and string
+    /// value pairs in RFC 7519 order.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = Vec::new();
+        if let Some(iss) = &self.iss {
+            entries.push(("iss", iss.clone()));
+        }
+        if let Some(sub) = &self.sub {
+            entries.push(("sub", sub.clone()));
+        }
+        if let Some(aud) = &self.aud {
+            entries.push(("aud", aud.to_string()));
+        }
+        if let Some(exp) = self.exp {
+            entries.push(("exp", exp.to_string()));
+        }
+        if let Some(nbf) = self.nbf {
+            entries.push(("nbf", nbf.to_string()));
+        }
+        if let Some(iat) = self.iat {
+            entries.push(("iat", iat.to_string()));
+        }
+        if let Some(jti) = &self.jti {
+            entries.push(("jti", jti.clone()));
+        }
+        entries
+    }
+
+    /// Returns the number of registered claims that are set.
+    pub fn len(&self) -> usize {
+        REGISTERED_CLAIM_NAMES
+            .iter()
+            .filter(|key| self.has(key))
+            .count()
+    }
+
+    /// Returns `true` if no registered claim is set.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Unsets every registered claim.
+    pub fn clear(&mut self) {
+        *self = RegisteredClaims::default();
+    }
+}
@@ -0,0 +1,585 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{Claims, NumericDate};
+use idk::{common::ErrorType, error::Error};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The base context of every credential and presentation.
+pub const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+
+/// The type every verifiable credential has.
+pub const VERIFIABLE_CREDENTIAL: &str = "VerifiableCredential";
+
+/// The type every verifiable presentation has.
+pub const VERIFIABLE_PRESENTATION: &str = "VerifiablePresentation";
+
+/// The claim holding a verifiable credential in a JWT.
+pub const VC_CLAIM: &str = "vc";
+
+/// The claim holding a verifiable presentation in a JWT.
+pub const VP_CLAIM: &str = "vp";
+
+/// The issuer of a verifiable credential, either a URI or an object
+/// with an `id` and other properties such as a `name`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Issuer {
+    /// The URI identifying the issuer.
+    Uri(String),
+    /// An issuer described by an object.
+    Object {
+        /// The URI identifying the issuer.
+        id: String,
+        /// The other properties of the issuer.
+        #[serde(flatten)]
+        properties: Map<String, Value>,
+    },
+}
+
+impl Issuer {
+    /// Returns the URI identifying the issuer.
+    pub fn id(&self) -> &str {
+        match self {
+            Issuer::Uri(id) | Issuer::Object { id, .. } => id,
+        }
+    }
+}
+
+impl From<&str> for Issuer {
+    fn from(id: &str) -> Self {
+        Issuer::Uri(id.to_string())
+    }
+}
+
+impl From<String> for Issuer {
+    fn from(id: String) -> Self {
+        Issuer::Uri(id)
+    }
+}
+
+/// The subject of the claims made by a verifiable credential.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CredentialSubject {
+    /// The URI identifying the subject, usually a DID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The claims made about the subject.
+    #[serde(flatten)]
+    pub claims: Map<String, Value>,
+}
+
+impl CredentialSubject {
+    /// Creates a subject identified by `id` with no claims.
+    pub fn new(id: impl Into<String>) -> CredentialSubject {
+        CredentialSubject {
+            id: Some(id.into()),
+            claims: Map::new(),
+        }
+    }
+
+    /// Adds or updates a claim made about the subject.
+    pub fn with_claim(mut self, key: &str, value: impl Into<Value>) -> CredentialSubject {
+        self.claims.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// The location of the status of a credential, such as an entry of a
+/// revocation list.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CredentialStatus {
+    /// The URI of the status entry.
+    pub id: String,
+    /// The type of the status mechanism.
+    #[serde(rename = "type")]
+    pub status_type: String,
+    /// The properties specific to the status mechanism.
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+/// An embedded proof of a credential or presentation, such as a Data
+/// Integrity or Linked Data signature.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Proof {
+    /// The type of the proof.
+    #[serde(rename = "type")]
+    pub proof_type: String,
+    /// The time the proof was created, as an XML Schema `dateTime`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// The URI of the key used to verify the proof.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_method: Option<String>,
+    /// The purpose of the proof, such as `assertionMethod`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_purpose: Option<String>,
+    /// A detached JWS over the credential or presentation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jws: Option<String>,
+    /// The encoded proof value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_value: Option<String>,
+    /// The properties specific to the proof type.
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+/// A verifiable credential as defined by the W3C Verifiable
+/// Credentials Data Model.
+///
+/// [`VerifiableCredential::to_claims`] and
+/// [`VerifiableCredential::from_claims`] convert a credential to and
+/// from the claims of a JWT-VC, where `iss`, `sub`, `nbf`, `exp` and
+/// `jti` carry the issuer, subject id, issuance date, expiration date
+/// and id of the credential held in the `vc` claim.
+///
+/// # Example
+///
+/// ```
+/// use cclm::vc::{CredentialSubject, VerifiableCredential};
+///
+/// let subject = CredentialSubject::new("did:example:alice")
+///     .with_claim("degree", "Bachelor of Science");
+/// let credential = VerifiableCredential::new(
+///     "https://university.example",
+///     "2023-11-14T22:13:20Z",
+///     subject,
+/// );
+///
+/// let claims = credential.to_claims().unwrap();
+/// assert_eq!(claims.issuer(), Some("https://university.example"));
+/// assert_eq!(claims.subject(), Some("did:example:alice"));
+/// assert_eq!(claims.not_before().unwrap().seconds(), 1_700_000_000);
+/// assert!(VerifiableCredential::check_claims(&claims).is_ok());
+/// assert_eq!(VerifiableCredential::from_claims(&claims).unwrap(), credential);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiableCredential {
+    /// The JSON-LD contexts, starting with [`CREDENTIALS_CONTEXT`].
+    #[serde(rename = "@context")]
+    pub context: Vec<Value>,
+    /// The URI identifying the credential.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The types of the credential, including [`VERIFIABLE_CREDENTIAL`].
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    /// The issuer of the credential.
+    pub issuer: Issuer,
+    /// The time from which the credential is valid, as an XML Schema
+    /// `dateTime`.
+    pub issuance_date: String,
+    /// The time after which the credential is no longer valid, as an
+    /// XML Schema `dateTime`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<String>,
+    /// The subject of the claims made by the credential.
+    pub credential_subject: CredentialSubject,
+    /// The location of the status of the credential.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_status: Option<CredentialStatus>,
+    /// The embedded proof of the credential.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<Proof>,
+    /// The other properties of the credential, such as
+    /// `credentialSchema` or `evidence`.
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+impl VerifiableCredential {
+    /// Creates a credential of type [`VERIFIABLE_CREDENTIAL`] in the base
+    /// context.
+    pub fn new(
+        issuer: impl Into<Issuer>,
+        issuance_date: impl Into<String>,
+        credential_subject: CredentialSubject,
+    ) -> VerifiableCredential {
+        VerifiableCredential {
+            context: vec![CREDENTIALS_CONTEXT.into()],
+            id: None,
+            types: vec![VERIFIABLE_CREDENTIAL.to_string()],
+            issuer: issuer.into(),
+            issuance_date: issuance_date.into(),
+            expiration_date: None,
+            credential_subject,
+            credential_status: None,
+            proof: None,
+            properties: Map::new(),
+        }
+    }
+
+    /// Returns the claims of the JWT-VC encoding the credential.
+    ///
+    /// Returns an error of type `format` if the issuance or expiration
+    /// date is not a valid `dateTime`.
+    pub fn to_claims(&self) -> Result<Claims, Error> {
+        let mut claims = Claims::new();
+        claims.set_issuer(self.issuer.id());
+        claims.set_not_before(parse_date_time(&self.issuance_date)?);
+        if let Some(expiration_date) = &self.expiration_date {
+            claims.set_expiration(parse_date_time(expiration_date)?);
+        }
+        if let Some(id) = &self.id {
+            claims.set_jwt_id(id);
+        }
+        if let Some(id) = &self.credential_subject.id {
+            claims.set_subject(id);
+        }
+        claims
+            .set_claim_as(VC_CLAIM, self)
+            .map_err(|err| format_error(&format!("Invalid credential: {err}")))?;
+        Ok(claims)
+    }
+
+    /// Returns the credential held in the `vc` claim of a JWT-VC.
+    ///
+    /// The properties the `vc` claim leaves out are taken from the
+    /// `iss`, `sub`, `nbf`, `exp` and `jti` claims. Returns an error of
+    /// type `format` if the claim is missing or malformed. Use
+    /// [`VerifiableCredential::check_claims`] to check that the claims
+    /// agree with the credential.
+    pub fn from_claims(claims: &Claims) -> Result<VerifiableCredential, Error> {
+        let mut credential = object_claim(claims, VC_CLAIM)?;
+        fill(
+            &mut credential,
+            "issuer",
+            claims.get_claim("iss").map(String::as_str),
+        );
+        fill(
+            &mut credential,
+            "id",
+            claims.get_claim("jti").map(String::as_str),
+        );
+        fill_date(&mut credential, "issuanceDate", claims, "nbf")?;
+        fill_date(&mut credential, "expirationDate", claims, "exp")?;
+        if let Some(Value::Object(subject)) = credential.get_mut("credentialSubject") {
+            fill(subject, "id", claims.get_claim("sub").map(String::as_str));
+        }
+        deserialize(credential, "credential")
+    }
+
+    /// Checks that the `iss` claim matches the issuer of the credential
+    /// held in the `vc` claim, and that the `sub` claim matches the id
+    /// of its subject.
+    ///
+    /// The `iss` claim is required, and so is the `sub` claim when the
+    /// subject has an id. Returns an error of type `rejection` when the
+    /// claims disagree with the credential, and of type `format` when
+    /// the `vc` claim is missing or malformed.
+    pub fn check_claims(claims: &Claims) -> Result<(), Error> {
+        let credential = VerifiableCredential::from_claims(claims)?;
+        let Some(iss) = claims.get_claim("iss") else {
+            return Err(rejection_error("The iss claim is missing"));
+        };
+        if iss != credential.issuer.id() {
+            return Err(rejection_error(&format!(
+                "The iss claim {iss} does not match the credential issuer {}",
+                credential.issuer.id()
+            )));
+        }
+        if let Some(id) = &credential.credential_subject.id {
+            if claims.get_claim("sub") != Some(id) {
+                return Err(rejection_error(&format!(
+                    "The sub claim does not match the credential subject {id}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A credential presented in a verifiable presentation, either as a
+/// JWT-VC or embedded as an object.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PresentedCredential {
+    /// A credential encoded as a JWT-VC.
+    Jwt(String),
+    /// An embedded credential.
+    Embedded(Box<VerifiableCredential>),
+}
+
+/// A verifiable presentation as defined by the W3C Verifiable
+/// Credentials Data Model.
+///
+/// [`VerifiablePresentation::to_claims`] and
+/// [`VerifiablePresentation::from_claims`] convert a presentation to
+/// and from the claims of a JWT-VP, where `iss` and `jti` carry the
+/// holder and id of the presentation held in the `vp` claim.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiablePresentation {
+    /// The JSON-LD contexts, starting with [`CREDENTIALS_CONTEXT`].
+    #[serde(rename = "@context")]
+    pub context: Vec<Value>,
+    /// The URI identifying the presentation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The types of the presentation, including
+    /// [`VERIFIABLE_PRESENTATION`].
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    /// The URI identifying the holder of the presentation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holder: Option<String>,
+    /// The credentials presented.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verifiable_credential: Vec<PresentedCredential>,
+    /// The embedded proof of the presentation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<Proof>,
+    /// The other properties of the presentation.
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+impl VerifiablePresentation {
+    /// Creates a presentation of type [`VERIFIABLE_PRESENTATION`] in the
+    /// base context.
+    pub fn new(
+        holder: impl Into<String>,
+        verifiable_credential: Vec<PresentedCredential>,
+    ) -> VerifiablePresentation {
+        VerifiablePresentation {
+            context: vec![CREDENTIALS_CONTEXT.into()],
+            id: None,
+            types: vec![VERIFIABLE_PRESENTATION.to_string()],
+            holder: Some(holder.into()),
+            verifiable_credential,
+            proof: None,
+            properties: Map::new(),
+        }
+    }
+
+    /// Returns the claims of the JWT-VP encoding the presentation.
+    pub fn to_claims(&self) -> Result<Claims, Error> {
+        let mut claims = Claims::new();
+        if let Some(holder) = &self.holder {
+            claims.set_issuer(holder);
+        }
+        if let Some(id) = &self.id {
+            claims.set_jwt_id(id);
+        }
+        claims
+            .set_claim_as(VP_CLAIM, self)
+            .map_err(|err| format_error(&format!("Invalid presentation: {err}")))?;
+        Ok(claims)
+    }
+
+    /// Returns the presentation held in the `vp` claim of a JWT-VP.
+    ///
+    /// The holder and id the `vp` claim leaves out are taken from the
+    /// `iss` and `jti` claims. Returns an error of type `format` if the
+    /// claim is missing or malformed.
+    pub fn from_claims(claims: &Claims) -> Result<VerifiablePresentation, Error> {
+        let mut presentation = object_claim(claims, VP_CLAIM)?;
+        fill(
+            &mut presentation,
+            "holder",
+            claims.get_claim("iss").map(String::as_str),
+        );
+        fill(
+            &mut presentation,
+            "id",
+            claims.get_claim("jti").map(String::as_str),
+        );
+        deserialize(presentation, "presentation")
+    }
+
+    /// Checks that the `iss` claim is present and matches the holder of
+    /// the presentation held in the `vp` claim.
+    ///
+    /// Returns an error of type `rejection` when the claims disagree
+    /// with the presentation, and of type `format` when the `vp` claim
+    /// is missing or malformed.
+    pub fn check_claims(claims: &Claims) -> Result<(), Error> {
+        let presentation = VerifiablePresentation::from_claims(claims)?;
+        let Some(iss) = claims.get_claim("iss") else {
+            return Err(rejection_error("The iss claim is missing"));
+        };
+        if presentation.holder.as_deref() != Some(iss) {
+            return Err(rejection_error(&format!(
+                "The iss claim {iss} does not match the presentation holder"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the object held in the claim `name`.
+fn object_claim(claims: &Claims, name: &str) -> Result<Map<String, Value>, Error> {
+    match claims.get_claim_value(name) {
+        Some(Value::Object(object)) => Ok(object.clone()),
+        Some(_) => Err(format_error(&format!("The {name} claim is not an object"))),
+        None => Err(format_error(&format!("The {name} claim is missing"))),
+    }
+}
+
+/// Sets the property `key` of `object` to `value` unless it is present.
+fn fill(object: &mut Map<String, Value>, key: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        object
+            .entry(key)
+            .or_insert_with(|| Value::String(value.to_string()));
+    }
+}
+
+/// Sets the date property `key` of `object` from the numeric date claim
+/// `claim` unless it is present.
+fn fill_date(
+    object: &mut Map<String, Value>,
+    key: &str,
+    claims: &Claims,
+    claim: &str,
+) -> Result<(), Error> {
+    let date = claims
+        .get_claim_as::<NumericDate>(claim)
+        .map_err(|err| format_error(&format!("Invalid {claim} claim: {err}")))?;
+    if let Some(date) = date {
+        fill(object, key, Some(&format_date_time(date)));
+    }
+    Ok(())
+}
+
+/// Deserializes a credential or presentation from its JSON object.
+fn deserialize<T: DeserializeOwned>(object: Map<String, Value>, what: &str) -> Result<T, Error> {
+    serde_json::from_value(Value::Object(object))
+        .map_err(|err| format_error(&format!("Invalid {what}: {err}")))
+}
+
+fn format_error(message: &str) -> Error {
+    Error::new(message, ErrorType::new("format"))
+}
+
+fn rejection_error(message: &str) -> Error {
+    Error::new(message, ErrorType::new("rejection"))
+}
+
+/// Parses an XML Schema `dateTime` with a time zone, such as
+/// `2023-11-14T22:13:20Z`, into a numeric date. Fractions of a second
+/// are truncated.
+fn parse_date_time(value: &str) -> Result<NumericDate, Error> {
+    let invalid = || format_error(&format!("Invalid dateTime: {value}"));
+    let bytes = value.as_bytes();
+    let number = |start: usize, len: usize| -> Option<i64> {
+        let digits = bytes.get(start..start + len)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        Some(
+            digits
+                .iter()
+                .fold(0, |acc, digit| acc * 10 + i64::from(digit - b'0')),
+        )
+    };
+    let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':')];
+    if separators
+        .iter()
+        .any(|&(i, c)| !bytes.get(i).map_or(false, |b| b.eq_ignore_ascii_case(&c)))
+    {
+        return Err(invalid());
+    }
+    let (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) = (
+        number(0, 4),
+        number(5, 2),
+        number(8, 2),
+        number(11, 2),
+        number(14, 2),
+        number(17, 2),
+    ) else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return Err(invalid());
+    }
+
+    // Skip the fraction of a second, then read the time zone
+    let mut rest = &value[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        rest = &fraction[digits..];
+    }
+    let offset = match rest.as_bytes() {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let (Some(hours), Some(minutes)) =
+                (number(value.len() - 5, 2), number(value.len() - 2, 2))
+            else {
+                return Err(invalid());
+            };
+            if hours > 14 || minutes > 59 {
+                return Err(invalid());
+            }
+            let offset = hours * 3_600 + minutes * 60;
+            if *sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return Err(invalid()),
+    };
+
+    let midnight = NumericDate::from_ymd(year, month, day).seconds();
+    Ok(NumericDate::from_seconds(
+        midnight + hour * 3_600 + minute * 60 + second - offset,
+    ))
+}
+
+/// Formats a numeric date as an XML Schema `dateTime` in UTC.
+fn format_date_time(date: NumericDate) -> String {
+    let seconds = date.seconds();
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the date of the proleptic Gregorian calendar `days` after
+/// 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
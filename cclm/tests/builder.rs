@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    extern crate cclm;
+    extern crate idk;
+
+    use self::cclm::{Audience, ClaimsBuilder, NumericDate};
+    use serde_json::json;
+    use std::time::Duration;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_fluent_claims() {
+        let claims = ClaimsBuilder::new()
+            .issuer("https://issuer.example")
+            .subject("alice")
+            .audience("api")
+            .issued_at(NOW)
+            .not_before(NOW + 10)
+            .expires_in(Duration::from_secs(3_600))
+            .jwt_id("id-1")
+            .custom("scope", json!(["read", "write"]))
+            .custom("admin", false)
+            .build()
+            .unwrap();
+
+        assert_eq!(claims.issuer(), Some("https://issuer.example"));
+        assert_eq!(claims.subject(), Some("alice"));
+        assert_eq!(claims.audience(), Some(&Audience::from("api")));
+        assert_eq!(claims.issued_at(), Some(NumericDate::from_seconds(NOW)));
+        assert_eq!(
+            claims.not_before(),
+            Some(NumericDate::from_seconds(NOW + 10))
+        );
+        assert_eq!(
+            claims.expiration(),
+            Some(NumericDate::from_seconds(NOW + 3_600))
+        );
+        assert_eq!(claims.jwt_id(), Some("id-1"));
+        assert_eq!(
+            claims.get_claim_value("scope"),
+            Some(&json!(["read", "write"]))
+        );
+        assert_eq!(claims.get_claim_value("admin"), Some(&json!(false)));
+    }
+
+    #[test]
+    fn test_expires_in_defaults_to_now() {
+        let before = NumericDate::now().seconds();
+        let claims = ClaimsBuilder::new()
+            .expires_in(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let exp = claims.expiration().unwrap().seconds();
+        assert!(exp >= before + 60 && exp <= NumericDate::now().seconds() + 60);
+        assert_eq!(claims.issued_at(), None);
+    }
+
+    #[test]
+    fn test_constraints_are_satisfied() {
+        let builder = ClaimsBuilder::new()
+            .subject("alice")
+            .audience(vec!["api", "web"])
+            .expires_in(Duration::from_secs(60))
+            .custom("role", "admin")
+            .require("sub")
+            .require("exp")
+            .allow_values("aud", ["api", "web", "mobile"])
+            .allow_values("role", ["admin", "user"])
+            .max_length("sub", 5)
+            .max_length("aud", 3);
+        assert!(builder.violations().is_empty());
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn test_every_violation_is_reported() {
+        let builder = ClaimsBuilder::new()
+            .issuer("https://a-very-long-issuer.example")
+            .audience(vec!["api", "admin"])
+            .custom("role", "root")
+            .custom("level", 3)
+            .require("sub")
+            .require("exp")
+            .allow_values("aud", ["api", "web"])
+            .allow_values("role", ["admin", "user"])
+            .allow_values("level", [1, 2])
+            .max_length("iss", 20);
+        let violations = builder.violations();
+        assert_eq!(
+            violations,
+            [
+                "sub is required",
+                "exp is required",
+                r#"aud must be one of ["api", "web"], found "admin""#,
+                "level must be one of [1, 2], found 3",
+                r#"role must be one of ["admin", "user"], found "root""#,
+                "iss must be at most 20 characters, found 34",
+            ]
+        );
+
+        let err = builder.build().unwrap_err();
+        assert_eq!(err.error_type.name, "argument");
+        assert!(err.message.starts_with("6 claim constraint(s) violated: "));
+        for violation in &violations {
+            assert!(err.message.contains(violation.as_str()), "{violation}");
+        }
+    }
+
+    #[test]
+    fn test_constraints_ignore_absent_optional_claims() {
+        let claims = ClaimsBuilder::new()
+            .allow_values("role", ["admin"])
+            .max_length("sub", 1)
+            .build()
+            .unwrap();
+        assert!(claims.is_empty());
+    }
+
+    #[test]
+    fn test_allowed_array_value() {
+        let builder = ClaimsBuilder::new()
+            .custom("scope", json!(["read"]))
+            .allow_values("scope", [json!(["read"]), json!(["read", "write"])]);
+        assert!(builder.violations().is_empty());
+    }
+
+    #[test]
+    fn test_max_length_counts_characters() {
+        let builder = ClaimsBuilder::new()
+            .subject("élodie")
+            .custom("tags", json!(["short", "much too long"]))
+            .max_length("sub", 6)
+            .max_length("tags", 5);
+        assert_eq!(
+            builder.violations(),
+            ["tags must be at most 5 characters, found 13"]
+        );
+    }
+
+    #[test]
+    fn test_require_is_idempotent() {
+        let builder = ClaimsBuilder::new().require("sub").require("sub");
+        assert_eq!(builder.violations(), ["sub is required"]);
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    extern crate cclm;
+
+    use self::cclm::{canonical::to_canonical_json, Claims};
+    use serde_json::{json, Value};
+
+    // RFC 8785, Appendix B: IEEE 754 doubles and their canonical form.
+    const NUMBERS: [(u64, &str); 24] = [
+        (0x0000000000000000, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ];
+
+    #[test]
+    fn test_rfc8785_numbers() {
+        for (bits, expected) in NUMBERS {
+            let value = f64::from_bits(bits);
+            assert_eq!(to_canonical_json(&value).unwrap(), expected, "{bits:#x}");
+        }
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(to_canonical_json(&42u64).unwrap(), "42");
+        assert_eq!(to_canonical_json(&-7i64).unwrap(), "-7");
+        assert_eq!(to_canonical_json(&1.0f64).unwrap(), "1");
+        assert_eq!(to_canonical_json(&1e21f64).unwrap(), "1e+21");
+        assert_eq!(
+            to_canonical_json(&1e20f64).unwrap(),
+            "100000000000000000000"
+        );
+        assert_eq!(to_canonical_json(&1e-7f64).unwrap(), "1e-7");
+        // Integers beyond 2^53 are rounded to the nearest double.
+        assert_eq!(
+            to_canonical_json(&9_007_199_254_740_993u64).unwrap(),
+            "9007199254740992"
+        );
+    }
+
+    // RFC 8785, section 3.2.2.
+    #[test]
+    fn test_rfc8785_sample() {
+        let value: Value = serde_json::from_str(
+            r#"{
+                "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+                "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+                "literals": [null, true, false]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_canonical_json(&value).unwrap(),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    // RFC 8785, section 3.2.3: members are sorted by UTF-16 code units.
+    #[test]
+    fn test_rfc8785_sorting() {
+        let value: Value = serde_json::from_str(
+            r#"{
+                "€": "Euro Sign",
+                "\r": "Carriage Return",
+                "דּ": "Hebrew Letter Dalet With Dagesh",
+                "1": "One",
+                "😀": "Emoji: Grinning Face",
+                "\u0080": "Control",
+                "ö": "Latin Small Letter O With Diaeresis"
+            }"#,
+        )
+        .unwrap();
+        let json = to_canonical_json(&value).unwrap();
+        let names: Vec<String> = json
+            .split(r#"":""#)
+            .map(|part| part.rsplit('"').next().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            &names[..7],
+            [
+                "\\r",
+                "1",
+                "\u{80}",
+                "\u{f6}",
+                "\u{20ac}",
+                "\u{1f600}",
+                "\u{fb33}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_values() {
+        let value = json!({"b": {"z": [], "y": {}}, "a": [{"d": 1, "c": 2}]});
+        assert_eq!(
+            to_canonical_json(&value).unwrap(),
+            r#"{"a":[{"c":2,"d":1}],"b":{"y":{},"z":[]}}"#
+        );
+    }
+
+    #[test]
+    fn test_claims_are_deterministic() {
+        let canonical = |order: &[usize]| {
+            let names = ["alpha", "beta", "gamma", "delta", "epsilon", "zeta"];
+            let mut claims = Claims::new();
+            for &i in order {
+                claims.set_claim_value(names[i], json!({"n": i as f64, "s": names[i]}));
+            }
+            claims.set_issuer("https://issuer.example");
+            claims.set_audience(vec!["api", "web"]);
+            claims.set_expiration(1_700_000_000);
+            (claims.to_canonical_json().unwrap(), claims.to_string())
+        };
+        let expected = canonical(&[0, 1, 2, 3, 4, 5]);
+        assert!(expected
+            .0
+            .starts_with(r#"{"alpha":{"n":0,"s":"alpha"},"aud":["api","web"],"#));
+        for order in [[5, 4, 3, 2, 1, 0], [2, 0, 4, 1, 5, 3]] {
+            assert_eq!(canonical(&order), expected);
+        }
+    }
+}
@@ -1,5 +1,4 @@
 #[cfg(test)]
-
 mod tests {
 
     extern crate cclm;
@@ -7,6 +6,8 @@ mod tests {
 
     use self::cclm::Claims;
     use self::dtt::DateTime;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
 
     #[test]
     fn test_claims() {
@@ -102,10 +103,12 @@ mod tests {
         claims.set_claim("aud", "MINI-FUNCTIONS-CLAIMS-AUD");
         claims.set_claim("custom", "MINI-FUNCTIONS-CLAIMS-CUSTOM");
         let retrieved_claims = claims.get_claims();
-        assert_eq!(
-            retrieved_claims.get("aud").unwrap(),
-            "MINI-FUNCTIONS-CLAIMS-AUD"
-        );
+        // Registered claims are held in their typed form
+        assert!(retrieved_claims.get("aud").is_none());
+        assert!(claims
+            .audience()
+            .unwrap()
+            .contains("MINI-FUNCTIONS-CLAIMS-AUD"));
         assert_eq!(
             retrieved_claims.get("custom").unwrap(),
             "MINI-FUNCTIONS-CLAIMS-CUSTOM"
@@ -121,6 +124,58 @@ mod tests {
         assert!(display_output.contains("custom: MINI-FUNCTIONS-CLAIMS-CUSTOM"));
     }
 
+    #[test]
+    fn test_nested_claim_values() {
+        let mut claims = Claims::new();
+        claims.set_claim_value(
+            "vc",
+            json!({
+                "@context": ["https://www.w3.org/2018/credentials/v1"],
+                "type": ["VerifiableCredential"],
+                "credentialSubject": { "degree": { "type": "BachelorDegree" } }
+            }),
+        );
+        claims.set_claim_value("admin", true);
+
+        let vc = claims.get_claim_value("vc").unwrap();
+        assert_eq!(vc["credentialSubject"]["degree"]["type"], "BachelorDegree");
+        assert!(claims.get_claim("vc").is_none());
+        assert_eq!(claims.get_claim_as::<bool>("admin").unwrap(), Some(true));
+
+        let json = serde_json::to_string(&claims).unwrap();
+        let decoded: Claims = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.get_claim_value("vc"), Some(vc));
+    }
+
+    #[test]
+    fn test_claim_as_struct() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Degree {
+            name: String,
+            year: u16,
+        }
+
+        let degree = Degree {
+            name: "Bachelor of Science".to_string(),
+            year: 2020,
+        };
+        let mut claims = Claims::new();
+        claims.set_claim_as("degree", &degree).unwrap();
+        assert_eq!(
+            claims.get_claim_as::<Degree>("degree").unwrap(),
+            Some(degree)
+        );
+        assert_eq!(claims.get_claim_as::<Degree>("missing").unwrap(), None);
+        assert!(claims.get_claim_as::<u16>("degree").is_err());
+    }
+
+    #[test]
+    fn test_display_non_string_values() {
+        let mut claims = Claims::new();
+        claims.set_claim_value("roles", json!(["admin", "user"]));
+        assert_eq!(format!("{claims}"), r#"Claims { roles: ["admin","user"] }"#);
+    }
+
     #[test]
     fn test_default_trait() {
         let claims = Claims::default();
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    extern crate cclm;
+    extern crate idk;
+
+    use self::cclm::{Claims, Did};
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_valid_dids() {
+        for (value, method, id) in [
+            (
+                "did:example:123456789abcdefghi",
+                "example",
+                "123456789abcdefghi",
+            ),
+            (
+                "did:web:w3c-ccg.github.io:user:alice",
+                "web",
+                "w3c-ccg.github.io:user:alice",
+            ),
+            (
+                "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp",
+                "key",
+                "z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp",
+            ),
+            ("did:example:%C3%A9t%C3%A9", "example", "%C3%A9t%C3%A9"),
+            ("did:example::a", "example", ":a"),
+            ("did:3:a_b-c.d", "3", "a_b-c.d"),
+        ] {
+            let did = Did::parse(value).unwrap();
+            assert_eq!(did.method(), method, "{value}");
+            assert_eq!(did.method_specific_id(), id, "{value}");
+            assert!(!did.is_url(), "{value}");
+            assert_eq!(did.to_string(), value);
+            assert_eq!(did.did(), value);
+        }
+    }
+
+    #[test]
+    fn test_parse_did_urls() {
+        let did: Did = "did:example:123?service=agent&relativeRef=/credentials#degree"
+            .parse()
+            .unwrap();
+        assert_eq!(did.method_specific_id(), "123");
+        assert_eq!(did.path(), None);
+        assert_eq!(did.query(), Some("service=agent&relativeRef=/credentials"));
+        assert_eq!(did.fragment(), Some("degree"));
+        assert!(did.is_url());
+        assert_eq!(did.did(), "did:example:123");
+
+        let did = Did::parse("did:example:123/path/to/rsrc").unwrap();
+        assert_eq!(did.path(), Some("/path/to/rsrc"));
+        assert_eq!(did.query(), None);
+
+        let did = Did::parse("did:example:123#").unwrap();
+        assert_eq!(did.fragment(), Some(""));
+        assert_eq!(did.to_string(), "did:example:123#");
+
+        for value in [
+            "did:example:123/path?query#fragment",
+            "did:example:123?versionTime=2021-05-10T17:00:00Z",
+            "did:example:123#key-1?not-a-query",
+        ] {
+            assert_eq!(Did::parse(value).unwrap().to_string(), value);
+        }
+
+        let key = Did::parse("did:example:123?service=files")
+            .unwrap()
+            .with_fragment("keys-1")
+            .unwrap();
+        assert_eq!(key.to_string(), "did:example:123#keys-1");
+        assert!(Did::parse("did:example:123")
+            .unwrap()
+            .with_fragment("a b")
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_dids() {
+        for value in [
+            "",
+            "did",
+            "did:",
+            "did:example",
+            "did:example:",
+            "did:example:123:",
+            "did::123",
+            "did:Example:123",
+            "did:ex-ample:123",
+            "DID:example:123",
+            "urn:example:123",
+            "did:example:12 3",
+            "did:example:é",
+            "did:example:%zz",
+            "did:example:%4",
+            "did:example:123/pa th",
+            "did:example:123?q=a b",
+            "did:example:123#frag ment",
+            "did:example:123#a#b",
+            "did:example:/path",
+        ] {
+            let err = Did::parse(value).unwrap_err();
+            assert_eq!(err.error_type.name, "format", "{value}");
+            assert!(err.message.contains("Invalid DID"), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_did_serde() {
+        let did = Did::parse("did:example:123#keys-1").unwrap();
+        assert_eq!(
+            serde_json::to_value(&did).unwrap(),
+            json!("did:example:123#keys-1")
+        );
+        assert_eq!(
+            serde_json::from_value::<Did>(json!("did:example:123#keys-1")).unwrap(),
+            did
+        );
+        assert!(serde_json::from_value::<Did>(json!("did:example")).is_err());
+        assert!(serde_json::from_value::<Did>(json!(123)).is_err());
+    }
+
+    #[test]
+    fn test_did_claim() {
+        let mut claims = Claims::new();
+        assert!(claims.did().unwrap().is_none());
+
+        let did = Did::parse("did:example:alice").unwrap();
+        claims.set_did(&did);
+        assert_eq!(claims.get_claim("did").unwrap(), "did:example:alice");
+        assert_eq!(claims.did().unwrap(), Some(did.clone()));
+        assert_eq!(claims.get_claim_as::<Did>("did").unwrap(), Some(did));
+
+        claims.set_claim("did", "did:example");
+        assert_eq!(claims.did().unwrap_err().error_type.name, "format");
+        claims.set_claim_value("did", 42);
+        assert_eq!(claims.did().unwrap_err().error_type.name, "format");
+    }
+}
@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod tests {
+    extern crate cclm;
+    extern crate serde_json;
+
+    use self::cclm::{Audience, Claims, NumericDate};
+    use self::serde_json::json;
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut claims = Claims::new();
+        claims.set_issuer("https://issuer.com");
+        claims.set_subject("user123");
+        claims.set_audience("https://example.com");
+        claims.set_expiration(NumericDate::from_seconds(1_700_000_600));
+        claims.set_not_before(1_700_000_000);
+        claims.set_issued_at(1_700_000_000);
+        claims.set_jwt_id("abc123");
+
+        assert_eq!(claims.issuer(), Some("https://issuer.com"));
+        assert_eq!(claims.subject(), Some("user123"));
+        assert_eq!(
+            claims.audience(),
+            Some(&Audience::Single("https://example.com".to_string()))
+        );
+        assert_eq!(claims.expiration().unwrap().seconds(), 1_700_000_600);
+        assert_eq!(claims.not_before().unwrap().seconds(), 1_700_000_000);
+        assert_eq!(claims.issued_at().unwrap().seconds(), 1_700_000_000);
+        assert_eq!(claims.jwt_id(), Some("abc123"));
+        assert_eq!(claims.len(), 7);
+    }
+
+    #[test]
+    fn test_serialize_emits_numbers_and_arrays() {
+        let mut claims = Claims::new();
+        claims.set_issuer("joe");
+        claims.set_audience(vec!["api", "web"]);
+        claims.set_expiration(1_300_819_380);
+        claims.set_claim("name", "John Doe");
+
+        let value = serde_json::to_value(&claims).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "iss": "joe",
+                "aud": ["api", "web"],
+                "exp": 1_300_819_380,
+                "name": "John Doe"
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_round_trip() {
+        let json = r#"{"iss":"joe","aud":["api","web"],"exp":1300819380,"nbf":1300819000.5,"http://example.com/is_root":"true"}"#;
+        let claims: Claims = serde_json::from_str(json).unwrap();
+
+        assert_eq!(claims.issuer(), Some("joe"));
+        assert!(claims.audience().unwrap().contains("web"));
+        assert_eq!(claims.expiration().unwrap().seconds(), 1_300_819_380);
+        assert_eq!(claims.not_before().unwrap().seconds(), 1_300_819_000);
+        assert_eq!(
+            claims.get_claim("http://example.com/is_root").unwrap(),
+            "true"
+        );
+
+        let round_trip: Claims =
+            serde_json::from_str(&serde_json::to_string(&claims).unwrap()).unwrap();
+        assert_eq!(round_trip.registered, claims.registered);
+        assert_eq!(round_trip.claims, claims.claims);
+    }
+
+    #[test]
+    fn test_deserialize_keeps_string_numeric_date_free_form() {
+        let json = r#"{"exp":"tomorrow"}"#;
+        let claims: Claims = serde_json::from_str(json).unwrap();
+        assert!(claims.expiration().is_none());
+        assert!(claims.get_claim_as::<NumericDate>("exp").is_err());
+    }
+
+    #[test]
+    fn test_numeric_date_from_ymd() {
+        for (year, month, day, seconds) in [
+            (1970, 1, 1, 0),
+            (2000, 3, 1, 951_868_800),
+            (2024, 2, 29, 1_709_164_800),
+            (1900, 3, 1, -2_203_891_200),
+            (-1, 12, 31, -62_167_305_600),
+        ] {
+            assert_eq!(
+                NumericDate::from_ymd(year, month, day).seconds(),
+                seconds,
+                "{year}-{month}-{day}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_accepts_numeric_string_date() {
+        let json = r#"{"exp":"1300819380"}"#;
+        let claims: Claims = serde_json::from_str(json).unwrap();
+        assert_eq!(claims.expiration().unwrap().seconds(), 1_300_819_380);
+    }
+
+    #[test]
+    fn test_set_claim_and_typed_setters_do_not_overlap() {
+        let mut claims = Claims::new();
+        claims.set_issuer("joe");
+        assert_eq!(claims.get_claim("iss").unwrap(), "joe");
+
+        // A registered claim set by name is held in its typed form.
+        claims.set_claim("iss", "jane");
+        assert_eq!(claims.issuer(), Some("jane"));
+        assert!(!claims.get_claims().contains_key("iss"));
+
+        // A value that is not valid for the claim stays free-form, and
+        // the typed value replaces it.
+        claims.set_claim("exp", "2023-01-01T00:00:00Z");
+        assert!(claims.expiration().is_none());
+        assert_eq!(claims.get_claim("exp").unwrap(), "2023-01-01T00:00:00Z");
+        claims.set_expiration(1_300_819_380);
+        assert!(!claims.get_claims().contains_key("exp"));
+        assert_eq!(claims.len(), 2);
+
+        let value = serde_json::to_value(&claims).unwrap();
+        assert_eq!(value, json!({ "iss": "jane", "exp": 1_300_819_380 }));
+    }
+
+    #[test]
+    fn test_set_claim_then_typed_getters() {
+        let mut claims = Claims::new();
+        claims.set_claim("sub", "user123");
+        claims.set_claim("jti", "id-1");
+        claims.set_claim("iat", "1300819370");
+        claims.set_claim_value("iss", "joe");
+        claims.set_claim_value("aud", json!(["api", "web"]));
+        claims.set_claim_value("exp", 1_300_819_380);
+        claims.set_claim_value("nbf", 1_300_819_370.5);
+        assert_eq!(claims.subject(), Some("user123"));
+        assert_eq!(claims.jwt_id(), Some("id-1"));
+        assert_eq!(claims.issuer(), Some("joe"));
+        assert!(claims.audience().unwrap().contains("web"));
+        assert_eq!(claims.expiration().unwrap().seconds(), 1_300_819_380);
+        assert_eq!(claims.not_before().unwrap().seconds(), 1_300_819_370);
+        assert_eq!(claims.issued_at().unwrap().seconds(), 1_300_819_370);
+        assert!(claims.get_claims().is_empty());
+        assert_eq!(claims.len(), 7);
+
+        // A value of the wrong type is kept free-form.
+        claims.set_claim_value("sub", 42);
+        assert!(claims.subject().is_none());
+        assert_eq!(claims.get_claim_value("sub"), Some(&json!(42)));
+        assert_eq!(claims.remove_claim("sub").unwrap(), "42");
+        assert_eq!(claims.remove_claim("iat").unwrap(), "1300819370");
+    }
+
+    #[test]
+    fn test_invalid_registered_claims_round_trip() {
+        let mut claims = Claims::new();
+        claims.set_claim("exp", "2023-01-01");
+        claims.set_claim_value("aud", 5);
+        claims.set_subject("user123");
+        let json = serde_json::to_string(&claims).unwrap();
+
+        let decoded: Claims = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.subject(), Some("user123"));
+        assert!(decoded.expiration().is_none());
+        assert!(decoded.audience().is_none());
+        assert_eq!(decoded.get_claim("exp").unwrap(), "2023-01-01");
+        assert_eq!(decoded.get_claim_value("aud"), Some(&json!(5)));
+        assert!(decoded.get_claim_as::<NumericDate>("exp").is_err());
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&claims).unwrap()
+        );
+
+        // Valid registered claims are still read into their typed form
+        let decoded: Claims =
+            serde_json::from_value(json!({"exp": "1300819380", "aud": ["a", "b"]})).unwrap();
+        assert_eq!(decoded.expiration().unwrap().seconds(), 1_300_819_380);
+        assert!(decoded.audience().unwrap().contains("b"));
+        assert!(decoded.get_claims().is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_clear_registered_claims() {
+        let mut claims = Claims::new();
+        claims.set_subject("user123");
+        claims.set_issued_at(1_300_819_380);
+        assert!(claims.has_claim("iat"));
+        assert_eq!(claims.remove_claim_value("iat"), Some(json!(1_300_819_380)));
+        assert!(!claims.has_claim("iat"));
+
+        claims.clear_claims();
+        assert!(claims.is_empty());
+        assert!(claims.subject().is_none());
+    }
+
+    #[test]
+    fn test_audience() {
+        let single = Audience::from("api");
+        let multiple = Audience::from(vec!["api", "web"]);
+        assert!(single.contains("api"));
+        assert_eq!(multiple.iter().count(), 2);
+        assert_eq!(multiple.to_string(), "api, web");
+    }
+
+    #[test]
+    fn test_numeric_date_now() {
+        let now = NumericDate::now();
+        assert!(now.seconds() > 1_600_000_000);
+        assert!(NumericDate::from_seconds(1) < now);
+    }
+}
@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod tests {
+    extern crate cclm;
+    extern crate idk;
+
+    use self::cclm::{
+        vc::{
+            CredentialSubject, Issuer, PresentedCredential, VerifiableCredential,
+            VerifiablePresentation, CREDENTIALS_CONTEXT,
+        },
+        Claims, NumericDate,
+    };
+    use serde_json::json;
+
+    // Example 1 of the W3C Verifiable Credentials Data Model, with a
+    // status entry and a proof added.
+    fn example() -> serde_json::Value {
+        json!({
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                "https://www.w3.org/2018/credentials/examples/v1"
+            ],
+            "id": "http://example.edu/credentials/1872",
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "issuer": {"id": "https://example.edu/issuers/565049", "name": "Example University"},
+            "issuanceDate": "2010-01-01T19:23:24Z",
+            "expirationDate": "2020-01-01T19:23:24Z",
+            "credentialSubject": {
+                "id": "did:example:ebfeb1f712ebc6f1c276e12ec21",
+                "alumniOf": {"id": "did:example:c276e12ec21ebfeb1f712ebc6f1", "name": "Example University"}
+            },
+            "credentialStatus": {
+                "id": "https://example.edu/status/24",
+                "type": "CredentialStatusList2017"
+            },
+            "credentialSchema": {"id": "https://example.org/examples/degree.json", "type": "JsonSchemaValidator2018"},
+            "proof": {
+                "type": "RsaSignature2018",
+                "created": "2017-06-18T21:19:10Z",
+                "proofPurpose": "assertionMethod",
+                "verificationMethod": "https://example.edu/issuers/565049#key-1",
+                "jws": "eyJhbGciOiJSUzI1NiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19..TCYt5X"
+            }
+        })
+    }
+
+    fn credential() -> VerifiableCredential {
+        serde_json::from_value(example()).unwrap()
+    }
+
+    #[test]
+    fn test_credential_model_round_trip() {
+        let credential = credential();
+        assert_eq!(credential.context.len(), 2);
+        assert_eq!(credential.types[1], "AlumniCredential");
+        assert_eq!(credential.issuer.id(), "https://example.edu/issuers/565049");
+        assert!(matches!(
+            &credential.issuer,
+            Issuer::Object { properties, .. } if properties["name"] == "Example University"
+        ));
+        assert_eq!(
+            credential.credential_subject.id.as_deref(),
+            Some("did:example:ebfeb1f712ebc6f1c276e12ec21")
+        );
+        assert!(credential
+            .credential_subject
+            .claims
+            .contains_key("alumniOf"));
+        let status = credential.credential_status.as_ref().unwrap();
+        assert_eq!(status.status_type, "CredentialStatusList2017");
+        let proof = credential.proof.as_ref().unwrap();
+        assert_eq!(proof.proof_type, "RsaSignature2018");
+        assert_eq!(proof.proof_purpose.as_deref(), Some("assertionMethod"));
+        assert!(credential.properties.contains_key("credentialSchema"));
+
+        assert_eq!(serde_json::to_value(&credential).unwrap(), example());
+    }
+
+    #[test]
+    fn test_credential_to_claims() {
+        let claims = credential().to_claims().unwrap();
+        assert_eq!(claims.issuer(), Some("https://example.edu/issuers/565049"));
+        assert_eq!(
+            claims.subject(),
+            Some("did:example:ebfeb1f712ebc6f1c276e12ec21")
+        );
+        assert_eq!(claims.jwt_id(), Some("http://example.edu/credentials/1872"));
+        assert_eq!(
+            claims.not_before(),
+            Some(NumericDate::from_seconds(1_262_373_804))
+        );
+        assert_eq!(
+            claims.expiration(),
+            Some(NumericDate::from_seconds(1_577_906_604))
+        );
+        assert_eq!(claims.get_claim_value("vc"), Some(&example()));
+        assert!(VerifiableCredential::check_claims(&claims).is_ok());
+        assert_eq!(
+            VerifiableCredential::from_claims(&claims).unwrap(),
+            credential()
+        );
+    }
+
+    #[test]
+    fn test_credential_from_compact_claims() {
+        // The vc claim of a JWT-VC may leave out the properties carried
+        // by the registered claims.
+        let mut claims = Claims::new();
+        claims.set_issuer("did:example:issuer");
+        claims.set_subject("did:example:alice");
+        claims.set_not_before(1_700_000_000);
+        claims.set_expiration(1_700_086_400);
+        claims.set_jwt_id("urn:uuid:3978344f-8596-4c3a-a978-8fcaba3903c5");
+        claims.set_claim_value(
+            "vc",
+            json!({
+                "@context": [CREDENTIALS_CONTEXT],
+                "type": ["VerifiableCredential"],
+                "credentialSubject": {"over18": true}
+            }),
+        );
+
+        let credential = VerifiableCredential::from_claims(&claims).unwrap();
+        assert_eq!(credential.issuer, Issuer::from("did:example:issuer"));
+        assert_eq!(
+            credential.credential_subject.id.as_deref(),
+            Some("did:example:alice")
+        );
+        assert_eq!(credential.issuance_date, "2023-11-14T22:13:20Z");
+        assert_eq!(
+            credential.expiration_date.as_deref(),
+            Some("2023-11-15T22:13:20Z")
+        );
+        assert_eq!(
+            credential.id.as_deref(),
+            Some("urn:uuid:3978344f-8596-4c3a-a978-8fcaba3903c5")
+        );
+        assert!(VerifiableCredential::check_claims(&claims).is_ok());
+    }
+
+    #[test]
+    fn test_check_claims_rejects_mismatches() {
+        let claims = credential().to_claims().unwrap();
+
+        let mut other_issuer = claims.clone();
+        other_issuer.set_issuer("https://attacker.example");
+        let mut other_subject = claims.clone();
+        other_subject.set_subject("did:example:mallory");
+        let mut no_subject = claims.clone();
+        no_subject.remove_claim("sub");
+        let mut no_issuer = claims.clone();
+        no_issuer.remove_claim("iss");
+
+        for claims in [other_issuer, other_subject, no_subject, no_issuer] {
+            let err = VerifiableCredential::check_claims(&claims).unwrap_err();
+            assert_eq!(err.error_type.name, "rejection", "{}", err.message);
+        }
+
+        let mut no_vc = claims;
+        no_vc.remove_claim("vc");
+        let err = VerifiableCredential::check_claims(&no_vc).unwrap_err();
+        assert_eq!(err.error_type.name, "format");
+        no_vc.set_claim("vc", "not an object");
+        assert!(VerifiableCredential::from_claims(&no_vc).is_err());
+    }
+
+    #[test]
+    fn test_issuance_dates() {
+        let subject = CredentialSubject::default();
+        for (date, seconds) in [
+            ("1970-01-01T00:00:00Z", 0),
+            ("2023-11-14T22:13:20Z", 1_700_000_000),
+            ("2023-11-14t22:13:20.987z", 1_700_000_000),
+            ("2023-11-15T00:13:20+02:00", 1_700_000_000),
+            ("2023-11-14T20:43:20-01:30", 1_700_000_000),
+            ("2000-02-29T12:00:00Z", 951_825_600),
+            ("1969-12-31T23:59:59Z", -1),
+        ] {
+            let credential = VerifiableCredential::new("did:example:issuer", date, subject.clone());
+            let claims = credential.to_claims().unwrap();
+            assert_eq!(claims.not_before().unwrap().seconds(), seconds, "{date}");
+        }
+
+        for date in [
+            "2023-11-14",
+            "2023-11-14T22:13:20",
+            "2023-02-29T00:00:00Z",
+            "2023-13-01T00:00:00Z",
+            "2023-11-14T24:00:00Z",
+            "2023-11-14T22:13:20.Z",
+            "2023-11-14T22:13:20+0200",
+            "2023-11-14 22:13:20Z",
+        ] {
+            let credential = VerifiableCredential::new("did:example:issuer", date, subject.clone());
+            let err = credential.to_claims().unwrap_err();
+            assert_eq!(err.error_type.name, "format", "{date}");
+        }
+    }
+
+    #[test]
+    fn test_presentation_claims() {
+        let presentation = VerifiablePresentation::new(
+            "did:example:holder",
+            vec![
+                PresentedCredential::Jwt("eyJhbGciOiJFZERTQSJ9.e30.c2ln".to_string()),
+                PresentedCredential::Embedded(Box::new(credential())),
+            ],
+        );
+        let claims = presentation.to_claims().unwrap();
+        assert_eq!(claims.issuer(), Some("did:example:holder"));
+        assert_eq!(
+            claims.get_claim_value("vp").unwrap()["verifiableCredential"][0],
+            "eyJhbGciOiJFZERTQSJ9.e30.c2ln"
+        );
+        assert!(VerifiablePresentation::check_claims(&claims).is_ok());
+        assert_eq!(
+            VerifiablePresentation::from_claims(&claims).unwrap(),
+            presentation
+        );
+
+        let mut other_holder = claims;
+        other_holder.set_issuer("did:example:mallory");
+        let err = VerifiablePresentation::check_claims(&other_holder).unwrap_err();
+        assert_eq!(err.error_type.name, "rejection");
+    }
+}
@@ -21,13 +21,12 @@ fn bench_decode_benchmark(c: &mut Criterion) {
     let claims = Claims::default();
 
     let token = JWT::encode(header, claims, secret).unwrap();
-    let mut jwt =
-        JWT {
-            header: Header::default(),
-            claims: Claims::default(),
-            signature: vec![],
-            token,
-        };
+    let mut jwt = JWT {
+        header: Header::default(),
+        claims: Claims::default(),
+        signature: vec![],
+        token,
+    };
 
     c.bench_function("decode", move |b| b.iter(|| jwt.decode(secret)));
 }
@@ -77,6 +76,7 @@ fn bench_get_token_header_benchmark(c: &mut Criterion) {
                 kid: Some("example_kid".to_string()),
                 typ: Some("example_type".to_string()),
                 cty: Some("example_cty".to_string()),
+                ..Header::default()
             },
             claims: Claims::default(),
             signature: vec![],
@@ -110,6 +110,7 @@ fn bench_validate_benchmark(c: &mut Criterion) {
             kid: Some("example_kid".to_string()),
             typ: Some("example_type".to_string()),
             cty: Some("example_cty".to_string()),
+            ..Header::default()
         },
         claims: Claims::default(),
         signature: vec![],
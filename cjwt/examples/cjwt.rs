@@ -17,10 +17,10 @@ fn main() {
 
     // Create a Header struct with default method and replace values.
     let mut hdrv: Header = Header::default();
-    Header::default().alg = std::mem::replace(&mut hdrv.alg, Some(HD_ALG));
-    Header::default().kid = std::mem::replace(&mut hdrv.kid, Some(HD_KID.to_string()));
-    Header::default().typ = std::mem::replace(&mut hdrv.typ, Some(HD_TYP.to_string()));
-    Header::default().cty = std::mem::replace(&mut hdrv.cty, Some(HD_CTY.to_string()));
+    Header::default().alg = hdrv.alg.replace(HD_ALG);
+    Header::default().kid = hdrv.kid.replace(HD_KID.to_string());
+    Header::default().typ = hdrv.typ.replace(HD_TYP.to_string());
+    Header::default().cty = hdrv.cty.replace(HD_CTY.to_string());
     println!("🦀 Header::default():             ✅ {hdrv:?}\n");
 
     // Create a JWT struct with default method.
@@ -29,10 +29,10 @@ fn main() {
 
     // Create a JWT struct with default method and replace values.
     let mut jdrv: JWT = JWT::default();
-    JWT::default().header.alg = std::mem::replace(&mut jdrv.header.alg, Some(HD_ALG));
-    JWT::default().header.kid = std::mem::replace(&mut jdrv.header.kid, Some(HD_KID.to_string()));
-    JWT::default().header.typ = std::mem::replace(&mut jdrv.header.typ, Some(HD_TYP.to_string()));
-    JWT::default().header.cty = std::mem::replace(&mut jdrv.header.cty, Some(HD_CTY.to_string()));
+    JWT::default().header.alg = jdrv.header.alg.replace(HD_ALG);
+    JWT::default().header.kid = jdrv.header.kid.replace(HD_KID.to_string());
+    JWT::default().header.typ = jdrv.header.typ.replace(HD_TYP.to_string());
+    JWT::default().header.cty = jdrv.header.cty.replace(HD_CTY.to_string());
     println!("🦀 JWT::default():                ✅ {jdrv:?}\n");
 
     // Encode a JWT struct.
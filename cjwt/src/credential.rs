@@ -0,0 +1,105 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{Header, Signer, Validation, Verifier, JWT};
+use cclm::vc::{VerifiableCredential, VerifiablePresentation};
+use idk::jwt::JwtError;
+
+impl JWT {
+    /// Issues `credential` as a JWT-VC signed with `key`.
+    ///
+    /// The `iss`, `sub`, `nbf`, `exp` and `jti` claims are set from the
+    /// issuer, subject id, issuance date, expiration date and id of the
+    /// credential, which is held in the `vc` claim.
+    ///
+    /// Returns `JwtError::InvalidPayload` if a date of the credential is
+    /// not a valid `dateTime`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cclm::vc::{CredentialSubject, VerifiableCredential};
+    /// use cjwt::{Algorithm, EdDsaKey, Header, Validation, JWT};
+    ///
+    /// let key = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+    /// let credential = VerifiableCredential::new(
+    ///     "did:example:issuer",
+    ///     "2023-11-14T22:13:20Z",
+    ///     CredentialSubject::new("did:example:alice").with_claim("over18", true),
+    /// );
+    /// let header = Header { alg: Some(Algorithm::EdDSA), ..Header::default() };
+    /// let token = JWT::issue_credential(header, &credential, &key).unwrap();
+    ///
+    /// let verified = JWT::verify_credential(&token, &key, &Validation::default()).unwrap();
+    /// assert_eq!(verified, credential);
+    /// ```
+    pub fn issue_credential<K: Signer + ?Sized>(
+        header: Header,
+        credential: &VerifiableCredential,
+        key: &K,
+    ) -> Result<String, JwtError> {
+        let claims = credential.to_claims().map_err(payload_error)?;
+        JWT::encode_with_key(header, claims, key)
+    }
+
+    /// Verifies the JWT-VC `token` with `key`, validates its claims
+    /// against `validation` and returns the credential it holds.
+    ///
+    /// Returns `JwtError::InvalidPayload` if the `vc` claim is missing
+    /// or malformed, if the `iss` claim does not match the issuer of
+    /// the credential, or if the `sub` claim does not match the id of
+    /// its subject.
+    pub fn verify_credential<K: Verifier + ?Sized>(
+        token: &str,
+        key: &K,
+        validation: &Validation,
+    ) -> Result<VerifiableCredential, JwtError> {
+        let mut jwt = JWT {
+            token: token.to_string(),
+            ..JWT::default()
+        };
+        jwt.decode_with_validation(key, validation)?;
+        VerifiableCredential::check_claims(&jwt.claims).map_err(payload_error)?;
+        VerifiableCredential::from_claims(&jwt.claims).map_err(payload_error)
+    }
+
+    /// Issues `presentation` as a JWT-VP signed with `key`.
+    ///
+    /// The `iss` and `jti` claims are set from the holder and id of the
+    /// presentation, which is held in the `vp` claim.
+    pub fn issue_presentation<K: Signer + ?Sized>(
+        header: Header,
+        presentation: &VerifiablePresentation,
+        key: &K,
+    ) -> Result<String, JwtError> {
+        let claims = presentation.to_claims().map_err(payload_error)?;
+        JWT::encode_with_key(header, claims, key)
+    }
+
+    /// Verifies the JWT-VP `token` with `key`, validates its claims
+    /// against `validation` and returns the presentation it holds.
+    ///
+    /// The credentials presented are returned as they are and must be
+    /// verified separately, for example with
+    /// [`JWT::verify_credential`]. Returns `JwtError::InvalidPayload` if
+    /// the `vp` claim is missing or malformed, or if the `iss` claim
+    /// does not match the holder of the presentation.
+    pub fn verify_presentation<K: Verifier + ?Sized>(
+        token: &str,
+        key: &K,
+        validation: &Validation,
+    ) -> Result<VerifiablePresentation, JwtError> {
+        let mut jwt = JWT {
+            token: token.to_string(),
+            ..JWT::default()
+        };
+        jwt.decode_with_validation(key, validation)?;
+        VerifiablePresentation::check_claims(&jwt.claims).map_err(payload_error)?;
+        VerifiablePresentation::from_claims(&jwt.claims).map_err(payload_error)
+    }
+}
+
+fn payload_error(err: idk::error::Error) -> JwtError {
+    JwtError::InvalidPayload(err.message)
+}
@@ -0,0 +1,314 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{header_algorithm, Algorithm, EcdsaKey, EdDsaKey, Jwk, JwkKey, Validation, JWT};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use cclm::Did;
+use idk::jwt::JwtError;
+use std::fmt;
+
+/// The base58btc alphabet used by multibase values starting with `z`.
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The multicodec prefixes of the public keys supported by `did:key`,
+/// with the algorithm they are used with.
+const MULTICODECS: [(&[u8], Algorithm); 4] = [
+    (&[0xed, 0x01], Algorithm::EdDSA),
+    (&[0x80, 0x24], Algorithm::ES256),
+    (&[0x81, 0x24], Algorithm::ES384),
+    (&[0x82, 0x24], Algorithm::ES512),
+];
+
+/// A source of the verification keys of Decentralized Identifiers.
+pub trait DidResolver: fmt::Debug + Send + Sync {
+    /// Returns the public verification key of `did`.
+    ///
+    /// When `did` is a DID URL, its fragment must name the
+    /// verification method of the key. Returns `JwtError::KeyInvalid`
+    /// if the DID cannot be resolved to a key.
+    fn resolve(&self, did: &Did) -> Result<Jwk, JwtError>;
+}
+
+/// Resolves `did:key` DIDs, whose method-specific id is the multibase
+/// encoded public key, without any network access.
+///
+/// Ed25519, P-256, P-384 and P-521 keys are supported. The key id of the
+/// resolved JWK is the DID URL of its verification method.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{DidKeyResolver, DidResolver, EdDsaKey, Jwk};
+///
+/// let key = EdDsaKey::from_secret_bytes(&[0; 32]).unwrap();
+/// let did = DidKeyResolver::did(&Jwk::from(&key)).unwrap();
+/// assert_eq!(
+///     did.to_string(),
+///     "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp"
+/// );
+///
+/// let jwk = DidKeyResolver.resolve(&did).unwrap();
+/// assert_eq!(jwk.crv.as_deref(), Some("Ed25519"));
+/// assert!(!jwk.is_private());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DidKeyResolver;
+
+impl DidKeyResolver {
+    /// Returns the `did:key` DID of the public key of `jwk`.
+    ///
+    /// Returns `JwtError::KeyInvalid` if the key type is not supported.
+    pub fn did(jwk: &Jwk) -> Result<Did, JwtError> {
+        let unsupported = || JwtError::KeyInvalid(format!("did:key does not support {}", jwk.kty));
+        let public = jwk.to_public();
+        let (prefix, algorithm) = MULTICODECS
+            .iter()
+            .find(|(_, algorithm)| public.supports(*algorithm))
+            .ok_or_else(unsupported)?;
+
+        let mut bytes = prefix.to_vec();
+        match public.to_key(*algorithm)? {
+            JwkKey::EdDsa(key) => bytes.extend(key.public_key_bytes()),
+            JwkKey::Ecdsa(key) => {
+                // did:key uses compressed points: the parity of y, then x
+                let point = key.public_key_bytes();
+                let size = (point.len() - 1) / 2;
+                bytes.push(0x02 | (point[point.len() - 1] & 1));
+                bytes.extend(&point[1..=size]);
+            }
+            _ => return Err(unsupported()),
+        }
+        parse_did(&format!("did:key:z{}", base58_encode(&bytes)))
+    }
+}
+
+impl DidResolver for DidKeyResolver {
+    fn resolve(&self, did: &Did) -> Result<Jwk, JwtError> {
+        check_method(did, "key")?;
+        let id = did.method_specific_id();
+        check_fragment(did, id)?;
+
+        let bytes = id
+            .strip_prefix('z')
+            .and_then(base58_decode)
+            .ok_or_else(|| JwtError::KeyInvalid(format!("{did} is not a base58btc multibase")))?;
+        let (prefix, algorithm) = MULTICODECS
+            .iter()
+            .find(|(prefix, _)| bytes.starts_with(prefix))
+            .ok_or_else(|| JwtError::KeyInvalid(format!("{did} has an unsupported key type")))?;
+        let public = &bytes[prefix.len()..];
+        let mut jwk = match algorithm {
+            Algorithm::EdDSA => Jwk::from(&EdDsaKey::from_public_bytes(public)?),
+            _ => Jwk::from(&EcdsaKey::from_public_bytes(*algorithm, public)?),
+        };
+        jwk.kid = Some(format!("{}#{id}", did.did()));
+        Ok(jwk)
+    }
+}
+
+/// Resolves `did:jwk` DIDs, whose method-specific id is the base64url
+/// encoded public JWK, without any network access.
+///
+/// The key id of the resolved JWK is the DID URL of its verification
+/// method, `#0`.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{DidJwkResolver, DidResolver, EcdsaKey, Jwk, Algorithm};
+///
+/// let key = EcdsaKey::from_secret_bytes(Algorithm::ES256, &[7; 32]).unwrap();
+/// let did = DidJwkResolver::did(&Jwk::from(&key)).unwrap();
+/// assert!(did.to_string().starts_with("did:jwk:eyJ"));
+///
+/// let jwk = DidJwkResolver.resolve(&did).unwrap();
+/// assert_eq!(jwk.kid, Some(format!("{did}#0")));
+/// assert_eq!(jwk.x, Jwk::from(&key).x);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DidJwkResolver;
+
+impl DidJwkResolver {
+    /// Returns the `did:jwk` DID of the public key of `jwk`.
+    pub fn did(jwk: &Jwk) -> Result<Did, JwtError> {
+        let json = jwk.to_public().to_json()?;
+        parse_did(&format!("did:jwk:{}", URL_SAFE_NO_PAD.encode(json)))
+    }
+}
+
+impl DidResolver for DidJwkResolver {
+    fn resolve(&self, did: &Did) -> Result<Jwk, JwtError> {
+        check_method(did, "jwk")?;
+        check_fragment(did, "0")?;
+        let json = URL_SAFE_NO_PAD
+            .decode(did.method_specific_id())
+            .map_err(|err| JwtError::KeyInvalid(format!("{did} is not base64url: {err}")))?;
+        let json = String::from_utf8(json)
+            .map_err(|_| JwtError::KeyInvalid(format!("{did} does not hold a JWK")))?;
+        let mut jwk = Jwk::from_json(&json)?;
+        if jwk.is_private() {
+            return Err(JwtError::KeyInvalid(format!(
+                "{} holds a private key",
+                did.did()
+            )));
+        }
+        jwk.kid = Some(format!("{}#0", did.did()));
+        Ok(jwk)
+    }
+}
+
+/// Resolves `did:key` and `did:jwk` DIDs without any network access.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OfflineDidResolver;
+
+impl DidResolver for OfflineDidResolver {
+    fn resolve(&self, did: &Did) -> Result<Jwk, JwtError> {
+        match did.method() {
+            "key" => DidKeyResolver.resolve(did),
+            "jwk" => DidJwkResolver.resolve(did),
+            method => Err(JwtError::KeyInvalid(format!(
+                "DID method {method} cannot be resolved offline"
+            ))),
+        }
+    }
+}
+
+impl JWT {
+    /// Decodes a JWT token issued by a DID, verifies its signature with
+    /// the key `resolver` returns for the DID and checks its claims
+    /// against `validation`.
+    ///
+    /// The `iss` claim must be a DID. When the `kid` header parameter is
+    /// a DID URL, it must belong to that DID and selects the
+    /// verification method; a `kid` that is a bare fragment such as
+    /// `#0` is taken relative to the DID. Returns
+    /// `JwtError::IssuerInvalid` if `iss` is missing or not a DID,
+    /// `JwtError::InvalidHeader` if `kid` belongs to another DID, and
+    /// `JwtError::KeyInvalid` if the DID cannot be resolved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, DidKeyResolver, EdDsaKey, Header, Jwk, OfflineDidResolver, Validation, JWT};
+    ///
+    /// let key = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+    /// let did = DidKeyResolver::did(&Jwk::from(&key)).unwrap();
+    /// let mut claims = JWT::claims();
+    /// claims.set_issuer(did.to_string());
+    /// let header = Header { alg: Some(Algorithm::EdDSA), ..Header::default() };
+    /// let token = JWT::encode_with_key(header, claims, &key).unwrap();
+    ///
+    /// let mut jwt = JWT { token, ..JWT::default() };
+    /// assert!(jwt.decode_with_did(&OfflineDidResolver, &Validation::default()).is_ok());
+    /// ```
+    pub fn decode_with_did(
+        &mut self,
+        resolver: &dyn DidResolver,
+        validation: &Validation,
+    ) -> Result<String, JwtError> {
+        let header = JWT::decode_header(&self.token)?;
+        let alg = header_algorithm(&header)?;
+        let claims = JWT::insecure_decode(&self.token)?.claims;
+        let iss = claims
+            .issuer()
+            .ok_or_else(|| JwtError::IssuerInvalid("The iss claim is missing".to_string()))?;
+        let issuer = Did::parse(iss)
+            .map_err(|_| JwtError::IssuerInvalid(format!("The iss claim {iss} is not a DID")))?;
+
+        let did = match header.kid.as_deref() {
+            Some(kid) if kid.starts_with('#') => issuer
+                .with_fragment(&kid[1..])
+                .map_err(|_| JwtError::InvalidHeader(format!("Invalid kid {kid}")))?,
+            Some(kid) if kid.starts_with("did:") => {
+                let did = parse_did(kid)
+                    .map_err(|_| JwtError::InvalidHeader(format!("Invalid kid {kid}")))?;
+                if did.did() != issuer.did() {
+                    return Err(JwtError::InvalidHeader(format!(
+                        "The kid {kid} does not belong to the issuer {iss}"
+                    )));
+                }
+                did
+            }
+            _ => issuer,
+        };
+        let key = resolver.resolve(&did)?.to_key(alg)?;
+        self.decode_with_validation(&key, validation)
+    }
+}
+
+fn parse_did(value: &str) -> Result<Did, JwtError> {
+    Did::parse(value).map_err(|err| JwtError::KeyInvalid(err.message))
+}
+
+fn check_method(did: &Did, method: &str) -> Result<(), JwtError> {
+    if did.method() != method {
+        return Err(JwtError::KeyInvalid(format!(
+            "{did} is not a did:{method} DID"
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that a DID URL names the only verification method of a DID.
+fn check_fragment(did: &Did, fragment: &str) -> Result<(), JwtError> {
+    if did.path().is_some() || did.query().is_some() {
+        return Err(JwtError::KeyInvalid(format!(
+            "{did} must not have a path or query"
+        )));
+    }
+    match did.fragment() {
+        Some(found) if found != fragment => Err(JwtError::KeyInvalid(format!(
+            "{did} has no verification method {found}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    // Little-endian base58 digits of the big-endian number `bytes`
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in &bytes[zeros..] {
+        let mut carry = u32::from(byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    std::iter::repeat('1')
+        .take(zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&digit| char::from(BASE58_ALPHABET[usize::from(digit)])),
+        )
+        .collect()
+}
+
+fn base58_decode(value: &str) -> Option<Vec<u8>> {
+    let zeros = value.bytes().take_while(|&b| b == b'1').count();
+    // Little-endian bytes of the number `value`
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in value.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in &mut bytes {
+            carry += u32::from(*byte) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+    let mut decoded = vec![0; zeros];
+    decoded.extend(bytes.iter().rev());
+    Some(decoded)
+}
@@ -0,0 +1,311 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{signature_invalid, Algorithm, Signer, Verifier};
+use idk::jwt::JwtError;
+use p256::{
+    ecdsa::signature::{Signer as _, Verifier as _},
+    elliptic_curve::{
+        pkcs8::{AssociatedOid, DecodePrivateKey, DecodePublicKey},
+        sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+        AffinePoint, CurveArithmetic, FieldBytesSize, PublicKey, SecretKey,
+    },
+};
+use std::fmt;
+
+/// An elliptic curve key bound to one of the ECDSA algorithms: ES256
+/// (P-256), ES384 (P-384) or ES512 (P-521).
+///
+/// Signatures use the JOSE encoding of RFC 7518, section 3.4: the
+/// fixed-size big-endian `r` and `s` values concatenated, not ASN.1
+/// DER. A key loaded from a private key can both sign and verify; a
+/// key loaded from a public key can only verify.
+///
+/// # Example
+///
+/// ```no_run
+/// use cjwt::{Algorithm, EcdsaKey, Signer, Verifier};
+/// let pem = std::fs::read_to_string("private.pem").unwrap();
+/// let key = EcdsaKey::from_pem(Algorithm::ES256, &pem).unwrap();
+/// let signature = key.sign(b"header.claims").unwrap();
+/// assert_eq!(signature.len(), 64);
+/// assert!(key.to_public().verify(b"header.claims", &signature).is_ok());
+/// ```
+#[derive(Clone)]
+pub struct EcdsaKey {
+    algorithm: Algorithm,
+    pair: KeyPair,
+}
+
+/// The curve specific signing and verifying keys.
+#[derive(Clone)]
+enum KeyPair {
+    P256(Option<p256::ecdsa::SigningKey>, p256::ecdsa::VerifyingKey),
+    P384(Option<p384::ecdsa::SigningKey>, p384::ecdsa::VerifyingKey),
+    P521(Option<p521::ecdsa::SigningKey>, p521::ecdsa::VerifyingKey),
+}
+
+impl EcdsaKey {
+    /// Creates a key for `algorithm` from a PEM document.
+    ///
+    /// Accepts PKCS#8 (`PRIVATE KEY`) and SEC1 (`EC PRIVATE KEY`)
+    /// private keys as well as SubjectPublicKeyInfo (`PUBLIC KEY`)
+    /// public keys. The key must be on the curve required by
+    /// `algorithm`.
+    pub fn from_pem(algorithm: Algorithm, pem: &str) -> Result<EcdsaKey, JwtError> {
+        let pair = match algorithm {
+            Algorithm::ES256 => {
+                let (secret, public) = parse_pem::<p256::NistP256>(pem)?;
+                KeyPair::P256(secret.map(Into::into), public.into())
+            }
+            Algorithm::ES384 => {
+                let (secret, public) = parse_pem::<p384::NistP384>(pem)?;
+                KeyPair::P384(secret.map(Into::into), public.into())
+            }
+            Algorithm::ES512 => {
+                let (secret, public) = parse_pem::<p521::NistP521>(pem)?;
+                p521_pair(secret, public)?
+            }
+            _ => return Err(not_ecdsa(algorithm)),
+        };
+        Ok(EcdsaKey { algorithm, pair })
+    }
+
+    /// Creates a key for `algorithm` from a DER document.
+    ///
+    /// Accepts the same PKCS#8, SEC1 and SubjectPublicKeyInfo
+    /// structures as [`EcdsaKey::from_pem`].
+    pub fn from_der(algorithm: Algorithm, der: &[u8]) -> Result<EcdsaKey, JwtError> {
+        let pair = match algorithm {
+            Algorithm::ES256 => {
+                let (secret, public) = parse_der::<p256::NistP256>(der)?;
+                KeyPair::P256(secret.map(Into::into), public.into())
+            }
+            Algorithm::ES384 => {
+                let (secret, public) = parse_der::<p384::NistP384>(der)?;
+                KeyPair::P384(secret.map(Into::into), public.into())
+            }
+            Algorithm::ES512 => {
+                let (secret, public) = parse_der::<p521::NistP521>(der)?;
+                p521_pair(secret, public)?
+            }
+            _ => return Err(not_ecdsa(algorithm)),
+        };
+        Ok(EcdsaKey { algorithm, pair })
+    }
+
+    /// Creates a signing key for `algorithm` from the big-endian bytes
+    /// of the private scalar (the JWK `d` parameter).
+    pub fn from_secret_bytes(algorithm: Algorithm, bytes: &[u8]) -> Result<EcdsaKey, JwtError> {
+        let pair = match algorithm {
+            Algorithm::ES256 => {
+                let secret = p256::ecdsa::SigningKey::from_slice(bytes).map_err(key_error)?;
+                let public = *secret.verifying_key();
+                KeyPair::P256(Some(secret), public)
+            }
+            Algorithm::ES384 => {
+                let secret = p384::ecdsa::SigningKey::from_slice(bytes).map_err(key_error)?;
+                let public = *secret.verifying_key();
+                KeyPair::P384(Some(secret), public)
+            }
+            Algorithm::ES512 => {
+                let secret = p521::ecdsa::SigningKey::from_slice(bytes).map_err(key_error)?;
+                let public = p521::ecdsa::VerifyingKey::from(&secret);
+                KeyPair::P521(Some(secret), public)
+            }
+            _ => return Err(not_ecdsa(algorithm)),
+        };
+        Ok(EcdsaKey { algorithm, pair })
+    }
+
+    /// Creates a verification key for `algorithm` from a SEC1 encoded
+    /// curve point, either compressed or uncompressed.
+    pub fn from_public_bytes(algorithm: Algorithm, bytes: &[u8]) -> Result<EcdsaKey, JwtError> {
+        let pair = match algorithm {
+            Algorithm::ES256 => KeyPair::P256(
+                None,
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(bytes).map_err(key_error)?,
+            ),
+            Algorithm::ES384 => KeyPair::P384(
+                None,
+                p384::ecdsa::VerifyingKey::from_sec1_bytes(bytes).map_err(key_error)?,
+            ),
+            Algorithm::ES512 => KeyPair::P521(
+                None,
+                p521::ecdsa::VerifyingKey::from_sec1_bytes(bytes).map_err(key_error)?,
+            ),
+            _ => return Err(not_ecdsa(algorithm)),
+        };
+        Ok(EcdsaKey { algorithm, pair })
+    }
+
+    /// Returns the public key as an uncompressed SEC1 curve point
+    /// (`0x04 || x || y`).
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        match &self.pair {
+            KeyPair::P256(_, public) => public.to_encoded_point(false).as_bytes().to_vec(),
+            KeyPair::P384(_, public) => public.to_encoded_point(false).as_bytes().to_vec(),
+            KeyPair::P521(_, public) => public.to_encoded_point(false).as_bytes().to_vec(),
+        }
+    }
+
+    /// Returns a copy of this key without the private part.
+    pub fn to_public(&self) -> EcdsaKey {
+        let pair = match &self.pair {
+            KeyPair::P256(_, public) => KeyPair::P256(None, *public),
+            KeyPair::P384(_, public) => KeyPair::P384(None, *public),
+            KeyPair::P521(_, public) => KeyPair::P521(None, public.clone()),
+        };
+        EcdsaKey {
+            algorithm: self.algorithm,
+            pair,
+        }
+    }
+
+    /// Returns `true` if this key holds a private key and can sign.
+    pub fn is_private(&self) -> bool {
+        match &self.pair {
+            KeyPair::P256(secret, _) => secret.is_some(),
+            KeyPair::P384(secret, _) => secret.is_some(),
+            KeyPair::P521(secret, _) => secret.is_some(),
+        }
+    }
+
+    /// Returns the big-endian bytes of the private scalar, if any.
+    pub(crate) fn secret_bytes(&self) -> Option<Vec<u8>> {
+        match &self.pair {
+            KeyPair::P256(secret, _) => secret.as_ref().map(|secret| secret.to_bytes().to_vec()),
+            KeyPair::P384(secret, _) => secret.as_ref().map(|secret| secret.to_bytes().to_vec()),
+            KeyPair::P521(secret, _) => secret.as_ref().map(|secret| secret.to_bytes().to_vec()),
+        }
+    }
+}
+
+impl fmt::Debug for EcdsaKey {
+    /// Formats the key without revealing the private key.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EcdsaKey")
+            .field("algorithm", &self.algorithm)
+            .field("private", &self.is_private())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the error for an `algorithm` that is not an ECDSA
+/// algorithm.
+fn not_ecdsa(algorithm: Algorithm) -> JwtError {
+    JwtError::InvalidHeader(format!("{algorithm} is not an ECDSA algorithm"))
+}
+
+/// Converts a key decoding error into a `JwtError::KeyInvalid`.
+fn key_error<E: fmt::Display>(err: E) -> JwtError {
+    JwtError::KeyInvalid(err.to_string())
+}
+
+/// Decodes a PKCS#8 or SEC1 private key, or an SPKI public key, on
+/// curve `C` from PEM.
+fn parse_pem<C>(pem: &str) -> Result<(Option<SecretKey<C>>, PublicKey<C>), JwtError>
+where
+    C: AssociatedOid + CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    if let Ok(secret) =
+        SecretKey::<C>::from_pkcs8_pem(pem).or_else(|_| SecretKey::from_sec1_pem(pem))
+    {
+        let public = secret.public_key();
+        return Ok((Some(secret), public));
+    }
+    let public = PublicKey::<C>::from_public_key_pem(pem)
+        .map_err(|_| JwtError::KeyInvalid("Unsupported EC PEM key".to_string()))?;
+    Ok((None, public))
+}
+
+/// Decodes a PKCS#8 or SEC1 private key, or an SPKI public key, on
+/// curve `C` from DER.
+fn parse_der<C>(der: &[u8]) -> Result<(Option<SecretKey<C>>, PublicKey<C>), JwtError>
+where
+    C: AssociatedOid + CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    if let Ok(secret) =
+        SecretKey::<C>::from_pkcs8_der(der).or_else(|_| SecretKey::from_sec1_der(der))
+    {
+        let public = secret.public_key();
+        return Ok((Some(secret), public));
+    }
+    let public = PublicKey::<C>::from_public_key_der(der)
+        .map_err(|_| JwtError::KeyInvalid("Unsupported EC DER key".to_string()))?;
+    Ok((None, public))
+}
+
+/// Builds the P-521 key pair, whose ECDSA types do not convert from
+/// the generic curve keys.
+fn p521_pair(
+    secret: Option<SecretKey<p521::NistP521>>,
+    public: PublicKey<p521::NistP521>,
+) -> Result<KeyPair, JwtError> {
+    let secret = match secret {
+        Some(secret) => {
+            Some(p521::ecdsa::SigningKey::from_bytes(&secret.to_bytes()).map_err(key_error)?)
+        }
+        None => None,
+    };
+    let public = p521::ecdsa::VerifyingKey::from_affine(*public.as_affine()).map_err(key_error)?;
+    Ok(KeyPair::P521(secret, public))
+}
+
+/// Returns the error for signing with a public key.
+fn no_private_key() -> JwtError {
+    JwtError::KeyInvalid("EC public key cannot be used for signing".to_string())
+}
+
+impl Signer for EcdsaKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, JwtError> {
+        let signature = match &self.pair {
+            KeyPair::P256(secret, _) => {
+                let secret = secret.as_ref().ok_or_else(no_private_key)?;
+                let signature: p256::ecdsa::Signature =
+                    secret.try_sign(signing_input).map_err(key_error)?;
+                signature.to_vec()
+            }
+            KeyPair::P384(secret, _) => {
+                let secret = secret.as_ref().ok_or_else(no_private_key)?;
+                let signature: p384::ecdsa::Signature =
+                    secret.try_sign(signing_input).map_err(key_error)?;
+                signature.to_vec()
+            }
+            KeyPair::P521(secret, _) => {
+                let secret = secret.as_ref().ok_or_else(no_private_key)?;
+                let signature: p521::ecdsa::Signature =
+                    secret.try_sign(signing_input).map_err(key_error)?;
+                signature.to_vec()
+            }
+        };
+        Ok(signature)
+    }
+}
+
+impl Verifier for EcdsaKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), JwtError> {
+        let verified = match &self.pair {
+            KeyPair::P256(_, public) => p256::ecdsa::Signature::from_slice(signature)
+                .and_then(|signature| public.verify(signing_input, &signature)),
+            KeyPair::P384(_, public) => p384::ecdsa::Signature::from_slice(signature)
+                .and_then(|signature| public.verify(signing_input, &signature)),
+            KeyPair::P521(_, public) => p521::ecdsa::Signature::from_slice(signature)
+                .and_then(|signature| public.verify(signing_input, &signature)),
+        };
+        verified.map_err(|_| signature_invalid())
+    }
+}
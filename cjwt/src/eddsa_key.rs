@@ -0,0 +1,154 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{signature_invalid, Algorithm, Signer, Verifier};
+use ed25519_dalek::{
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    Signature, Signer as _, SigningKey, VerifyingKey,
+};
+use idk::jwt::JwtError;
+use std::fmt;
+
+/// An Ed25519 key for the `EdDSA` algorithm (RFC 8037).
+///
+/// A key loaded from a private key can both sign and verify; a key
+/// loaded from a public key can only verify.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{EdDsaKey, Signer, Verifier};
+/// let key = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+/// let signature = key.sign(b"header.claims").unwrap();
+/// assert_eq!(signature.len(), 64);
+/// assert!(key.to_public().verify(b"header.claims", &signature).is_ok());
+/// ```
+#[derive(Clone)]
+pub struct EdDsaKey {
+    private_key: Option<SigningKey>,
+    public_key: VerifyingKey,
+}
+
+impl EdDsaKey {
+    /// Creates a key from a PEM document.
+    ///
+    /// Accepts PKCS#8 (`PRIVATE KEY`) private keys and
+    /// SubjectPublicKeyInfo (`PUBLIC KEY`) public keys.
+    pub fn from_pem(pem: &str) -> Result<EdDsaKey, JwtError> {
+        if let Ok(private_key) = SigningKey::from_pkcs8_pem(pem) {
+            return Ok(EdDsaKey::from_signing_key(private_key));
+        }
+        let public_key = VerifyingKey::from_public_key_pem(pem)
+            .map_err(|_| JwtError::KeyInvalid("Unsupported Ed25519 PEM key".to_string()))?;
+        Ok(EdDsaKey {
+            private_key: None,
+            public_key,
+        })
+    }
+
+    /// Creates a key from a DER document.
+    ///
+    /// Accepts the same PKCS#8 and SubjectPublicKeyInfo structures as
+    /// [`EdDsaKey::from_pem`].
+    pub fn from_der(der: &[u8]) -> Result<EdDsaKey, JwtError> {
+        if let Ok(private_key) = SigningKey::from_pkcs8_der(der) {
+            return Ok(EdDsaKey::from_signing_key(private_key));
+        }
+        let public_key = VerifyingKey::from_public_key_der(der)
+            .map_err(|_| JwtError::KeyInvalid("Unsupported Ed25519 DER key".to_string()))?;
+        Ok(EdDsaKey {
+            private_key: None,
+            public_key,
+        })
+    }
+
+    /// Creates a signing key from the 32-byte private key seed (the
+    /// JWK `d` parameter).
+    pub fn from_secret_bytes(bytes: &[u8]) -> Result<EdDsaKey, JwtError> {
+        let seed = <[u8; 32]>::try_from(bytes).map_err(|_| {
+            JwtError::KeyInvalid("Ed25519 private key must be 32 bytes".to_string())
+        })?;
+        Ok(EdDsaKey::from_signing_key(SigningKey::from_bytes(&seed)))
+    }
+
+    /// Creates a verification key from the 32-byte public key (the JWK
+    /// `x` parameter).
+    pub fn from_public_bytes(bytes: &[u8]) -> Result<EdDsaKey, JwtError> {
+        let bytes = <[u8; 32]>::try_from(bytes)
+            .map_err(|_| JwtError::KeyInvalid("Ed25519 public key must be 32 bytes".to_string()))?;
+        let public_key = VerifyingKey::from_bytes(&bytes)
+            .map_err(|err| JwtError::KeyInvalid(err.to_string()))?;
+        Ok(EdDsaKey {
+            private_key: None,
+            public_key,
+        })
+    }
+
+    fn from_signing_key(private_key: SigningKey) -> EdDsaKey {
+        EdDsaKey {
+            public_key: private_key.verifying_key(),
+            private_key: Some(private_key),
+        }
+    }
+
+    /// Returns the 32-byte public key.
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        self.public_key.as_bytes().to_vec()
+    }
+
+    /// Returns a copy of this key without the private part.
+    pub fn to_public(&self) -> EdDsaKey {
+        EdDsaKey {
+            private_key: None,
+            public_key: self.public_key,
+        }
+    }
+
+    /// Returns `true` if this key holds a private key and can sign.
+    pub fn is_private(&self) -> bool {
+        self.private_key.is_some()
+    }
+
+    /// Returns the 32-byte private key seed, if any.
+    pub(crate) fn secret_bytes(&self) -> Option<Vec<u8>> {
+        self.private_key
+            .as_ref()
+            .map(|private_key| private_key.to_bytes().to_vec())
+    }
+}
+
+impl fmt::Debug for EdDsaKey {
+    /// Formats the key without revealing the private key.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EdDsaKey")
+            .field("algorithm", &Algorithm::EdDSA)
+            .field("private", &self.is_private())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Signer for EdDsaKey {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::EdDSA
+    }
+
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, JwtError> {
+        let private_key = self.private_key.as_ref().ok_or_else(|| {
+            JwtError::KeyInvalid("Ed25519 public key cannot be used for signing".to_string())
+        })?;
+        Ok(private_key.sign(signing_input).to_vec())
+    }
+}
+
+impl Verifier for EdDsaKey {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::EdDSA
+    }
+
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), JwtError> {
+        Signature::from_slice(signature)
+            .and_then(|signature| self.public_key.verify_strict(signing_input, &signature))
+            .map_err(|_| signature_invalid())
+    }
+}
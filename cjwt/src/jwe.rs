@@ -0,0 +1,515 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{random::random_bytes, Algorithm, Header, UNDERSTOOD_CRITICAL_PARAMETERS};
+use aes::Aes128;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes128Gcm, Aes256Gcm, Nonce,
+};
+use aes_kw::{KekAes128, KekAes256};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use hmac::{Hmac, Mac};
+use idk::jwt::JwtError;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+/// The length, in bytes, of the authentication tag of every supported
+/// content encryption algorithm.
+const TAG_LEN: usize = 16;
+
+/// The maximum length, in bytes, of a plaintext decompressed from a JWE
+/// with the `zip` header parameter set to `DEF`. Decryption fails with
+/// `JwtError::InvalidPayload` beyond it, so a small JWE cannot inflate
+/// to an arbitrary amount of memory.
+pub const MAX_DECOMPRESSED_LEN: usize = 1 << 20;
+
+/// The content encryption algorithms of a JWE (RFC 7518, section 5),
+/// named by the `enc` header parameter.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, PartialOrd)]
+pub enum Encryption {
+    /// AES GCM using a 128-bit key.
+    A128GCM,
+
+    /// AES GCM using a 256-bit key.
+    A256GCM,
+
+    /// AES-128-CBC with HMAC SHA-256 truncated to 128 bits.
+    #[serde(rename = "A128CBC-HS256")]
+    A128CbcHs256,
+}
+
+impl fmt::Display for Encryption {
+    /// Formats an Encryption enum as a string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Encryption::A128GCM => "A128GCM",
+            Encryption::A256GCM => "A256GCM",
+            Encryption::A128CbcHs256 => "A128CBC-HS256",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Encryption {
+    /// Returns the length, in bytes, of the content encryption key.
+    pub fn key_len(self) -> usize {
+        match self {
+            Encryption::A128GCM => 16,
+            Encryption::A256GCM | Encryption::A128CbcHs256 => 32,
+        }
+    }
+
+    /// Returns the length, in bytes, of the initialization vector.
+    fn iv_len(self) -> usize {
+        match self {
+            Encryption::A128GCM | Encryption::A256GCM => 12,
+            Encryption::A128CbcHs256 => 16,
+        }
+    }
+
+    /// Generates a random content encryption key.
+    pub(crate) fn generate_key(self) -> Vec<u8> {
+        random_bytes(self.key_len())
+    }
+
+    /// Checks the length of a decrypted content encryption key.
+    pub(crate) fn check_key(self, cek: Vec<u8>) -> Result<Vec<u8>, JwtError> {
+        if cek.len() != self.key_len() {
+            return Err(decryption_failed());
+        }
+        Ok(cek)
+    }
+
+    /// Encrypts `plaintext`, returning the ciphertext and the
+    /// authentication tag.
+    fn encrypt(
+        self,
+        cek: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), JwtError> {
+        let invalid_key = |_| JwtError::KeyInvalid(format!("Invalid {self} key"));
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        let mut ciphertext = match self {
+            Encryption::A128GCM => Aes128Gcm::new_from_slice(cek)
+                .map_err(invalid_key)?
+                .encrypt(Nonce::from_slice(iv), payload),
+            Encryption::A256GCM => Aes256Gcm::new_from_slice(cek)
+                .map_err(invalid_key)?
+                .encrypt(Nonce::from_slice(iv), payload),
+            Encryption::A128CbcHs256 => {
+                let (mac_key, enc_key) = cek.split_at(16);
+                let ciphertext = cbc::Encryptor::<Aes128>::new_from_slices(enc_key, iv)
+                    .map_err(invalid_key)?
+                    .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+                let tag = cbc_hs256_mac(mac_key, aad, iv, &ciphertext)?
+                    .finalize()
+                    .into_bytes();
+                return Ok((ciphertext, tag[..TAG_LEN].to_vec()));
+            }
+        }
+        .map_err(|_| JwtError::KeyInvalid(format!("{self} encryption failed")))?;
+        let tag = ciphertext.split_off(ciphertext.len() - TAG_LEN);
+        Ok((ciphertext, tag))
+    }
+
+    /// Checks the authentication tag and decrypts `ciphertext`.
+    ///
+    /// Returns `JwtError::DecryptionFailed` whatever the cause, so that
+    /// the error does not tell which check failed.
+    fn decrypt(
+        self,
+        cek: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, JwtError> {
+        if cek.len() != self.key_len() || iv.len() != self.iv_len() || tag.len() != TAG_LEN {
+            return Err(decryption_failed());
+        }
+        let plaintext = match self {
+            Encryption::A128GCM | Encryption::A256GCM => {
+                let msg = [ciphertext, tag].concat();
+                let payload = Payload { msg: &msg, aad };
+                let nonce = Nonce::from_slice(iv);
+                if self == Encryption::A128GCM {
+                    Aes128Gcm::new_from_slice(cek)
+                        .map_err(|_| decryption_failed())?
+                        .decrypt(nonce, payload)
+                } else {
+                    Aes256Gcm::new_from_slice(cek)
+                        .map_err(|_| decryption_failed())?
+                        .decrypt(nonce, payload)
+                }
+                .map_err(|_| decryption_failed())?
+            }
+            Encryption::A128CbcHs256 => {
+                let (mac_key, enc_key) = cek.split_at(16);
+                cbc_hs256_mac(mac_key, aad, iv, ciphertext)?
+                    .verify_truncated_left(tag)
+                    .map_err(|_| decryption_failed())?;
+                cbc::Decryptor::<Aes128>::new_from_slices(enc_key, iv)
+                    .map_err(|_| decryption_failed())?
+                    .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+                    .map_err(|_| decryption_failed())?
+            }
+        };
+        Ok(plaintext)
+    }
+}
+
+/// Returns the HMAC SHA-256 over the additional authenticated data,
+/// initialization vector, ciphertext and additional authenticated data
+/// length (RFC 7518, section 5.2.2.1).
+fn cbc_hs256_mac(
+    mac_key: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Hmac<Sha256>, JwtError> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(mac_key)?;
+    mac.update(aad);
+    mac.update(iv);
+    mac.update(ciphertext);
+    mac.update(&(aad.len() as u64 * 8).to_be_bytes());
+    Ok(mac)
+}
+
+/// Returns the error reported for any decryption failure.
+fn decryption_failed() -> JwtError {
+    JwtError::DecryptionFailed("Decryption failed".to_string())
+}
+
+/// A key used to determine the content encryption key of a JWE and
+/// produce its JWE Encrypted Key.
+pub trait Encrypter {
+    /// Returns the key management algorithm of this key.
+    fn algorithm(&self) -> Algorithm;
+
+    /// Returns the content encryption key for `enc` and the JWE
+    /// Encrypted Key carried in the token.
+    fn encrypt_key(&self, enc: Encryption) -> Result<(Vec<u8>, Vec<u8>), JwtError>;
+}
+
+/// A key used to recover the content encryption key of a JWE from its
+/// JWE Encrypted Key.
+pub trait Decrypter {
+    /// Returns the key management algorithm of this key.
+    fn algorithm(&self) -> Algorithm;
+
+    /// Returns the content encryption key for `enc` carried by
+    /// `encrypted_key`.
+    fn decrypt_key(&self, encrypted_key: &[u8], enc: Encryption) -> Result<Vec<u8>, JwtError>;
+}
+
+/// A shared symmetric key used directly as the content encryption key
+/// (the `dir` algorithm).
+///
+/// The key must be as long as the content encryption key of the `enc`
+/// algorithm: 16 bytes for A128GCM and 32 bytes for A256GCM and
+/// A128CBC-HS256.
+#[derive(Clone)]
+pub struct DirectKey {
+    key: Vec<u8>,
+}
+
+impl DirectKey {
+    /// Creates a direct encryption key.
+    pub fn new(key: &[u8]) -> DirectKey {
+        DirectKey { key: key.to_vec() }
+    }
+
+    fn content_key(&self, enc: Encryption) -> Result<Vec<u8>, JwtError> {
+        if self.key.len() != enc.key_len() {
+            return Err(JwtError::KeyInvalid(format!(
+                "{enc} requires a {} byte key",
+                enc.key_len()
+            )));
+        }
+        Ok(self.key.clone())
+    }
+}
+
+impl fmt::Debug for DirectKey {
+    /// Formats the key without revealing it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectKey")
+            .field("algorithm", &Algorithm::Dir)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Encrypter for DirectKey {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Dir
+    }
+
+    fn encrypt_key(&self, enc: Encryption) -> Result<(Vec<u8>, Vec<u8>), JwtError> {
+        Ok((self.content_key(enc)?, Vec::new()))
+    }
+}
+
+impl Decrypter for DirectKey {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Dir
+    }
+
+    fn decrypt_key(&self, encrypted_key: &[u8], enc: Encryption) -> Result<Vec<u8>, JwtError> {
+        if !encrypted_key.is_empty() {
+            return Err(JwtError::FormatInvalid(
+                "JWE Encrypted Key must be empty with dir".to_string(),
+            ));
+        }
+        self.content_key(enc)
+    }
+}
+
+/// A key encryption key for the `A128KW` and `A256KW` algorithms (AES
+/// Key Wrap, RFC 3394).
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{AesKwKey, Algorithm, Encryption, Header, Jwe};
+/// let key = AesKwKey::new(Algorithm::A128KW, &[7; 16]).unwrap();
+/// let jwe = Jwe {
+///     header: Header {
+///         alg: Some(Algorithm::A128KW),
+///         enc: Some(Encryption::A128GCM),
+///         ..Header::default()
+///     },
+///     plaintext: b"Live long and prosper.".to_vec(),
+/// };
+/// let token = jwe.encrypt(&key).unwrap();
+/// assert_eq!(token.split('.').count(), 5);
+/// assert_eq!(Jwe::decrypt(&token, &key).unwrap().plaintext, jwe.plaintext);
+/// ```
+#[derive(Clone)]
+pub struct AesKwKey {
+    algorithm: Algorithm,
+    key: Vec<u8>,
+}
+
+impl AesKwKey {
+    /// Creates a key encryption key for `algorithm`.
+    ///
+    /// Returns `JwtError::InvalidHeader` if `algorithm` is not A128KW or
+    /// A256KW and `JwtError::KeyInvalid` if the key is not 16 or 32
+    /// bytes long respectively.
+    pub fn new(algorithm: Algorithm, key: &[u8]) -> Result<AesKwKey, JwtError> {
+        let len = match algorithm {
+            Algorithm::A128KW => 16,
+            Algorithm::A256KW => 32,
+            _ => {
+                return Err(JwtError::InvalidHeader(format!(
+                    "{algorithm} is not an AES Key Wrap algorithm"
+                )))
+            }
+        };
+        if key.len() != len {
+            return Err(JwtError::KeyInvalid(format!(
+                "{algorithm} requires a {len} byte key"
+            )));
+        }
+        Ok(AesKwKey {
+            algorithm,
+            key: key.to_vec(),
+        })
+    }
+}
+
+impl fmt::Debug for AesKwKey {
+    /// Formats the key without revealing it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AesKwKey")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Encrypter for AesKwKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn encrypt_key(&self, enc: Encryption) -> Result<(Vec<u8>, Vec<u8>), JwtError> {
+        let cek = enc.generate_key();
+        let wrapped = if self.algorithm == Algorithm::A128KW {
+            KekAes128::try_from(self.key.as_slice()).and_then(|kek| kek.wrap_vec(&cek))
+        } else {
+            KekAes256::try_from(self.key.as_slice()).and_then(|kek| kek.wrap_vec(&cek))
+        };
+        let encrypted_key = wrapped.map_err(|err| JwtError::KeyInvalid(err.to_string()))?;
+        Ok((cek, encrypted_key))
+    }
+}
+
+impl Decrypter for AesKwKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn decrypt_key(&self, encrypted_key: &[u8], enc: Encryption) -> Result<Vec<u8>, JwtError> {
+        let unwrapped = if self.algorithm == Algorithm::A128KW {
+            KekAes128::try_from(self.key.as_slice()).and_then(|kek| kek.unwrap_vec(encrypted_key))
+        } else {
+            KekAes256::try_from(self.key.as_slice()).and_then(|kek| kek.unwrap_vec(encrypted_key))
+        };
+        enc.check_key(unwrapped.map_err(|_| decryption_failed())?)
+    }
+}
+
+/// A JSON Web Encryption (JWE, RFC 7516) in compact serialization.
+///
+/// The header must name the content encryption algorithm with `enc`.
+/// When `alg` is unset it is filled in with the key's algorithm. When
+/// `zip` is `DEF`, the plaintext is compressed with DEFLATE before it is
+/// encrypted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Jwe {
+    /// The JOSE header, integrity protected but not encrypted.
+    pub header: Header,
+    /// The plaintext.
+    pub plaintext: Vec<u8>,
+}
+
+impl Jwe {
+    /// Encrypts the plaintext with a content encryption key determined
+    /// by `key` and returns the compact serialization
+    /// `header.encrypted_key.iv.ciphertext.tag`.
+    ///
+    /// Returns `JwtError::InvalidHeader` if `enc` is missing, `zip` is
+    /// not `DEF` or `alg` names a different algorithm than the key.
+    pub fn encrypt<K: Encrypter + ?Sized>(&self, key: &K) -> Result<String, JwtError> {
+        let mut header = self.header.clone();
+        let alg = *header.alg.get_or_insert(key.algorithm());
+        check_algorithm(alg, key.algorithm())?;
+        let enc = content_encryption(&header)?;
+        let plaintext = compress(&header, &self.plaintext)?;
+
+        let (cek, encrypted_key) = key.encrypt_key(enc)?;
+        let header_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_string(&header)?);
+        let iv = random_bytes(enc.iv_len());
+        let (ciphertext, tag) = enc.encrypt(&cek, &iv, header_b64.as_bytes(), &plaintext)?;
+
+        Ok([
+            header_b64,
+            URL_SAFE_NO_PAD.encode(encrypted_key),
+            URL_SAFE_NO_PAD.encode(iv),
+            URL_SAFE_NO_PAD.encode(ciphertext),
+            URL_SAFE_NO_PAD.encode(tag),
+        ]
+        .join("."))
+    }
+
+    /// Decrypts a JWE compact serialization with `key`.
+    ///
+    /// Returns `JwtError::FormatInvalid` unless the token has five
+    /// segments, `JwtError::InvalidHeader` if `enc` is missing or `alg`
+    /// does not match the key, and `JwtError::DecryptionFailed` if the
+    /// token cannot be decrypted or has been altered.
+    pub fn decrypt<K: Decrypter + ?Sized>(token: &str, key: &K) -> Result<Jwe, JwtError> {
+        let segments: Vec<&str> = token.split('.').collect();
+        let [header_b64, encrypted_key_b64, iv_b64, ciphertext_b64, tag_b64] = segments[..] else {
+            return Err(JwtError::FormatInvalid(format!(
+                "JWE must have 5 segments, found {}",
+                segments.len()
+            )));
+        };
+        if header_b64.is_empty() {
+            return Err(JwtError::FormatInvalid(
+                "JWE header must not be empty".to_string(),
+            ));
+        }
+
+        let header: Header = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header_b64)?)?;
+        let alg = header
+            .alg
+            .ok_or_else(|| JwtError::InvalidHeader("Missing alg header parameter".to_string()))?;
+        check_algorithm(alg, key.algorithm())?;
+        header.check_critical(UNDERSTOOD_CRITICAL_PARAMETERS)?;
+        let enc = content_encryption(&header)?;
+
+        let cek = key.decrypt_key(&URL_SAFE_NO_PAD.decode(encrypted_key_b64)?, enc)?;
+        let plaintext = enc.decrypt(
+            &cek,
+            &URL_SAFE_NO_PAD.decode(iv_b64)?,
+            header_b64.as_bytes(),
+            &URL_SAFE_NO_PAD.decode(ciphertext_b64)?,
+            &URL_SAFE_NO_PAD.decode(tag_b64)?,
+        )?;
+        let plaintext = decompress(&header, plaintext)?;
+        Ok(Jwe { header, plaintext })
+    }
+}
+
+/// Checks that the `alg` header parameter names `expected`.
+fn check_algorithm(alg: Algorithm, expected: Algorithm) -> Result<(), JwtError> {
+    if alg != expected {
+        return Err(JwtError::InvalidHeader(format!(
+            "Header algorithm {alg} does not match key algorithm {expected}"
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the content encryption algorithm named by the `enc` header
+/// parameter.
+fn content_encryption(header: &Header) -> Result<Encryption, JwtError> {
+    header
+        .enc
+        .ok_or_else(|| JwtError::InvalidHeader("Missing enc header parameter".to_string()))
+}
+
+/// Returns `true` if the `zip` header parameter asks for DEFLATE
+/// compression.
+fn is_deflated(header: &Header) -> Result<bool, JwtError> {
+    match header.zip.as_deref() {
+        None => Ok(false),
+        Some("DEF") => Ok(true),
+        Some(zip) => Err(JwtError::InvalidHeader(format!(
+            "Unsupported zip header parameter {zip}"
+        ))),
+    }
+}
+
+/// Compresses `plaintext` as requested by the `zip` header parameter.
+fn compress(header: &Header, plaintext: &[u8]) -> Result<Vec<u8>, JwtError> {
+    if !is_deflated(header)? {
+        return Ok(plaintext.to_vec());
+    }
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(plaintext)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompresses `plaintext` as requested by the `zip` header parameter.
+fn decompress(header: &Header, plaintext: Vec<u8>) -> Result<Vec<u8>, JwtError> {
+    if !is_deflated(header)? {
+        return Ok(plaintext);
+    }
+    let mut decompressed = Vec::new();
+    DeflateDecoder::new(plaintext.as_slice())
+        .take(MAX_DECOMPRESSED_LEN as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|_| JwtError::InvalidPayload("Invalid DEFLATE payload".to_string()))?;
+    if decompressed.len() > MAX_DECOMPRESSED_LEN {
+        return Err(JwtError::InvalidPayload(format!(
+            "The decompressed payload exceeds {MAX_DECOMPRESSED_LEN} bytes"
+        )));
+    }
+    Ok(decompressed)
+}
@@ -0,0 +1,483 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{Algorithm, EcdsaKey, EdDsaKey, HmacKey, RsaKey, Signer, Verifier};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use idk::jwt::JwtError;
+use rsa::{
+    traits::{PrivateKeyParts, PublicKeyParts},
+    BigUint, RsaPrivateKey, RsaPublicKey,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, path::Path};
+
+/// A JSON Web Key (RFC 7517).
+///
+/// Holds the parameters of `oct` (RFC 7518, section 6.4), `RSA`
+/// (section 6.3), `EC` (section 6.2) and `OKP` (RFC 8037) keys as their
+/// base64url-encoded JSON strings. Use [`Jwk::to_key`] to turn it into a
+/// key that signs or verifies tokens.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{Algorithm, Jwk, Signer};
+/// let jwk = Jwk::from_json(r#"{"kty":"oct","kid":"k1","k":"c2VjcmV0"}"#).unwrap();
+/// assert_eq!(jwk.kid.as_deref(), Some("k1"));
+/// let key = jwk.to_key(Algorithm::HS256).unwrap();
+/// assert_eq!(key.algorithm(), Algorithm::HS256);
+/// ```
+#[derive(Clone, Default, Eq, Hash, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Jwk {
+    /// The `kty` (Key Type) parameter: `oct`, `RSA`, `EC` or `OKP`.
+    pub kty: String,
+    /// The `use` (Public Key Use) parameter, `sig` or `enc`.
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub key_use: Option<String>,
+    /// The `key_ops` (Key Operations) parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+    /// The `alg` (Algorithm) parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    /// The `kid` (Key ID) parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// The `x5u` (X.509 URL) parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5u: Option<String>,
+    /// The `x5c` (X.509 Certificate Chain) parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+    /// The `x5t` (X.509 Certificate SHA-1 Thumbprint) parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+    /// The `x5t#S256` (X.509 Certificate SHA-256 Thumbprint) parameter.
+    #[serde(rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub x5t_s256: Option<String>,
+    /// The `crv` (Curve) parameter of `EC` and `OKP` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    /// The `x` coordinate of `EC` keys, or the public key of `OKP`
+    /// keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    /// The `y` coordinate of `EC` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    /// The private key of `EC` and `OKP` keys, or the private exponent
+    /// of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+    /// The modulus of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    /// The public exponent of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// The first prime factor of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p: Option<String>,
+    /// The second prime factor of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    /// The first factor CRT exponent of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dp: Option<String>,
+    /// The second factor CRT exponent of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dq: Option<String>,
+    /// The first CRT coefficient of `RSA` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qi: Option<String>,
+    /// The key value of `oct` keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub k: Option<String>,
+}
+
+impl Jwk {
+    /// Parses a JWK from its JSON representation.
+    pub fn from_json(json: &str) -> Result<Jwk, JwtError> {
+        serde_json::from_str(json).map_err(|err| JwtError::KeyInvalid(err.to_string()))
+    }
+
+    /// Serializes the JWK to JSON.
+    pub fn to_json(&self) -> Result<String, JwtError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Returns `true` if the JWK holds private or secret key material.
+    pub fn is_private(&self) -> bool {
+        self.d.is_some() || self.k.is_some()
+    }
+
+    /// Returns a copy of the JWK without its private or secret
+    /// parameters, suitable for publishing.
+    pub fn to_public(&self) -> Jwk {
+        Jwk {
+            d: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
+            k: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns `true` if the key type, curve and `alg` parameter of the
+    /// JWK allow it to be used with `algorithm`.
+    pub fn supports(&self, algorithm: Algorithm) -> bool {
+        if let Some(alg) = &self.alg {
+            if *alg != algorithm.to_string() {
+                return false;
+            }
+        }
+        let crv = self.crv.as_deref();
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => self.kty == "oct",
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512 => self.kty == "RSA",
+            Algorithm::ES256 => self.kty == "EC" && crv == Some("P-256"),
+            Algorithm::ES384 => self.kty == "EC" && crv == Some("P-384"),
+            Algorithm::ES512 => self.kty == "EC" && crv == Some("P-521"),
+            Algorithm::EdDSA => self.kty == "OKP" && crv == Some("Ed25519"),
+            // JWE key management keys are built with `DirectKey`,
+            // `AesKwKey` and `RsaKey` instead.
+            Algorithm::Dir | Algorithm::A128KW | Algorithm::A256KW | Algorithm::RsaOaep256 => false,
+            // Unsecured tokens have no key.
+            Algorithm::None => false,
+        }
+    }
+
+    /// Builds the key described by the JWK for `algorithm`.
+    ///
+    /// Returns `JwtError::InvalidHeader` if the JWK cannot be used with
+    /// `algorithm` (see [`Jwk::supports`]) and `JwtError::KeyInvalid`
+    /// if its parameters are missing or malformed.
+    pub fn to_key(&self, algorithm: Algorithm) -> Result<JwkKey, JwtError> {
+        if !self.supports(algorithm) {
+            return Err(JwtError::InvalidHeader(format!(
+                "JWK with kty {} cannot be used with {algorithm}",
+                self.kty
+            )));
+        }
+        match self.kty.as_str() {
+            "oct" => Ok(JwkKey::Hmac(HmacKey::new(
+                algorithm,
+                &param("k", &self.k)?,
+            )?)),
+            "RSA" => self.to_rsa_key(algorithm).map(JwkKey::Rsa),
+            "EC" => self.to_ecdsa_key(algorithm).map(JwkKey::Ecdsa),
+            _ => self.to_eddsa_key().map(JwkKey::EdDsa),
+        }
+    }
+
+    fn to_rsa_key(&self, algorithm: Algorithm) -> Result<RsaKey, JwtError> {
+        let n = BigUint::from_bytes_be(&param("n", &self.n)?);
+        let e = BigUint::from_bytes_be(&param("e", &self.e)?);
+        if self.d.is_none() {
+            let public_key = RsaPublicKey::new(n, e).map_err(key_error)?;
+            return RsaKey::from_public_key(algorithm, public_key);
+        }
+        let d = BigUint::from_bytes_be(&param("d", &self.d)?);
+        let primes = match (&self.p, &self.q) {
+            (Some(_), Some(_)) => vec![
+                BigUint::from_bytes_be(&param("p", &self.p)?),
+                BigUint::from_bytes_be(&param("q", &self.q)?),
+            ],
+            _ => Vec::new(),
+        };
+        let private_key = RsaPrivateKey::from_components(n, e, d, primes).map_err(key_error)?;
+        RsaKey::from_private_key(algorithm, private_key)
+    }
+
+    fn to_ecdsa_key(&self, algorithm: Algorithm) -> Result<EcdsaKey, JwtError> {
+        let x = param("x", &self.x)?;
+        let y = param("y", &self.y)?;
+        let size = match algorithm {
+            Algorithm::ES384 => 48,
+            Algorithm::ES512 => 66,
+            _ => 32,
+        };
+        if x.len() != size || y.len() != size {
+            return Err(JwtError::KeyInvalid(format!(
+                "EC coordinates must be {size} bytes"
+            )));
+        }
+        let mut point = vec![0x04];
+        point.extend(x);
+        point.extend(y);
+        match &self.d {
+            Some(_) => {
+                let key = EcdsaKey::from_secret_bytes(algorithm, &param("d", &self.d)?)?;
+                check_public(key.public_key_bytes(), &point)?;
+                Ok(key)
+            }
+            None => EcdsaKey::from_public_bytes(algorithm, &point),
+        }
+    }
+
+    fn to_eddsa_key(&self) -> Result<EdDsaKey, JwtError> {
+        let x = param("x", &self.x)?;
+        match &self.d {
+            Some(_) => {
+                let key = EdDsaKey::from_secret_bytes(&param("d", &self.d)?)?;
+                check_public(key.public_key_bytes(), &x)?;
+                Ok(key)
+            }
+            None => EdDsaKey::from_public_bytes(&x),
+        }
+    }
+}
+
+impl fmt::Debug for Jwk {
+    /// Formats the JWK without revealing its private or secret
+    /// parameters.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Jwk")
+            .field("kty", &self.kty)
+            .field("kid", &self.kid)
+            .field("alg", &self.alg)
+            .field("crv", &self.crv)
+            .field("private", &self.is_private())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Base64url-decodes the required JWK parameter `name`.
+fn param(name: &str, value: &Option<String>) -> Result<Vec<u8>, JwtError> {
+    let value = value
+        .as_deref()
+        .ok_or_else(|| JwtError::KeyInvalid(format!("Missing JWK parameter {name}")))?;
+    URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|err| JwtError::KeyInvalid(format!("Invalid JWK parameter {name}: {err}")))
+}
+
+/// Base64url-encodes a JWK parameter.
+fn encode(bytes: impl AsRef<[u8]>) -> Option<String> {
+    Some(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Converts a key error into a `JwtError::KeyInvalid`.
+fn key_error<E: fmt::Display>(err: E) -> JwtError {
+    JwtError::KeyInvalid(err.to_string())
+}
+
+/// Checks that the public key derived from a private key matches the
+/// public parameters of the JWK.
+fn check_public(derived: Vec<u8>, expected: &[u8]) -> Result<(), JwtError> {
+    if derived != expected {
+        return Err(JwtError::KeyInvalid(
+            "JWK private key does not match its public key".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl From<&HmacKey> for Jwk {
+    /// Converts an HMAC key into an `oct` JWK holding the secret.
+    fn from(key: &HmacKey) -> Self {
+        Jwk {
+            kty: "oct".to_string(),
+            alg: Some(Signer::algorithm(key).to_string()),
+            k: encode(key.secret()),
+            ..Jwk::default()
+        }
+    }
+}
+
+impl From<&RsaKey> for Jwk {
+    /// Converts an RSA key into an `RSA` JWK, including the private
+    /// parameters if the key holds a private key.
+    fn from(key: &RsaKey) -> Self {
+        let public_key = key.public_key();
+        let mut jwk = Jwk {
+            kty: "RSA".to_string(),
+            alg: Some(Signer::algorithm(key).to_string()),
+            n: encode(public_key.n().to_bytes_be()),
+            e: encode(public_key.e().to_bytes_be()),
+            ..Jwk::default()
+        };
+        if let Some(private_key) = key.private_key() {
+            let primes = private_key.primes();
+            jwk.d = encode(private_key.d().to_bytes_be());
+            jwk.p = encode(primes[0].to_bytes_be());
+            jwk.q = encode(primes[1].to_bytes_be());
+            jwk.dp = private_key.dp().and_then(|dp| encode(dp.to_bytes_be()));
+            jwk.dq = private_key.dq().and_then(|dq| encode(dq.to_bytes_be()));
+            jwk.qi = private_key
+                .crt_coefficient()
+                .and_then(|qi| encode(qi.to_bytes_be()));
+        }
+        jwk
+    }
+}
+
+impl From<&EcdsaKey> for Jwk {
+    /// Converts an ECDSA key into an `EC` JWK, including `d` if the key
+    /// holds a private key.
+    fn from(key: &EcdsaKey) -> Self {
+        let algorithm = Signer::algorithm(key);
+        let crv = match algorithm {
+            Algorithm::ES384 => "P-384",
+            Algorithm::ES512 => "P-521",
+            _ => "P-256",
+        };
+        let point = key.public_key_bytes();
+        let size = (point.len() - 1) / 2;
+        Jwk {
+            kty: "EC".to_string(),
+            alg: Some(algorithm.to_string()),
+            crv: Some(crv.to_string()),
+            x: encode(&point[1..=size]),
+            y: encode(&point[size + 1..]),
+            d: key.secret_bytes().and_then(encode),
+            ..Jwk::default()
+        }
+    }
+}
+
+impl From<&EdDsaKey> for Jwk {
+    /// Converts an Ed25519 key into an `OKP` JWK, including `d` if the
+    /// key holds a private key.
+    fn from(key: &EdDsaKey) -> Self {
+        Jwk {
+            kty: "OKP".to_string(),
+            alg: Some(Algorithm::EdDSA.to_string()),
+            crv: Some("Ed25519".to_string()),
+            x: encode(key.public_key_bytes()),
+            d: key.secret_bytes().and_then(encode),
+            ..Jwk::default()
+        }
+    }
+}
+
+/// A signing or verification key built from a [`Jwk`].
+#[derive(Clone, Debug)]
+pub enum JwkKey {
+    /// An `oct` key.
+    Hmac(HmacKey),
+    /// An `RSA` key.
+    Rsa(RsaKey),
+    /// An `EC` key.
+    Ecdsa(EcdsaKey),
+    /// An `OKP` Ed25519 key.
+    EdDsa(EdDsaKey),
+}
+
+impl Signer for JwkKey {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            JwkKey::Hmac(key) => Signer::algorithm(key),
+            JwkKey::Rsa(key) => Signer::algorithm(key),
+            JwkKey::Ecdsa(key) => Signer::algorithm(key),
+            JwkKey::EdDsa(key) => Signer::algorithm(key),
+        }
+    }
+
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, JwtError> {
+        match self {
+            JwkKey::Hmac(key) => key.sign(signing_input),
+            JwkKey::Rsa(key) => key.sign(signing_input),
+            JwkKey::Ecdsa(key) => key.sign(signing_input),
+            JwkKey::EdDsa(key) => key.sign(signing_input),
+        }
+    }
+}
+
+impl Verifier for JwkKey {
+    fn algorithm(&self) -> Algorithm {
+        Signer::algorithm(self)
+    }
+
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), JwtError> {
+        match self {
+            JwkKey::Hmac(key) => key.verify(signing_input, signature),
+            JwkKey::Rsa(key) => key.verify(signing_input, signature),
+            JwkKey::Ecdsa(key) => key.verify(signing_input, signature),
+            JwkKey::EdDsa(key) => key.verify(signing_input, signature),
+        }
+    }
+}
+
+/// A JSON Web Key Set (RFC 7517, section 5), as published on an
+/// identity provider's `jwks_uri`.
+///
+/// Keys with a `kty` this crate does not understand are kept but never
+/// selected.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{Algorithm, JwkSet};
+/// let jwks = JwkSet::from_json(
+///     r#"{"keys":[{"kty":"oct","kid":"old","k":"b2xk"},{"kty":"oct","kid":"new","k":"bmV3"}]}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(jwks.select(Some("new"), Algorithm::HS256).len(), 1);
+/// assert_eq!(jwks.select(None, Algorithm::HS256).len(), 2);
+/// assert!(jwks.select(None, Algorithm::RS256).is_empty());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JwkSet {
+    /// The keys of the set.
+    pub keys: Vec<Jwk>,
+}
+
+impl JwkSet {
+    /// Creates an empty JWK Set.
+    pub fn new() -> JwkSet {
+        JwkSet::default()
+    }
+
+    /// Parses a JWK Set from its JSON representation.
+    pub fn from_json(json: &str) -> Result<JwkSet, JwtError> {
+        serde_json::from_str(json).map_err(|err| JwtError::KeyInvalid(err.to_string()))
+    }
+
+    /// Reads and parses a JWK Set from a JSON file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<JwkSet, JwtError> {
+        JwkSet::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Serializes the JWK Set to JSON.
+    pub fn to_json(&self) -> Result<String, JwtError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Returns the key whose `kid` is `kid`.
+    pub fn find(&self, kid: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|jwk| jwk.kid.as_deref() == Some(kid))
+    }
+
+    /// Returns the keys that can verify a signature made with
+    /// `algorithm`, restricted to the key `kid` when given.
+    ///
+    /// Keys marked for encryption only, through `use` or `key_ops`,
+    /// are skipped.
+    pub fn select(&self, kid: Option<&str>, algorithm: Algorithm) -> Vec<&Jwk> {
+        self.keys
+            .iter()
+            .filter(|jwk| kid.map_or(true, |kid| jwk.kid.as_deref() == Some(kid)))
+            .filter(|jwk| {
+                jwk.key_use
+                    .as_deref()
+                    .map_or(true, |key_use| key_use == "sig")
+            })
+            .filter(|jwk| {
+                jwk.key_ops
+                    .as_ref()
+                    .map_or(true, |ops| ops.iter().any(|op| op == "verify"))
+            })
+            .filter(|jwk| jwk.supports(algorithm))
+            .collect()
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{
+    header_algorithm, payload_is_encoded, Algorithm, Header, JwkSet, Signer, Verifier,
+    UNDERSTOOD_CRITICAL_PARAMETERS,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use idk::jwt::JwtError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// How many signatures of a [`Jws`] must be valid for it to be
+/// accepted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SignaturePolicy {
+    /// Every signature must be valid and every key must have made one
+    /// of them, so stripping a signature fails the verification.
+    #[default]
+    All,
+    /// At least one signature must be valid.
+    Any,
+}
+
+/// One signature of a JWS JSON serialization (RFC 7515, section 7.2.1).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwsSignature {
+    /// The base64url-encoded protected header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protected: Option<String>,
+    /// The unprotected header, which is not covered by the signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<Header>,
+    /// The base64url-encoded signature.
+    pub signature: String,
+}
+
+impl JwsSignature {
+    /// Decodes the protected header.
+    pub fn protected_header(&self) -> Result<Option<Header>, JwtError> {
+        match &self.protected {
+            Some(protected) => Ok(Some(serde_json::from_slice(
+                &URL_SAFE_NO_PAD.decode(protected)?,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the JOSE header of the signature, the union of its
+    /// protected and unprotected headers.
+    ///
+    /// Returns `JwtError::InvalidHeader` if both headers set the same
+    /// parameter, which RFC 7515 forbids.
+    pub fn joint_header(&self) -> Result<Header, JwtError> {
+        let mut joint = header_parameters(self.protected_header()?.as_ref())?;
+        for (name, value) in header_parameters(self.header.as_ref())? {
+            if joint.contains_key(&name) {
+                return Err(JwtError::InvalidHeader(format!(
+                    "Header parameter {name} is both protected and unprotected"
+                )));
+            }
+            joint.insert(name, value);
+        }
+        Ok(serde_json::from_value(Value::Object(joint))?)
+    }
+
+    /// Returns the JWS signing input of the signature over `payload`.
+    fn signing_input(&self, payload: &str) -> Vec<u8> {
+        format!("{}.{payload}", self.protected.as_deref().unwrap_or("")).into_bytes()
+    }
+}
+
+/// A JSON Web Signature (JWS, RFC 7515) in JSON serialization, with one
+/// or more signatures over the same payload.
+///
+/// [`Jws::to_json`] produces the general serialization and
+/// [`Jws::to_flattened_json`] the flattened serialization of a JWS with
+/// a single signature. [`Jws::from_json`] reads both.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{Algorithm, EdDsaKey, Header, HmacKey, Jws, SignaturePolicy, Verifier};
+///
+/// let hmac = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+/// let eddsa = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+/// let protected = Header { alg: None, typ: None, ..Header::default() };
+///
+/// let mut jws = Jws::new(br#"{"doc":"contract"}"#);
+/// jws.sign(protected.clone(), None, &hmac).unwrap();
+/// jws.sign(protected, None, &eddsa).unwrap();
+///
+/// let jws = Jws::from_json(&jws.to_json().unwrap()).unwrap();
+/// let keys: [&dyn Verifier; 2] = [&hmac, &eddsa];
+/// assert_eq!(jws.verify(&keys, SignaturePolicy::All).unwrap().len(), 2);
+/// assert!(jws.verify(&keys[..1], SignaturePolicy::All).is_err());
+/// assert!(jws.verify(&keys[..1], SignaturePolicy::Any).is_ok());
+/// assert_eq!(jws.payload().unwrap(), br#"{"doc":"contract"}"#);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jws {
+    /// The base64url-encoded payload.
+    pub payload: String,
+    /// The signatures over the payload.
+    pub signatures: Vec<JwsSignature>,
+}
+
+/// The flattened JWS JSON serialization (RFC 7515, section 7.2.2).
+#[derive(Deserialize, Serialize)]
+struct FlattenedJws {
+    payload: String,
+    #[serde(flatten)]
+    signature: JwsSignature,
+}
+
+impl Jws {
+    /// Creates a JWS over `payload` without any signature.
+    pub fn new(payload: &[u8]) -> Jws {
+        Jws {
+            payload: URL_SAFE_NO_PAD.encode(payload),
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Decodes the payload.
+    pub fn payload(&self) -> Result<Vec<u8>, JwtError> {
+        Ok(URL_SAFE_NO_PAD.decode(&self.payload)?)
+    }
+
+    /// Signs the payload with `key` and adds the signature.
+    ///
+    /// When neither header sets `alg`, the protected header is filled in
+    /// with the key's algorithm. Returns `JwtError::InvalidHeader` if
+    /// `alg` names a different algorithm than the key or the headers
+    /// are not valid together.
+    pub fn sign<K: Signer + ?Sized>(
+        &mut self,
+        mut protected: Header,
+        header: Option<Header>,
+        key: &K,
+    ) -> Result<&mut Jws, JwtError> {
+        if header.as_ref().map_or(true, |header| header.alg.is_none()) {
+            protected.alg.get_or_insert(key.algorithm());
+        }
+        let mut signature = JwsSignature {
+            protected: Some(URL_SAFE_NO_PAD.encode(serde_json::to_string(&protected)?)),
+            header,
+            signature: String::new(),
+        };
+        let joint = check_header(&signature)?;
+        let alg = header_algorithm(&joint)?;
+        if alg != key.algorithm() {
+            return Err(JwtError::InvalidHeader(format!(
+                "Header algorithm {alg} does not match key algorithm {}",
+                key.algorithm()
+            )));
+        }
+
+        signature.signature =
+            URL_SAFE_NO_PAD.encode(key.sign(&signature.signing_input(&self.payload))?);
+        self.signatures.push(signature);
+        Ok(self)
+    }
+
+    /// Verifies the signatures with `keys` according to `policy` and
+    /// returns the joint headers of the valid signatures.
+    ///
+    /// A signature is valid if any key for its `alg` verifies it. Under
+    /// [`SignaturePolicy::All`], `keys` are the required signers: each
+    /// of them must have made a valid signature, otherwise
+    /// `JwtError::SignatureInvalid` names the index of the first key
+    /// that did not. Returns the error of the first invalid signature
+    /// when the policy is not met, and `JwtError::FormatInvalid` if
+    /// there is no signature.
+    pub fn verify(
+        &self,
+        keys: &[&dyn Verifier],
+        policy: SignaturePolicy,
+    ) -> Result<Vec<Header>, JwtError> {
+        self.verify_with(policy, keys.len(), |_, alg, signing_input, signature| {
+            let mut result = Err(JwtError::KeyInvalid(format!("No key matches alg {alg}")));
+            for (index, key) in keys.iter().enumerate() {
+                if key.algorithm() != alg {
+                    continue;
+                }
+                result = key.verify(signing_input, signature).map(|()| index);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            result
+        })
+    }
+
+    /// Verifies the signatures with the keys of a JWK Set selected by
+    /// the `kid` and `alg` of each signature, according to `policy`.
+    /// See [`Jws::verify`].
+    ///
+    /// Under [`SignaturePolicy::All`], every key of the set must have
+    /// made a valid signature, so `jwks` should hold exactly the
+    /// required signers.
+    pub fn verify_with_jwk_set(
+        &self,
+        jwks: &JwkSet,
+        policy: SignaturePolicy,
+    ) -> Result<Vec<Header>, JwtError> {
+        self.verify_with(
+            policy,
+            jwks.keys.len(),
+            |header, alg, signing_input, signature| {
+                let kid = header.kid.as_deref();
+                let mut result = Err(JwtError::KeyInvalid(match kid {
+                    Some(kid) => format!("No key in the JWK Set matches kid {kid} and alg {alg}"),
+                    None => format!("No key in the JWK Set matches alg {alg}"),
+                }));
+                for jwk in jwks.select(kid, alg) {
+                    result = jwk
+                        .to_key(alg)
+                        .and_then(|key| key.verify(signing_input, signature))
+                        .map(|()| {
+                            jwks.keys
+                                .iter()
+                                .position(|key| std::ptr::eq(key, jwk))
+                                .unwrap_or_default()
+                        });
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+                result
+            },
+        )
+    }
+
+    /// Returns the general JWS JSON serialization.
+    pub fn to_json(&self) -> Result<String, JwtError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Returns the flattened JWS JSON serialization.
+    ///
+    /// Returns `JwtError::FormatInvalid` unless the JWS has exactly one
+    /// signature.
+    pub fn to_flattened_json(&self) -> Result<String, JwtError> {
+        let [signature] = &self.signatures[..] else {
+            return Err(JwtError::FormatInvalid(format!(
+                "Flattened JWS must have 1 signature, found {}",
+                self.signatures.len()
+            )));
+        };
+        Ok(serde_json::to_string(&FlattenedJws {
+            payload: self.payload.clone(),
+            signature: signature.clone(),
+        })?)
+    }
+
+    /// Parses a general or flattened JWS JSON serialization.
+    ///
+    /// Returns `JwtError::FormatInvalid` if the JWS has no signature or
+    /// mixes both serializations.
+    pub fn from_json(json: &str) -> Result<Jws, JwtError> {
+        let value: Value = serde_json::from_str(json)?;
+        let jws = if value.get("signatures").is_some() {
+            if ["protected", "header", "signature"]
+                .iter()
+                .any(|name| value.get(name).is_some())
+            {
+                return Err(JwtError::FormatInvalid(
+                    "General JWS must not have flattened signature members".to_string(),
+                ));
+            }
+            serde_json::from_value(value)?
+        } else {
+            let flattened: FlattenedJws = serde_json::from_value(value)?;
+            Jws {
+                payload: flattened.payload,
+                signatures: vec![flattened.signature],
+            }
+        };
+        if jws.signatures.is_empty() {
+            return Err(JwtError::FormatInvalid(
+                "JWS must have at least 1 signature".to_string(),
+            ));
+        }
+        Ok(jws)
+    }
+
+    /// Checks every signature with `verify`, which returns the index of
+    /// the key out of `keys` that verified it, and applies `policy`.
+    fn verify_with<F>(
+        &self,
+        policy: SignaturePolicy,
+        keys: usize,
+        mut verify: F,
+    ) -> Result<Vec<Header>, JwtError>
+    where
+        F: FnMut(&Header, Algorithm, &[u8], &[u8]) -> Result<usize, JwtError>,
+    {
+        if self.signatures.is_empty() {
+            return Err(JwtError::FormatInvalid(
+                "JWS must have at least 1 signature".to_string(),
+            ));
+        }
+
+        let mut valid = Vec::new();
+        let mut signed = vec![false; keys];
+        let mut first_error = None;
+        for signature in &self.signatures {
+            let result = check_header(signature).and_then(|joint| {
+                let alg = header_algorithm(&joint)?;
+                let bytes = URL_SAFE_NO_PAD.decode(&signature.signature)?;
+                let key = verify(&joint, alg, &signature.signing_input(&self.payload), &bytes)?;
+                Ok((joint, key))
+            });
+            match result {
+                Ok((joint, key)) => {
+                    signed[key] = true;
+                    valid.push(joint);
+                }
+                Err(err) if policy == SignaturePolicy::All => return Err(err),
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        if policy == SignaturePolicy::All {
+            if let Some(key) = signed.iter().position(|signed| !signed) {
+                return Err(JwtError::SignatureInvalid(format!(
+                    "Key {key} made no valid signature"
+                )));
+            }
+        }
+        match first_error {
+            Some(err) if valid.is_empty() => Err(err),
+            _ => Ok(valid),
+        }
+    }
+}
+
+/// Returns the parameters set by `header`.
+fn header_parameters(header: Option<&Header>) -> Result<Map<String, Value>, JwtError> {
+    match header.map(serde_json::to_value).transpose()? {
+        Some(Value::Object(parameters)) => Ok(parameters),
+        _ => Ok(Map::new()),
+    }
+}
+
+/// Returns the joint header of `signature` after checking that `crit`
+/// is protected and understood and that the payload is base64url-encoded.
+fn check_header(signature: &JwsSignature) -> Result<Header, JwtError> {
+    if signature
+        .header
+        .as_ref()
+        .map_or(false, |header| header.crit.is_some())
+    {
+        return Err(JwtError::InvalidHeader(
+            "crit header parameter must be protected".to_string(),
+        ));
+    }
+    let joint = signature.joint_header()?;
+    joint.check_critical(UNDERSTOOD_CRITICAL_PARAMETERS)?;
+    if !payload_is_encoded(&joint)? {
+        return Err(JwtError::InvalidHeader(
+            "Unencoded payloads are not supported in JWS JSON serialization".to_string(),
+        ));
+    }
+    Ok(joint)
+}
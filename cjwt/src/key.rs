@@ -0,0 +1,150 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::Algorithm;
+use hmac::{digest::KeyInit, Hmac, Mac};
+use idk::jwt::JwtError;
+use sha2::{Sha256, Sha384, Sha512};
+use std::fmt;
+use subtle::ConstantTimeEq;
+
+/// A key that can produce JWS signatures for a single algorithm.
+pub trait Signer {
+    /// Returns the algorithm this key signs with.
+    fn algorithm(&self) -> Algorithm;
+
+    /// Signs the JWS signing input and returns the raw signature bytes.
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, JwtError>;
+}
+
+/// A key that can verify JWS signatures for a single algorithm.
+pub trait Verifier {
+    /// Returns the algorithm this key verifies.
+    fn algorithm(&self) -> Algorithm;
+
+    /// Verifies the raw `signature` bytes over the JWS signing input.
+    ///
+    /// Returns `JwtError::SignatureInvalid` if the signature does not
+    /// match.
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), JwtError>;
+}
+
+/// The "key" of an unsecured JWS (`alg` of `none`), which produces an
+/// empty signature.
+///
+/// It deliberately does not implement [`Verifier`]: unsecured tokens
+/// are only accepted by [`JWT::decode_unsecured`](crate::JWT::decode_unsecured)
+/// when [`Validation::allow_unsecured`](crate::Validation::allow_unsecured)
+/// is set.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unsecured;
+
+impl Signer for Unsecured {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::None
+    }
+
+    fn sign(&self, _signing_input: &[u8]) -> Result<Vec<u8>, JwtError> {
+        Ok(Vec::new())
+    }
+}
+
+/// A shared secret bound to one of the HMAC algorithms (HS256, HS384
+/// or HS512).
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{Algorithm, HmacKey, Signer, Verifier};
+/// let key = HmacKey::new(Algorithm::HS512, b"secret").unwrap();
+/// let signature = key.sign(b"header.claims").unwrap();
+/// assert_eq!(signature.len(), 64);
+/// assert!(key.verify(b"header.claims", &signature).is_ok());
+/// ```
+#[derive(Clone)]
+pub struct HmacKey {
+    algorithm: Algorithm,
+    secret: Vec<u8>,
+}
+
+impl HmacKey {
+    /// Creates a new HMAC key for `algorithm` from a shared secret.
+    ///
+    /// Returns `JwtError::InvalidHeader` if `algorithm` is not an HMAC
+    /// algorithm.
+    pub fn new(algorithm: Algorithm, secret: &[u8]) -> Result<HmacKey, JwtError> {
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => Ok(HmacKey {
+                algorithm,
+                secret: secret.to_vec(),
+            }),
+            _ => Err(JwtError::InvalidHeader(format!(
+                "{algorithm} is not an HMAC algorithm"
+            ))),
+        }
+    }
+
+    /// Returns the shared secret.
+    pub(crate) fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+}
+
+impl fmt::Debug for HmacKey {
+    /// Formats the key without revealing the secret.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacKey")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Computes the MAC `M` of `input` keyed with `secret`.
+fn mac<M: Mac + KeyInit>(secret: &[u8], input: &[u8]) -> Result<Vec<u8>, JwtError> {
+    let mut mac = <M as KeyInit>::new_from_slice(secret)?;
+    mac.update(input);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Compares two raw signatures in constant time.
+///
+/// The time taken depends only on the lengths of the signatures, never
+/// on their contents, so that a forged signature cannot be guessed byte
+/// by byte.
+pub(crate) fn constant_time_eq(expected: &[u8], signature: &[u8]) -> bool {
+    expected.len() == signature.len() && bool::from(expected.ct_eq(signature))
+}
+
+/// Returns the error reported when a signature does not match.
+pub(crate) fn signature_invalid() -> JwtError {
+    JwtError::SignatureInvalid("Signature verification failed".to_string())
+}
+
+impl Signer for HmacKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, JwtError> {
+        match self.algorithm {
+            Algorithm::HS384 => mac::<Hmac<Sha384>>(&self.secret, signing_input),
+            Algorithm::HS512 => mac::<Hmac<Sha512>>(&self.secret, signing_input),
+            _ => mac::<Hmac<Sha256>>(&self.secret, signing_input),
+        }
+    }
+}
+
+impl Verifier for HmacKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), JwtError> {
+        let expected = self.sign(signing_input)?;
+        if !constant_time_eq(&expected, signature) {
+            return Err(signature_invalid());
+        }
+        Ok(())
+    }
+}
@@ -25,6 +25,41 @@
 //! - [x] JWT token generation
 //! - [x] JWT token validation
 //! - [x] JWT token signing
+//! - [x] HMAC signing and verification with HS256, HS384 and HS512
+//! - [x] RSA signing and verification with RS256, RS384, RS512, PS256,
+//!   PS384 and PS512
+//! - [x] ECDSA signing and verification with ES256, ES384 and ES512
+//! - [x] EdDSA signing and verification with Ed25519
+//! - [x] Claims validation (`exp`, `nbf`, `iat`, `iss`, `aud`) with
+//!   leeway, maximum age and an injectable clock
+//! - [x] JSON Web Keys (JWK) and JWK Sets with key selection by `kid`
+//!   and `alg`
+//! - [x] Typed decoding into `JWT` or `TokenData<C>` for user-defined
+//!   claims, and unverified inspection with `insecure_decode`
+//! - [x] JSON Web Encryption (JWE) with `dir`, `A128KW`, `A256KW` and
+//!   `RSA-OAEP-256` key management, `A128GCM`, `A256GCM` and
+//!   `A128CBC-HS256` content encryption, and nested (signed then
+//!   encrypted) JWTs
+//! - [x] All registered JOSE header parameters (`jku`, `jwk`, `x5u`,
+//!   `x5c`, `x5t`, `x5t#S256`, `crit`) and custom header parameters,
+//!   with unsupported `crit` extensions rejected
+//! - [x] Detached payloads with unencoded (RFC 7797 `b64`) payload
+//!   support, and unsecured (`alg` of `none`) tokens behind an explicit
+//!   opt-in
+//! - [x] General and flattened JWS JSON serializations with multiple
+//!   signatures, verified when any or all of them are valid
+//! - [x] Token refresh with new `iat`, `exp` and `jti` claims and a
+//!   sliding expiry capped by a maximum lifetime
+//! - [x] Replay detection with a pluggable `jti` store and revocation
+//!   with a file-backed `jti` denylist
+//! - [x] Canonical JSON (RFC 8785 JCS) serialization of claims and
+//!   headers, and signing of the canonical form for byte-identical
+//!   tokens
+//! - [x] W3C verifiable credentials and presentations issued and verified
+//!   as JWT-VC and JWT-VP, with `iss` and `sub` checked against the
+//!   credential issuer and subject
+//! - [x] Offline `did:key` and `did:jwk` resolution, to verify tokens
+//!   whose `iss` is a DID without any network access
 //!
 //! ## Usage
 //!
@@ -47,24 +82,100 @@
 #![crate_name = "cjwt"]
 #![crate_type = "lib"]
 
+extern crate aes;
+extern crate aes_gcm;
+extern crate aes_kw;
 extern crate base64;
+extern crate cbc;
 extern crate cclm;
 extern crate dtt;
+extern crate ed25519_dalek;
+extern crate flate2;
 extern crate hmac;
 extern crate idk;
 extern crate jwt;
+extern crate p256;
+extern crate p384;
+extern crate p521;
+extern crate rsa;
 extern crate serde;
 extern crate serde_json;
 extern crate sha2;
+extern crate subtle;
 
 use self::cclm::Claims;
 use idk::jwt::JwtError;
 
-use base64::{engine::general_purpose, Engine as _};
-use hmac::{Hmac, Mac};
-use serde::{Deserialize, Serialize};
-use sha2::Sha256;
-use std::{fmt, string::ToString};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{cmp::Ordering, collections::BTreeMap, fmt};
+
+/// The `key` module contains the keys used to sign and verify JWTs.
+pub mod key;
+pub use key::*;
+
+/// The `rsa_key` module contains the RSA keys used for the RS256, RS384,
+/// RS512, PS256, PS384 and PS512 algorithms.
+pub mod rsa_key;
+pub use rsa_key::*;
+
+/// The `ecdsa_key` module contains the elliptic curve keys used for the
+/// ES256, ES384 and ES512 algorithms.
+pub mod ecdsa_key;
+pub use ecdsa_key::*;
+
+/// The `eddsa_key` module contains the Ed25519 keys used for the EdDSA
+/// algorithm.
+pub mod eddsa_key;
+pub use eddsa_key::*;
+
+/// The `credential` module contains the issuance and verification of
+/// W3C verifiable credentials and presentations encoded as JWTs.
+pub mod credential;
+
+/// The `did` module contains the offline resolvers of the verification
+/// keys of Decentralized Identifiers.
+pub mod did;
+pub use did::*;
+
+/// The `jwk` module contains the JSON Web Key (JWK) and JWK Set types.
+pub mod jwk;
+pub use jwk::*;
+
+/// The `token_data` module contains the decoded parts of a token with
+/// user-defined claims.
+pub mod token_data;
+pub use token_data::*;
+
+/// The `jwe` module contains the JSON Web Encryption (JWE) content
+/// encryption algorithms and key management keys.
+pub mod jwe;
+pub use jwe::*;
+
+/// The `jws` module contains the JSON Web Signature (JWS) JSON
+/// serializations with one or more signatures.
+pub mod jws;
+pub use jws::*;
+
+/// The `random` module contains the random generator of keys, nonces
+/// and token identifiers.
+mod random;
+
+/// The `refresh` module contains the policy used to refresh and
+/// re-issue tokens.
+pub mod refresh;
+pub use refresh::*;
+
+/// The `replay` module contains the stores used to reject replayed and
+/// revoked tokens.
+pub mod replay;
+pub use replay::*;
+
+/// The `validation` module contains the rules used to validate the
+/// claims of a JWT.
+pub mod validation;
+pub use validation::*;
 
 /// JWT is a struct that holds the JWT token and its associated claims.
 /// Provides a set of utility functions for working with JSON Web Tokens
@@ -81,18 +192,69 @@ pub struct JWT {
     pub token: String,
 }
 /// The Header struct contains the header of the JWT.
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize, PartialOrd)]
+///
+/// Header parameters without a dedicated field are kept in `extra`, so
+/// that they survive a round trip through serde.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct Header {
     /// Indicates the algorithm used to sign the JWT. Defaults to HS256.
     /// See the Algorithm enum for a list of supported algorithms.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alg: Option<Algorithm>,
     /// Indicates the key used to sign the JWT. This is used to select
     /// a specific key for a given JWT.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub kid: Option<String>,
     /// Indicates the media type of the JWT. Defaults to JWT.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub typ: Option<String>,
     /// Indicates the content type.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cty: Option<String>,
+    /// The URL of a JWK Set containing the key used to sign the JWT.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jku: Option<String>,
+    /// The public key used to sign the JWT.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwk: Option<Jwk>,
+    /// The URL of the X.509 certificate chain of the key used to sign
+    /// the JWT.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5u: Option<String>,
+    /// The X.509 certificate chain of the key used to sign the JWT, as
+    /// base64-encoded DER certificates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+    /// The base64url-encoded SHA-1 thumbprint of the X.509 certificate
+    /// of the key used to sign the JWT.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+    /// The base64url-encoded SHA-256 thumbprint of the X.509
+    /// certificate of the key used to sign the JWT.
+    #[serde(rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub x5t_s256: Option<String>,
+    /// The names of the header parameters that are extensions the
+    /// recipient must understand. See [`Header::check_critical`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crit: Option<Vec<String>>,
+    /// Whether the payload is base64url-encoded (RFC 7797). When
+    /// `false`, the payload is signed as is and must be detached, see
+    /// [`JWT::encode_detached`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b64: Option<bool>,
+    /// Indicates the content encryption algorithm of a JWE.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enc: Option<Encryption>,
+    /// Indicates the compression applied to the plaintext of a JWE
+    /// before encryption. Only `DEF` is supported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip: Option<String>,
+    /// The ephemeral public key used by ECDH-ES key agreement.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epk: Option<Jwk>,
+    /// The header parameters without a dedicated field.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 /// The Algorithm enum contains a list of supported algorithms.
@@ -116,6 +278,15 @@ pub enum Algorithm {
     /// RSASSA-PKCS1-v1_5 using SHA-512 hash algorithm.
     RS512,
 
+    /// RSASSA-PSS using SHA-256 hash algorithm and MGF1 with SHA-256.
+    PS256,
+
+    /// RSASSA-PSS using SHA-384 hash algorithm and MGF1 with SHA-384.
+    PS384,
+
+    /// RSASSA-PSS using SHA-512 hash algorithm and MGF1 with SHA-512.
+    PS512,
+
     /// ECDSA using P-256 and SHA-256.
     ES256,
 
@@ -124,22 +295,56 @@ pub enum Algorithm {
 
     /// ECDSA using P-521 and SHA-512.
     ES512,
+
+    /// EdDSA using Ed25519 (RFC 8037).
+    EdDSA,
+
+    /// Direct use of a shared symmetric key as the JWE content
+    /// encryption key.
+    #[serde(rename = "dir")]
+    Dir,
+
+    /// AES Key Wrap with a 128-bit key.
+    A128KW,
+
+    /// AES Key Wrap with a 256-bit key.
+    A256KW,
+
+    /// RSAES-OAEP using SHA-256 and MGF1 with SHA-256.
+    #[serde(rename = "RSA-OAEP-256")]
+    RsaOaep256,
+
+    /// No digital signature or MAC (an unsecured JWS, RFC 7515,
+    /// section 6). Only accepted when [`Validation::allow_unsecured`]
+    /// is set.
+    #[serde(rename = "none")]
+    None,
 }
 
-impl ToString for Algorithm {
-    /// Converts an Algorithm enum to a string.
-    fn to_string(&self) -> String {
-        match self {
-            Algorithm::HS256 => "HS256".to_string(),
-            Algorithm::HS384 => "HS384".to_string(),
-            Algorithm::HS512 => "HS512".to_string(),
-            Algorithm::RS256 => "RS256".to_string(),
-            Algorithm::RS384 => "RS384".to_string(),
-            Algorithm::RS512 => "RS512".to_string(),
-            Algorithm::ES256 => "ES256".to_string(),
-            Algorithm::ES384 => "ES384".to_string(),
-            Algorithm::ES512 => "ES512".to_string(),
-        }
+impl fmt::Display for Algorithm {
+    /// Formats an Algorithm enum as a string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Algorithm::HS256 => "HS256",
+            Algorithm::HS384 => "HS384",
+            Algorithm::HS512 => "HS512",
+            Algorithm::RS256 => "RS256",
+            Algorithm::RS384 => "RS384",
+            Algorithm::RS512 => "RS512",
+            Algorithm::PS256 => "PS256",
+            Algorithm::PS384 => "PS384",
+            Algorithm::PS512 => "PS512",
+            Algorithm::ES256 => "ES256",
+            Algorithm::ES384 => "ES384",
+            Algorithm::ES512 => "ES512",
+            Algorithm::EdDSA => "EdDSA",
+            Algorithm::Dir => "dir",
+            Algorithm::A128KW => "A128KW",
+            Algorithm::A256KW => "A256KW",
+            Algorithm::RsaOaep256 => "RSA-OAEP-256",
+            Algorithm::None => "none",
+        };
+        write!(f, "{name}")
     }
 }
 
@@ -159,6 +364,18 @@ impl Default for JWT {
                 kid: None,
                 typ: None,
                 cty: None,
+                jku: None,
+                jwk: None,
+                x5u: None,
+                x5c: None,
+                x5t: None,
+                x5t_s256: None,
+                crit: None,
+                b64: None,
+                enc: None,
+                zip: None,
+                epk: None,
+                extra: BTreeMap::new(),
             },
             claims: Claims::default(),
             signature: vec![],
@@ -175,10 +392,144 @@ impl Default for Header {
             kid: None,
             typ: Some("JWT".to_string()),
             cty: None,
+            jku: None,
+            jwk: None,
+            x5u: None,
+            x5c: None,
+            x5t: None,
+            x5t_s256: None,
+            crit: None,
+            b64: None,
+            enc: None,
+            zip: None,
+            epk: None,
+            extra: BTreeMap::new(),
         }
     }
 }
 
+impl PartialOrd for Header {
+    /// Compares headers field by field, in declaration order.
+    ///
+    /// The `extra` parameters are JSON values, which have no order, so
+    /// headers that differ only in them are not comparable.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let registered = (
+            &self.alg,
+            &self.kid,
+            &self.typ,
+            &self.cty,
+            &self.jku,
+            &self.jwk,
+            &self.x5u,
+            &self.x5c,
+            &self.x5t,
+            &self.x5t_s256,
+            &self.crit,
+            &self.b64,
+        )
+            .partial_cmp(&(
+                &other.alg,
+                &other.kid,
+                &other.typ,
+                &other.cty,
+                &other.jku,
+                &other.jwk,
+                &other.x5u,
+                &other.x5c,
+                &other.x5t,
+                &other.x5t_s256,
+                &other.crit,
+                &other.b64,
+            ))?;
+        let encryption =
+            (&self.enc, &self.zip, &self.epk).partial_cmp(&(&other.enc, &other.zip, &other.epk))?;
+        match registered.then(encryption) {
+            Ordering::Equal if self.extra != other.extra => None,
+            ordering => Some(ordering),
+        }
+    }
+}
+
+/// The header parameters registered by RFC 7515 and RFC 7516, which
+/// must not be listed in `crit`.
+const REGISTERED_HEADER_PARAMETERS: [&str; 20] = [
+    "alg", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit", "enc",
+    "zip", "epk", "apu", "apv", "iv", "tag", "p2s", "p2c",
+];
+
+/// The extension header parameters this crate understands when they are
+/// listed in `crit`.
+pub(crate) const UNDERSTOOD_CRITICAL_PARAMETERS: &[&str] = &["b64"];
+
+impl Header {
+    /// Serializes the header with the JSON Canonicalization Scheme
+    /// (RFC 8785), so equal headers always produce identical bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::Header;
+    /// let header = Header { kid: Some("k1".to_string()), ..Header::default() };
+    /// assert_eq!(
+    ///     header.to_canonical_json().unwrap(),
+    ///     r#"{"alg":"HS256","kid":"k1","typ":"JWT"}"#
+    /// );
+    /// ```
+    pub fn to_canonical_json(&self) -> Result<String, JwtError> {
+        Ok(cclm::canonical::to_canonical_json(self)?)
+    }
+
+    /// Checks the `crit` header parameter (RFC 7515, section 4.1.11).
+    ///
+    /// Every extension listed in `crit` must be present in the header
+    /// and named in `understood`. Returns `JwtError::InvalidHeader` if
+    /// `crit` is empty, lists a registered header parameter, or lists an
+    /// extension that is missing or not understood.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::Header;
+    /// let header: Header =
+    ///     serde_json::from_str(r#"{"alg":"HS256","crit":["exp"],"exp":1363284000}"#).unwrap();
+    /// assert!(header.check_critical(&["exp"]).is_ok());
+    /// assert!(header.check_critical(&[]).is_err());
+    /// ```
+    pub fn check_critical(&self, understood: &[&str]) -> Result<(), JwtError> {
+        let Some(crit) = &self.crit else {
+            return Ok(());
+        };
+        if crit.is_empty() {
+            return Err(JwtError::InvalidHeader(
+                "crit header parameter must not be empty".to_string(),
+            ));
+        }
+        for name in crit {
+            if REGISTERED_HEADER_PARAMETERS.contains(&name.as_str()) {
+                return Err(JwtError::InvalidHeader(format!(
+                    "crit header parameter must not list registered parameter {name}"
+                )));
+            }
+            let present = match name.as_str() {
+                "b64" => self.b64.is_some(),
+                _ => self.extra.contains_key(name),
+            };
+            if !present {
+                return Err(JwtError::InvalidHeader(format!(
+                    "Critical header parameter {name} is missing"
+                )));
+            }
+            if !understood.contains(&name.as_str()) {
+                return Err(JwtError::InvalidHeader(format!(
+                    "Critical header parameter {name} is not supported"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl JWT {
     /// Claims returns a default Claims struct.
     pub fn claims() -> Claims {
@@ -187,90 +538,588 @@ impl JWT {
     /// Decodes a JWT token. takes a mutable reference to a JWT struct
     /// and a reference to a slice of bytes representing a secret, and
     /// it returns a Result containing a string or an Error variant.
-    /// The function splits the JWT stored in the token field of the
-    /// JWT struct into its header, claims, and signature, decodes the
-    /// header and claims from base64, deserializes the header and
-    /// claims from JSON, and then verifies the JWT's signature using
-    /// the provided secret.
+    /// The function splits the compact serialization stored in the
+    /// token field of the JWT struct into its header, claims, and
+    /// signature, decodes the header and claims from base64url,
+    /// deserializes the header and claims from JSON, and then verifies
+    /// the JWT's signature using the provided secret. The raw signature
+    /// bytes are compared with the expected MAC in constant time.
     ///
     /// # Arguments
     ///
     /// * `secret` - A byte array containing the secret used to sign
-    /// the JWT.
+    ///   the JWT.
     ///
     /// # Returns
     ///
     /// * `Ok(String)` - The decoded JWT as a string.
+    /// * `Err(JwtError::FormatInvalid)` - If the token does not have
+    ///   exactly three segments.
+    /// * `Err(JwtError::SignatureInvalid)` - If the signature does not
+    ///   match.
     /// * `Err(Error)` - An error if the JWT is invalid or if there was
-    /// a problem decoding it.
+    ///   a problem decoding it.
     ///
     pub fn decode(&mut self, secret: &[u8]) -> Result<String, JwtError> {
-        let jwt = &self.token;
-        {
-            // Split the JWT into its header, claims, and signature
-            // let (header_b64, claims_b64_signature_b64) = jwt.split_once('.').unwrap();
-            let (header_b64, claims_b64_signature_b64) = match jwt.split_once('.') {
-                Some(tuple) => tuple,
-                None => return Err(JwtError::DecodeError("Invalid JWT".to_string())),
-            };
+        let key = hmac_key(&self.token, secret)?;
+        self.decode_with_key(&key)
+    }
+
+    /// Decodes a JWT token and verifies its signature with `key`.
+    ///
+    /// The `alg` header parameter must match the key's algorithm,
+    /// otherwise `JwtError::InvalidHeader` is returned without
+    /// verifying the signature. This prevents algorithm-confusion
+    /// attacks where a token claims a different algorithm than the one
+    /// the key is intended for.
+    ///
+    /// Once the signature has been verified, the `header`, `claims` and
+    /// `signature` fields are populated from the token.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, Header, HmacKey, JWT};
+    /// let key = HmacKey::new(Algorithm::HS384, b"secret").unwrap();
+    /// let header = Header { alg: Some(Algorithm::HS384), ..Header::default() };
+    /// let token = JWT::encode_with_key(header, JWT::claims(), &key).unwrap();
+    /// let mut jwt = JWT { token, ..JWT::default() };
+    /// assert!(jwt.decode_with_key(&key).is_ok());
+    /// assert_eq!(jwt.header.alg, Some(Algorithm::HS384));
+    /// ```
+    pub fn decode_with_key<K: Verifier + ?Sized>(&mut self, key: &K) -> Result<String, JwtError> {
+        *self = JWT::from_token(&self.token, key)?;
+        Ok(self.token.clone())
+    }
+
+    /// Decodes `token`, verifies its signature with `key` and returns
+    /// the populated JWT.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, HmacKey, JWT};
+    /// let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+    /// let mut claims = JWT::claims();
+    /// claims.set_issuer("https://issuer.example");
+    /// let token = JWT::encode_with_key(Default::default(), claims, &key).unwrap();
+    ///
+    /// let jwt = JWT::from_token(&token, &key).unwrap();
+    /// assert_eq!(jwt.claims.issuer(), Some("https://issuer.example"));
+    /// assert_eq!(jwt.token, token);
+    /// ```
+    pub fn from_token<K: Verifier + ?Sized>(token: &str, key: &K) -> Result<JWT, JwtError> {
+        let data = JWT::decode_as(token, key)?;
+        Ok(JWT::from_token_data(data, token))
+    }
+
+    /// Decodes `token`, verifies its signature with `key` and
+    /// deserializes its claims into `C`.
+    ///
+    /// Returns `JwtError::InvalidPayload` if the claims cannot be
+    /// deserialized into `C`, and `JwtError::InvalidHeader` if the
+    /// header lists an unsupported extension in `crit`. See
+    /// [`TokenData`] for an example.
+    pub fn decode_as<C, K>(token: &str, key: &K) -> Result<TokenData<C>, JwtError>
+    where
+        C: DeserializeOwned,
+        K: Verifier + ?Sized,
+    {
+        let (header_b64, claims_b64, _) = split_token(token)?;
+        let data = JWT::insecure_decode_as::<C>(token)?;
+
+        // Reject tokens whose algorithm does not match the key, or that
+        // rely on extensions this crate does not understand
+        check_algorithm(&data.header, key.algorithm())?;
+        data.header.check_critical(UNDERSTOOD_CRITICAL_PARAMETERS)?;
+        if !payload_is_encoded(&data.header)? {
+            return Err(JwtError::InvalidHeader(
+                "A payload with b64 false must be verified with JWT::verify_detached".to_string(),
+            ));
+        }
+
+        // Verify the signature over the JWS signing input
+        let signing_input = &token[..header_b64.len() + claims_b64.len() + 1];
+        key.verify(signing_input.as_bytes(), &data.signature)?;
+        Ok(data)
+    }
+
+    /// Decodes the header of `token` without verifying its signature.
+    ///
+    /// Useful to pick the key a token should be verified with.
+    pub fn decode_header(token: &str) -> Result<Header, JwtError> {
+        let (header_b64, _, _) = split_token(token)?;
+        Ok(serde_json::from_slice(
+            &URL_SAFE_NO_PAD.decode(header_b64)?,
+        )?)
+    }
+
+    /// Decodes `token` into a populated JWT **without** verifying its
+    /// signature or validating its claims.
+    ///
+    /// Only use this to inspect tokens, for example when debugging or
+    /// logging. Never trust the returned header or claims.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::JWT;
+    /// let token = JWT::generate(b"unknown secret").unwrap();
+    /// let jwt = JWT::insecure_decode(&token).unwrap();
+    /// assert_eq!(jwt.header.typ.as_deref(), Some("JWT"));
+    /// ```
+    pub fn insecure_decode(token: &str) -> Result<JWT, JwtError> {
+        let data = JWT::insecure_decode_as(token)?;
+        Ok(JWT::from_token_data(data, token))
+    }
 
-            let (claims_b64, inner_signature_b64) =
-                claims_b64_signature_b64.split_once('.').unwrap();
+    /// Decodes `token` and deserializes its claims into `C` **without**
+    /// verifying its signature or validating its claims.
+    ///
+    /// Only use this to inspect tokens, for example when debugging or
+    /// logging.
+    pub fn insecure_decode_as<C: DeserializeOwned>(token: &str) -> Result<TokenData<C>, JwtError> {
+        let (header_b64, claims_b64, signature_b64) = split_token(token)?;
 
-            // Base64-decode the header and claims
-            let header_json = general_purpose::STANDARD.decode(header_b64)?;
-            let claims_json = general_purpose::STANDARD.decode(claims_b64)?;
+        // Base64url-decode the header, claims and signature
+        let header_json = URL_SAFE_NO_PAD.decode(header_b64)?;
+        let claims_json = URL_SAFE_NO_PAD.decode(claims_b64)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature_b64)?;
 
-            // Allocate Vec of references to slices
-            let header_json_tmp = header_json.as_slice();
-            let claims_json = claims_json.as_slice();
+        // Deserialize the header and claims from JSON
+        let header: Header = serde_json::from_slice(&header_json)?;
+        let claims: C = serde_json::from_slice(&claims_json)?;
 
-            // Deserialize the header and claims from JSON
-            let _decoded_header: Header = serde_json::from_slice(header_json_tmp)?;
-            let _decoded_claims: Claims = serde_json::from_slice(claims_json)?;
+        Ok(TokenData {
+            header,
+            claims,
+            signature,
+        })
+    }
 
-            // Sign the JWT with the secret
-            type HmacSha256 = Hmac<Sha256>;
-            let mut hmac = HmacSha256::new_from_slice(secret).unwrap();
-            hmac.update(jwt.as_bytes());
-            let signature = hmac.finalize();
-            let signature_b64 = general_purpose::STANDARD.encode(signature.into_bytes());
+    fn from_token_data(data: TokenData<Claims>, token: &str) -> JWT {
+        JWT {
+            header: data.header,
+            claims: data.claims,
+            signature: data.signature,
+            token: token.to_string(),
+        }
+    }
 
-            // Compare the signature to the signature in the JWT
-            if signature_b64 != inner_signature_b64 {
-                return Err(JwtError::SignatureInvalid(signature_b64));
+    /// Decodes a JWT token and verifies its signature with a key from
+    /// `jwks`.
+    ///
+    /// The key is selected by the `kid` and `alg` header parameters
+    /// (see [`JwkSet::select`]). When the token has no `kid`, each key
+    /// matching `alg` is tried in turn, so that keys can be rotated by
+    /// publishing a new JWK Set. Returns `JwtError::KeyInvalid` if no
+    /// key matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Header, Jwk, JwkSet, JWT};
+    /// let jwk = Jwk::from_json(r#"{"kty":"oct","kid":"k1","alg":"HS256","k":"c2VjcmV0"}"#)
+    ///     .unwrap();
+    /// let key = jwk.to_key(cjwt::Algorithm::HS256).unwrap();
+    /// let header = Header { kid: Some("k1".to_string()), ..Header::default() };
+    /// let token = JWT::encode_with_key(header, JWT::claims(), &key).unwrap();
+    ///
+    /// let jwks = JwkSet { keys: vec![jwk] };
+    /// let mut jwt = JWT { token, ..JWT::default() };
+    /// assert!(jwt.decode_with_jwk_set(&jwks).is_ok());
+    /// ```
+    pub fn decode_with_jwk_set(&mut self, jwks: &JwkSet) -> Result<String, JwtError> {
+        let header = JWT::decode_header(&self.token)?;
+        let alg = header_algorithm(&header)?;
+        let kid = header.kid.as_deref();
+
+        let mut result = Err(JwtError::KeyInvalid(match kid {
+            Some(kid) => format!("No key in the JWK Set matches kid {kid} and alg {alg}"),
+            None => format!("No key in the JWK Set matches alg {alg}"),
+        }));
+        for jwk in jwks.select(kid, alg) {
+            result = jwk.to_key(alg).and_then(|key| self.decode_with_key(&key));
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Decodes a JWT token, verifies its signature with `key` and
+    /// checks its claims against `validation`.
+    ///
+    /// Claims are only checked once the signature has been verified.
+    /// Unsecured tokens are rejected unless
+    /// [`Validation::allow_unsecured`] is set, in which case they are
+    /// decoded with [`JWT::decode_unsecured`] and `key` is not used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, HmacKey, Validation, JWT};
+    /// use idk::jwt::JwtError;
+    ///
+    /// let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+    /// let mut claims = JWT::claims();
+    /// claims.set_issuer("https://issuer.example");
+    /// let token = JWT::encode_with_key(Default::default(), claims, &key).unwrap();
+    ///
+    /// let validation = Validation {
+    ///     issuers: vec!["https://other.example".to_string()],
+    ///     ..Validation::default()
+    /// };
+    /// let mut jwt = JWT { token, ..JWT::default() };
+    /// assert!(matches!(
+    ///     jwt.decode_with_validation(&key, &validation),
+    ///     Err(JwtError::IssuerInvalid(_))
+    /// ));
+    /// ```
+    pub fn decode_with_validation<K: Verifier + ?Sized>(
+        &mut self,
+        key: &K,
+        validation: &Validation,
+    ) -> Result<String, JwtError> {
+        let header = JWT::decode_header(&self.token)?;
+        if header.alg == Some(Algorithm::None) && validation.allow_unsecured {
+            return self.decode_unsecured(validation);
+        }
+        let token = self.decode_with_key(key)?;
+        validation.validate(&self.claims)?;
+        Ok(token)
+    }
+
+    /// Decodes an unsecured JWT token (`alg` of `none`) and checks its
+    /// claims against `validation`.
+    ///
+    /// Returns `JwtError::InvalidHeader` unless
+    /// [`Validation::allow_unsecured`] is set and the token is
+    /// unsecured, and `JwtError::SignatureInvalid` if its signature is
+    /// not empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, Header, Validation, JWT};
+    /// use idk::jwt::JwtError;
+    ///
+    /// let header = Header { alg: Some(Algorithm::None), ..Header::default() };
+    /// let token = JWT::encode(header, JWT::claims(), b"").unwrap();
+    /// assert!(token.ends_with('.'));
+    ///
+    /// let mut jwt = JWT { token, ..JWT::default() };
+    /// assert!(matches!(
+    ///     jwt.decode_unsecured(&Validation::default()),
+    ///     Err(JwtError::InvalidHeader(_))
+    /// ));
+    ///
+    /// let validation = Validation { allow_unsecured: true, ..Validation::default() };
+    /// assert!(jwt.decode_unsecured(&validation).is_ok());
+    /// ```
+    pub fn decode_unsecured(&mut self, validation: &Validation) -> Result<String, JwtError> {
+        if !validation.allow_unsecured {
+            return Err(JwtError::InvalidHeader(
+                "Unsecured tokens (alg none) are not allowed".to_string(),
+            ));
+        }
+        let jwt = JWT::insecure_decode(&self.token)?;
+        check_algorithm(&jwt.header, Algorithm::None)?;
+        jwt.header.check_critical(UNDERSTOOD_CRITICAL_PARAMETERS)?;
+        if !payload_is_encoded(&jwt.header)? {
+            return Err(JwtError::InvalidHeader(
+                "A payload with b64 false must be verified with JWT::verify_detached".to_string(),
+            ));
+        }
+        if !jwt.signature.is_empty() {
+            return Err(signature_invalid());
+        }
+        validation.validate(&jwt.claims)?;
+        *self = jwt;
+        Ok(self.token.clone())
+    }
+
+    /// Signs `payload` as a JWS with a detached payload (RFC 7515,
+    /// appendix F) and returns the compact serialization
+    /// `header..signature`, whose payload segment is empty.
+    ///
+    /// When `header.b64` is `Some(false)` the payload is signed as is,
+    /// without base64url encoding (RFC 7797), and `b64` is added to
+    /// `crit`. Otherwise the base64url-encoded payload is signed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, Header, HmacKey, JWT};
+    /// let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+    /// let header = Header { b64: Some(false), typ: None, ..Header::default() };
+    /// let token = JWT::encode_detached(header, b"$.02", &key).unwrap();
+    /// assert!(token.contains(".."));
+    ///
+    /// let header = JWT::verify_detached(&token, b"$.02", &key).unwrap();
+    /// assert_eq!(header.crit, Some(vec!["b64".to_string()]));
+    /// assert!(JWT::verify_detached(&token, b"$.03", &key).is_err());
+    /// ```
+    pub fn encode_detached<K: Signer + ?Sized>(
+        mut header: Header,
+        payload: &[u8],
+        key: &K,
+    ) -> Result<String, JwtError> {
+        let alg = *header.alg.get_or_insert(key.algorithm());
+        if alg != key.algorithm() {
+            return Err(JwtError::InvalidHeader(format!(
+                "Header algorithm {alg} does not match key algorithm {}",
+                key.algorithm()
+            )));
+        }
+        if header.b64 == Some(false) {
+            let crit = header.crit.get_or_insert_with(Vec::new);
+            if !crit.iter().any(|name| name == "b64") {
+                crit.push("b64".to_string());
             }
-            Ok(jwt.to_string())
         }
+
+        let header_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_string(&header)?);
+        let signing_input = detached_signing_input(&header, &header_b64, payload)?;
+        let signature = key.sign(&signing_input)?;
+        Ok(format!(
+            "{header_b64}..{}",
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    /// Verifies a JWS with a detached payload against `payload` and
+    /// returns its header.
+    ///
+    /// Returns `JwtError::FormatInvalid` unless the payload segment of
+    /// `token` is empty, and `JwtError::InvalidHeader` if `b64` is set
+    /// without being listed in `crit`. See [`JWT::encode_detached`].
+    pub fn verify_detached<K: Verifier + ?Sized>(
+        token: &str,
+        payload: &[u8],
+        key: &K,
+    ) -> Result<Header, JwtError> {
+        let segments: Vec<&str> = token.split('.').collect();
+        let [header_b64, "", signature_b64] = segments[..] else {
+            return Err(JwtError::FormatInvalid(
+                "Detached JWS must have 3 segments with an empty payload".to_string(),
+            ));
+        };
+        let header: Header = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header_b64)?)?;
+        check_algorithm(&header, key.algorithm())?;
+        header.check_critical(UNDERSTOOD_CRITICAL_PARAMETERS)?;
+
+        let signing_input = detached_signing_input(&header, header_b64, payload)?;
+        key.verify(&signing_input, &URL_SAFE_NO_PAD.decode(signature_b64)?)?;
+        Ok(header)
+    }
+
+    /// Encodes `claims` as an encrypted JWT, a JWE whose plaintext is
+    /// the JSON claims set.
+    ///
+    /// `header` is the JWE header and must name the content encryption
+    /// algorithm with `enc`. See [`Jwe::encrypt`].
+    pub fn encode_encrypted<K: Encrypter + ?Sized>(
+        header: Header,
+        claims: Claims,
+        key: &K,
+    ) -> Result<String, JwtError> {
+        let plaintext = serde_json::to_vec(&claims)?;
+        Jwe { header, plaintext }.encrypt(key)
+    }
+
+    /// Decrypts an encrypted JWT with `key` and returns the JWT
+    /// populated with its JWE header and claims.
+    ///
+    /// The `signature` field is left empty, since the token is not
+    /// signed. See [`Jwe::decrypt`].
+    pub fn decode_encrypted<K: Decrypter + ?Sized>(token: &str, key: &K) -> Result<JWT, JwtError> {
+        let jwe = Jwe::decrypt(token, key)?;
+        Ok(JWT {
+            header: jwe.header,
+            claims: serde_json::from_slice(&jwe.plaintext)?,
+            signature: Vec::new(),
+            token: token.to_string(),
+        })
+    }
+
+    /// Encodes a nested JWT: signs `claims` with `signer`, then
+    /// encrypts the resulting JWS with `encrypter` (RFC 7519, section
+    /// 11.2).
+    ///
+    /// `jwe_header` must name the content encryption algorithm with
+    /// `enc`. Its `cty` parameter is set to `JWT`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, DirectKey, Encryption, Header, HmacKey, JWT};
+    /// let signer = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+    /// let encrypter = DirectKey::new(&[7; 32]);
+    /// let mut claims = JWT::claims();
+    /// claims.set_subject("alice");
+    ///
+    /// let jwe_header = Header {
+    ///     alg: Some(Algorithm::Dir),
+    ///     enc: Some(Encryption::A256GCM),
+    ///     typ: None,
+    ///     ..Header::default()
+    /// };
+    /// let token =
+    ///     JWT::encode_nested(Header::default(), claims, &signer, jwe_header, &encrypter).unwrap();
+    ///
+    /// let jwt = JWT::decode_nested(&token, &encrypter, &signer).unwrap();
+    /// assert_eq!(jwt.claims.subject(), Some("alice"));
+    /// ```
+    pub fn encode_nested<S, E>(
+        header: Header,
+        claims: Claims,
+        signer: &S,
+        mut jwe_header: Header,
+        encrypter: &E,
+    ) -> Result<String, JwtError>
+    where
+        S: Signer + ?Sized,
+        E: Encrypter + ?Sized,
+    {
+        let jws = JWT::encode_with_key(header, claims, signer)?;
+        jwe_header.cty = Some("JWT".to_string());
+        Jwe {
+            header: jwe_header,
+            plaintext: jws.into_bytes(),
+        }
+        .encrypt(encrypter)
+    }
+
+    /// Decodes a nested JWT: decrypts `token` with `decrypter`, then
+    /// verifies the inner JWS with `verifier`.
+    ///
+    /// Returns `JwtError::InvalidHeader` unless the JWE header has a
+    /// `cty` of `JWT`. The returned JWT holds the inner JWS header,
+    /// claims, signature and token.
+    pub fn decode_nested<D, V>(token: &str, decrypter: &D, verifier: &V) -> Result<JWT, JwtError>
+    where
+        D: Decrypter + ?Sized,
+        V: Verifier + ?Sized,
+    {
+        let jwe = Jwe::decrypt(token, decrypter)?;
+        if !jwe
+            .header
+            .cty
+            .as_deref()
+            .map_or(false, |cty| cty.eq_ignore_ascii_case("JWT"))
+        {
+            return Err(JwtError::InvalidHeader(
+                "Nested JWT must have a cty header parameter of JWT".to_string(),
+            ));
+        }
+        let jws = String::from_utf8(jwe.plaintext)
+            .map_err(|_| JwtError::InvalidPayload("Nested JWT is not UTF-8".to_string()))?;
+        JWT::from_token(&jws, verifier)
     }
 
     /// Encodes a JWT token using the provided header, claims, and
     /// secret. It returns a Result containing a string or an Error
     /// variant. The function serializes the header and claims to JSON,
-    /// base64-encodes the header and claims, concatenates the encoded
-    /// header, claims, and separators, and then signs the JWT with the
-    /// provided secret.
+    /// base64url-encodes the header and claims without padding, signs
+    /// the `header.claims` signing input with the provided secret using
+    /// the HMAC algorithm named by `header.alg` (HS256 when unset), and
+    /// returns the RFC 7515 compact serialization
+    /// `header.claims.signature`.
+    ///
+    /// When `header.alg` is `none`, the secret is ignored and an
+    /// unsecured token `header.claims.` is returned.
     pub fn encode(header: Header, claims: Claims, secret: &[u8]) -> Result<String, JwtError> {
+        if header.alg == Some(Algorithm::None) {
+            return JWT::encode_with_key(header, claims, &Unsecured);
+        }
+        let key = HmacKey::new(header.alg.unwrap_or_default(), secret)?;
+        JWT::encode_with_key(header, claims, &key)
+    }
+
+    /// Encodes a JWT token using the provided header and claims, signed
+    /// with `key`.
+    ///
+    /// When `header.alg` is unset it is filled in with the key's
+    /// algorithm. Returns `JwtError::InvalidHeader` if it names a
+    /// different algorithm than the key.
+    pub fn encode_with_key<K: Signer + ?Sized>(
+        header: Header,
+        claims: Claims,
+        key: &K,
+    ) -> Result<String, JwtError> {
+        JWT::encode_json(header, claims, key, false)
+    }
+
+    /// Encodes a JWT token like [`JWT::encode_with_key`], but
+    /// serializes the header and claims with the JSON Canonicalization
+    /// Scheme (RFC 8785).
+    ///
+    /// The token then only depends on the header, claims and key, so
+    /// equal claims produce byte-identical tokens with deterministic
+    /// algorithms such as HMAC, RSASSA-PKCS1-v1_5, ECDSA (RFC 6979) and
+    /// EdDSA.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, HmacKey, JWT};
+    ///
+    /// let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+    /// let mut claims = JWT::claims();
+    /// claims.set_claim("b", "2");
+    /// claims.set_claim("a", "1");
+    /// let token = JWT::encode_canonical(Default::default(), claims, &key).unwrap();
+    /// assert!(token.starts_with("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJhIjoiMSIsImIiOiIyIn0."));
+    /// ```
+    pub fn encode_canonical<K: Signer + ?Sized>(
+        header: Header,
+        claims: Claims,
+        key: &K,
+    ) -> Result<String, JwtError> {
+        JWT::encode_json(header, claims, key, true)
+    }
+
+    /// Encodes a JWT token, serializing the header and claims with the
+    /// JSON Canonicalization Scheme when `canonical` is set.
+    fn encode_json<K: Signer + ?Sized>(
+        mut header: Header,
+        claims: Claims,
+        key: &K,
+        canonical: bool,
+    ) -> Result<String, JwtError> {
+        // Bind the header to the key's algorithm
+        let alg = *header.alg.get_or_insert(key.algorithm());
+        if alg != key.algorithm() {
+            return Err(JwtError::InvalidHeader(format!(
+                "Header algorithm {alg} does not match key algorithm {}",
+                key.algorithm()
+            )));
+        }
+
         // Serialize the header and claims to JSON
-        let header_json = serde_json::to_string(&header)?;
-        let claims_json = serde_json::to_string(&claims)?;
+        let (header_json, claims_json) = if canonical {
+            (header.to_canonical_json()?, claims.to_canonical_json()?)
+        } else {
+            (
+                serde_json::to_string(&header)?,
+                serde_json::to_string(&claims)?,
+            )
+        };
 
-        // Base64-encode the header and claims
-        let header_b64 = general_purpose::STANDARD.encode(header_json.as_bytes());
-        let claims_b64 = general_purpose::STANDARD.encode(claims_json.as_bytes());
+        // Base64url-encode the header and claims
+        let header_b64 = URL_SAFE_NO_PAD.encode(header_json.as_bytes());
+        let claims_b64 = URL_SAFE_NO_PAD.encode(claims_json.as_bytes());
 
-        // Concatenate the encoded header, claims, and separators
-        let jwt = format!("{header_b64}.{claims_b64}.");
+        // Concatenate the encoded header and claims into the signing input
+        let signing_input = format!("{header_b64}.{claims_b64}");
 
-        // Sign the JWT with the secret
-        type HmacSha256 = Hmac<Sha256>;
-        let mut hmac = HmacSha256::new_from_slice(secret)?;
-        hmac.update(jwt.as_bytes());
-        let signature = hmac.finalize();
+        // Sign the JWT with the key
+        let signature = key.sign(signing_input.as_bytes())?;
 
-        // Base64-encode the signature and concatenate it with the JWT
-        let signature_b64 = general_purpose::STANDARD.encode(signature.into_bytes());
-        let jwt = format!("{jwt}.{signature_b64}");
+        // Base64url-encode the signature and append it to the signing input
+        let signature_b64 = URL_SAFE_NO_PAD.encode(signature);
+        let jwt = format!("{signing_input}.{signature_b64}");
 
         Ok(jwt)
     }
@@ -297,47 +1146,101 @@ impl JWT {
         jwt.token.len()
     }
 
-    /// Validates a JWT token.
+    /// Validates a JWT token. The signature is verified with the HMAC
+    /// `secret` exactly as [`JWT::decode`] does, then the claims are
+    /// checked against the default [`Validation`] rules.
+    ///
+    /// Returns `JwtError::SignatureInvalid` if the signature does not
+    /// match.
     pub fn validate(&self, secret: &[u8]) -> Result<(), JwtError> {
-        let jwt = &self.token;
-        {
-            // Split the JWT into its header, claims, and signature
-            // let (header_b64, claims_b64_signature_b64) = jwt.split_once('.').unwrap();
-            let (header_b64, claims_b64_signature_b64) = match jwt.split_once('.') {
-                Some(tuple) => tuple,
-                None => return Err(JwtError::DecodeError("Invalid JWT".to_string())),
-            };
+        let key = hmac_key(&self.token, secret)?;
+        let jwt = JWT::from_token(&self.token, &key)?;
+        Validation::default().validate(&jwt.claims)
+    }
+}
 
-            let (claims_b64, inner_signature_b64) =
-                claims_b64_signature_b64.split_once('.').unwrap();
-
-            // Base64-decode the header and claims
-            let header_json = general_purpose::STANDARD.decode(header_b64)?;
-            let claims_json = general_purpose::STANDARD.decode(claims_b64)?;
-
-            // Allocate Vec of references to slices
-            let header_json_tmp = header_json.as_slice();
-            let claims_json = claims_json.as_slice();
-
-            // Deserialize the header and claims from JSON
-            let _decoded_header: Header = serde_json::from_slice(header_json_tmp)?;
-            let _decoded_claims: Claims = serde_json::from_slice(claims_json)?;
-
-            // Sign the JWT with the secret
-            type HmacSha256 = Hmac<Sha256>;
-            let mut hmac = HmacSha256::new_from_slice(secret)?;
-            hmac.update(jwt.as_bytes());
-            let signature = hmac.finalize();
-
-            // Verify the signature
-            let mut hmac = HmacSha256::new_from_slice(secret)?;
-            hmac.update(inner_signature_b64.as_bytes());
-            let inner_signature = hmac.finalize();
-            if signature != inner_signature {
-                return Err(JwtError::InvalidSignature("Invalid signature".to_string()));
-            }
+/// Returns the algorithm named by the `alg` header parameter.
+///
+/// Returns `JwtError::InvalidHeader` if the parameter is missing, since
+/// RFC 7515 requires it.
+pub(crate) fn header_algorithm(header: &Header) -> Result<Algorithm, JwtError> {
+    header
+        .alg
+        .ok_or_else(|| JwtError::InvalidHeader("Missing alg header parameter".to_string()))
+}
+
+/// Returns `false` if the `b64` header parameter says the payload is
+/// not base64url-encoded.
+///
+/// Returns `JwtError::InvalidHeader` if `b64` is set without being
+/// listed in `crit`, as RFC 7797 requires.
+pub(crate) fn payload_is_encoded(header: &Header) -> Result<bool, JwtError> {
+    let Some(b64) = header.b64 else {
+        return Ok(true);
+    };
+    let critical = header
+        .crit
+        .as_ref()
+        .map_or(false, |crit| crit.iter().any(|name| name == "b64"));
+    if !critical {
+        return Err(JwtError::InvalidHeader(
+            "b64 header parameter must be listed in crit".to_string(),
+        ));
+    }
+    Ok(b64)
+}
+
+/// Returns the signing input of a JWS with a detached `payload`.
+fn detached_signing_input(
+    header: &Header,
+    header_b64: &str,
+    payload: &[u8],
+) -> Result<Vec<u8>, JwtError> {
+    let mut signing_input = format!("{header_b64}.").into_bytes();
+    if payload_is_encoded(header)? {
+        signing_input.extend_from_slice(URL_SAFE_NO_PAD.encode(payload).as_bytes());
+    } else {
+        signing_input.extend_from_slice(payload);
+    }
+    Ok(signing_input)
+}
+
+/// Returns the HMAC key for the algorithm named by the `alg` header
+/// parameter of `token`.
+fn hmac_key(token: &str, secret: &[u8]) -> Result<HmacKey, JwtError> {
+    let header = JWT::decode_header(token)?;
+    HmacKey::new(header_algorithm(&header)?, secret)
+}
+
+/// Checks that the `alg` header parameter names `expected`.
+fn check_algorithm(header: &Header, expected: Algorithm) -> Result<(), JwtError> {
+    let alg = header_algorithm(header)?;
+    if alg != expected {
+        return Err(JwtError::InvalidHeader(format!(
+            "Header algorithm {alg} does not match key algorithm {expected}"
+        )));
+    }
+    Ok(())
+}
+
+/// Splits an RFC 7515 compact serialization into its header, claims
+/// and signature segments.
+///
+/// Returns `JwtError::FormatInvalid` unless the token has exactly three
+/// dot-separated segments with a non-empty header and claims.
+fn split_token(token: &str) -> Result<(&str, &str, &str), JwtError> {
+    let segments: Vec<&str> = token.split('.').collect();
+    match segments[..] {
+        [header, claims, signature] if !header.is_empty() && !claims.is_empty() => {
+            Ok((header, claims, signature))
         }
-        Ok(())
+        [_, _, _] => Err(JwtError::FormatInvalid(
+            "JWT header and claims must not be empty".to_string(),
+        )),
+        _ => Err(JwtError::FormatInvalid(format!(
+            "JWT must have 3 segments, found {}",
+            segments.len()
+        ))),
     }
 }
 
@@ -0,0 +1,13 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use rsa::rand_core::{OsRng, RngCore};
+
+/// Returns `len` random bytes from the operating system's
+/// cryptographically secure generator.
+pub(crate) fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
@@ -0,0 +1,201 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{random::random_bytes, Clock, Header, Signer, Validation, Verifier, JWT};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use cclm::{Claims, NumericDate};
+use idk::jwt::JwtError;
+use serde_json::Value;
+
+/// The claims that are issued anew, rather than copied, when a token is
+/// refreshed.
+const REISSUED_CLAIMS: [&str; 5] = ["iat", "exp", "nbf", "jti", ORIGINAL_ISSUED_AT];
+
+/// The claim recording when the first token of a refresh chain was
+/// issued, used to enforce [`RefreshPolicy::max_lifetime`].
+pub const ORIGINAL_ISSUED_AT: &str = "orig_iat";
+
+/// The rules used by [`JWT::refresh`] to issue a new token from a
+/// validated one.
+///
+/// Every refresh moves `exp` to `lifetime` seconds after the current
+/// time (a sliding expiry), but never past `max_lifetime` seconds after
+/// the first token of the chain was issued.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{Clock, RefreshPolicy};
+/// use dtt::DateTime;
+///
+/// let policy = RefreshPolicy {
+///     copy_claims: Some(vec!["sub".to_string(), "scope".to_string()]),
+///     lifetime: 900,
+///     max_lifetime: Some(86_400),
+///     clock: Clock::Fixed(DateTime::new()),
+///     ..RefreshPolicy::default()
+/// };
+/// assert_eq!(policy.lifetime, 900);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RefreshPolicy {
+    /// The names of the claims copied from the old token. Every claim
+    /// is copied when `None`. `iat`, `exp`, `nbf`, `jti` and `orig_iat`
+    /// are always issued anew.
+    pub copy_claims: Option<Vec<String>>,
+    /// The lifetime of the new token, in seconds.
+    pub lifetime: u64,
+    /// The maximum lifetime, in seconds, of a chain of refreshed tokens,
+    /// measured from the `orig_iat` claim, or the `iat` claim of a token
+    /// that was never refreshed. The lifetime is unbounded when `None`.
+    pub max_lifetime: Option<u64>,
+    /// The header of the new token. Its `alg` is filled in with the
+    /// key's algorithm when unset.
+    pub header: Header,
+    /// The source of the current time.
+    pub clock: Clock,
+}
+
+impl Default for RefreshPolicy {
+    /// Returns a policy that copies every claim, issues tokens valid for
+    /// one hour and does not limit the lifetime of a refresh chain.
+    fn default() -> Self {
+        RefreshPolicy {
+            copy_claims: None,
+            lifetime: 3_600,
+            max_lifetime: None,
+            header: Header {
+                alg: None,
+                ..Header::default()
+            },
+            clock: Clock::System,
+        }
+    }
+}
+
+impl RefreshPolicy {
+    /// Creates the default refresh policy.
+    pub fn new() -> RefreshPolicy {
+        RefreshPolicy::default()
+    }
+
+    /// Returns the claims of the token refreshed from `claims`.
+    ///
+    /// `claims` must come from a token whose signature and claims have
+    /// been verified, as [`JWT::refresh`] does.
+    ///
+    /// Returns `JwtError::SignatureExpired` if the maximum lifetime of
+    /// the refresh chain has been reached and
+    /// `JwtError::ExpirationInvalid` if `orig_iat` is malformed.
+    pub fn refresh_claims(&self, claims: &Claims) -> Result<Claims, JwtError> {
+        let now = self.clock.now()?.seconds();
+        let original_iat = claims
+            .get_claim_as::<NumericDate>(ORIGINAL_ISSUED_AT)
+            .map_err(|err| {
+                JwtError::ExpirationInvalid(format!("Invalid {ORIGINAL_ISSUED_AT} claim: {err}"))
+            })?
+            .or_else(|| claims.issued_at())
+            .map_or(now, |date| date.seconds());
+
+        let mut exp = now.saturating_add(seconds(self.lifetime));
+        if let Some(max_lifetime) = self.max_lifetime {
+            let deadline = original_iat.saturating_add(seconds(max_lifetime));
+            if now >= deadline {
+                return Err(JwtError::SignatureExpired(format!(
+                    "Token issued at {original_iat} can no longer be refreshed"
+                )));
+            }
+            exp = exp.min(deadline);
+        }
+
+        let Value::Object(mut copied) = serde_json::to_value(claims)? else {
+            return Err(JwtError::InvalidPayload(
+                "Claims must be a JSON object".to_string(),
+            ));
+        };
+        copied.retain(|name, _| {
+            !REISSUED_CLAIMS.contains(&name.as_str())
+                && self
+                    .copy_claims
+                    .as_ref()
+                    .map_or(true, |names| names.contains(name))
+        });
+        let mut refreshed: Claims = serde_json::from_value(Value::Object(copied))?;
+        refreshed.set_issued_at(now);
+        refreshed.set_expiration(exp);
+        refreshed.set_jwt_id(random_jwt_id());
+        refreshed.set_claim_value(ORIGINAL_ISSUED_AT, original_iat);
+        Ok(refreshed)
+    }
+}
+
+impl JWT {
+    /// Issues a new token from this one according to `policy` and signs
+    /// it with `key`.
+    ///
+    /// The `token` is decoded again, its signature verified with
+    /// `verifier` and its claims checked against `validation`, so the
+    /// `header` and `claims` fields are never trusted. Unsecured tokens
+    /// are rejected whatever [`Validation::allow_unsecured`] says. The
+    /// claims selected by the policy are then copied, and `iat`, `exp`
+    /// and `jti` are issued anew.
+    ///
+    /// `iat` and `exp` are read from [`RefreshPolicy::clock`], which
+    /// takes a `dtt::DateTime` as [`Clock::Fixed`] and is the system
+    /// clock by default. `jti` holds 128 bits from the operating
+    /// system's cryptographically secure generator rather than `vrd`, as
+    /// a predictable `jti` would let tokens issued to others be guessed.
+    ///
+    /// Returns the error of the verification if the token is not
+    /// valid, and `JwtError::SignatureExpired` if the maximum lifetime
+    /// of the refresh chain has been reached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cjwt::{Algorithm, HmacKey, RefreshPolicy, Validation, JWT};
+    ///
+    /// let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+    /// let mut claims = JWT::claims();
+    /// claims.set_subject("alice");
+    /// let token = JWT::encode_with_key(Default::default(), claims, &key).unwrap();
+    ///
+    /// let jwt = JWT { token, ..JWT::default() };
+    /// let validation = Validation::default();
+    /// let refreshed = jwt
+    ///     .refresh(&key, &validation, &key, &RefreshPolicy::default())
+    ///     .unwrap();
+    ///
+    /// let refreshed = JWT::from_token(&refreshed, &key).unwrap();
+    /// assert_eq!(refreshed.claims.subject(), Some("alice"));
+    /// assert!(refreshed.claims.jwt_id().is_some());
+    /// ```
+    pub fn refresh<V: Verifier + ?Sized, K: Signer + ?Sized>(
+        &self,
+        verifier: &V,
+        validation: &Validation,
+        key: &K,
+        policy: &RefreshPolicy,
+    ) -> Result<String, JwtError> {
+        let verified = JWT::from_token(&self.token, verifier)?;
+        validation.validate(&verified.claims)?;
+        let claims = policy.refresh_claims(&verified.claims)?;
+        JWT::encode_with_key(policy.header.clone(), claims, key)
+    }
+}
+
+/// Converts a number of seconds into an `i64`, saturating on overflow.
+fn seconds(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Returns a random `jti` of 128 bits, base64url-encoded.
+///
+/// The bytes come from the operating system's cryptographically secure
+/// generator rather than `vrd`, whose Mersenne Twister output can be
+/// predicted from earlier identifiers, which would let an attacker guess
+/// the `jti` of tokens issued to others.
+fn random_jwt_id() -> String {
+    URL_SAFE_NO_PAD.encode(random_bytes(16))
+}
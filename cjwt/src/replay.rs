@@ -0,0 +1,216 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use cclm::NumericDate;
+use idk::jwt::JwtError;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, RwLock},
+};
+
+/// A store of the `jti` values of the tokens already accepted, used to
+/// reject replayed tokens.
+///
+/// Set [`Validation::replay_store`](crate::Validation::replay_store) to
+/// make every token usable only once.
+pub trait ReplayStore: fmt::Debug + Send + Sync {
+    /// Records the use of the token identified by `jti`, which can be
+    /// forgotten once `expires_at` has passed.
+    ///
+    /// Returns `JwtError::TokenRevoked` if the token was already used.
+    fn check_and_record(
+        &self,
+        jti: &str,
+        expires_at: NumericDate,
+        now: NumericDate,
+    ) -> Result<(), JwtError>;
+}
+
+/// An in-memory [`ReplayStore`] that keeps each `jti` until its token
+/// expires.
+///
+/// # Example
+///
+/// ```
+/// use cclm::NumericDate;
+/// use cjwt::{MemoryReplayStore, ReplayStore};
+///
+/// let store = MemoryReplayStore::new();
+/// let (now, exp) = (NumericDate::from_seconds(100), NumericDate::from_seconds(200));
+/// assert!(store.check_and_record("a", exp, now).is_ok());
+/// assert!(store.check_and_record("a", exp, now).is_err());
+///
+/// // Expired entries are forgotten.
+/// let later = NumericDate::from_seconds(200);
+/// assert!(store.check_and_record("b", exp, later).is_ok());
+/// assert_eq!(store.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryReplayStore {
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl MemoryReplayStore {
+    /// Creates an empty store.
+    pub fn new() -> MemoryReplayStore {
+        MemoryReplayStore::default()
+    }
+
+    /// Returns the number of `jti` values held, including expired ones
+    /// that have not been purged yet.
+    pub fn len(&self) -> usize {
+        self.seen
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    /// Returns `true` if the store holds no `jti`.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ReplayStore for MemoryReplayStore {
+    fn check_and_record(
+        &self,
+        jti: &str,
+        expires_at: NumericDate,
+        now: NumericDate,
+    ) -> Result<(), JwtError> {
+        let mut seen = self.seen.lock().unwrap_or_else(|err| err.into_inner());
+        seen.retain(|_, expires_at| *expires_at > now.seconds());
+        if seen.contains_key(jti) {
+            return Err(JwtError::TokenRevoked(format!(
+                "Token {jti} has already been used"
+            )));
+        }
+        seen.insert(jti.to_string(), expires_at.seconds());
+        Ok(())
+    }
+}
+
+/// A denylist of revoked `jti` values backed by a file with one
+/// base64url-encoded `jti` per line.
+///
+/// Encoding lets any `jti` be revoked, whatever characters it holds, and
+/// `jti` values are compared exactly as given, without trimming. Blank
+/// lines and lines starting with `#` are ignored. Set
+/// [`Validation::denylist`](crate::Validation::denylist) to reject
+/// revoked tokens.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::JtiDenylist;
+///
+/// let path = std::env::temp_dir().join("cjwt-denylist-doc.txt");
+/// # let _ = std::fs::remove_file(&path);
+/// let denylist = JtiDenylist::open(&path).unwrap();
+/// denylist.revoke("token-1").unwrap();
+/// assert!(denylist.is_revoked("token-1"));
+///
+/// // The revocation survives a restart.
+/// assert!(JtiDenylist::open(&path).unwrap().is_revoked("token-1"));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct JtiDenylist {
+    path: PathBuf,
+    revoked: RwLock<HashSet<String>>,
+}
+
+impl JtiDenylist {
+    /// Opens the denylist stored at `path`, which is created on the
+    /// first revocation if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<JtiDenylist, JwtError> {
+        let denylist = JtiDenylist {
+            path: path.as_ref().to_path_buf(),
+            revoked: RwLock::new(HashSet::new()),
+        };
+        denylist.reload()?;
+        Ok(denylist)
+    }
+
+    /// Re-reads the file, picking up revocations made by other
+    /// processes.
+    ///
+    /// Returns `JwtError::FormatInvalid` if a line is not a
+    /// base64url-encoded `jti`.
+    pub fn reload(&self) -> Result<(), JwtError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let revoked = contents
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                URL_SAFE_NO_PAD
+                    .decode(line)
+                    .ok()
+                    .and_then(|jti| String::from_utf8(jti).ok())
+                    .ok_or_else(|| {
+                        JwtError::FormatInvalid(format!(
+                            "Invalid denylist entry on line {number} of {}",
+                            self.path.display()
+                        ))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        *self.revoked.write().unwrap_or_else(|err| err.into_inner()) = revoked;
+        Ok(())
+    }
+
+    /// Revokes the token identified by `jti` and appends it to the file.
+    ///
+    /// Returns `JwtError::InvalidPayload` if `jti` is empty.
+    pub fn revoke(&self, jti: &str) -> Result<(), JwtError> {
+        if jti.is_empty() {
+            return Err(JwtError::InvalidPayload(
+                "Cannot revoke an empty jti".to_string(),
+            ));
+        }
+        let mut revoked = self.revoked.write().unwrap_or_else(|err| err.into_inner());
+        if revoked.contains(jti) {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", URL_SAFE_NO_PAD.encode(jti))?;
+        revoked.insert(jti.to_string());
+        Ok(())
+    }
+
+    /// Returns `true` if the token identified by `jti` is revoked.
+    pub fn is_revoked(&self, jti: &str) -> bool {
+        self.revoked
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .contains(jti)
+    }
+
+    /// Returns the number of revoked `jti` values.
+    pub fn len(&self) -> usize {
+        self.revoked
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    /// Returns `true` if no token is revoked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
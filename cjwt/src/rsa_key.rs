@@ -0,0 +1,301 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{signature_invalid, Algorithm, Decrypter, Encrypter, Encryption, Signer, Verifier};
+use idk::jwt::JwtError;
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    rand_core::OsRng,
+    traits::PublicKeyParts,
+    Oaep, Pkcs1v15Sign, Pss, RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fmt;
+
+/// The smallest RSA modulus accepted, in bits (RFC 7518, section 3.3).
+pub const RSA_MIN_KEY_BITS: usize = 2048;
+
+/// An RSA key bound to one of the RSASSA-PKCS1-v1_5 (RS256, RS384,
+/// RS512) or RSASSA-PSS (PS256, PS384, PS512) algorithms, or to the
+/// RSA-OAEP-256 JWE key management algorithm.
+///
+/// A key loaded from a private key can both sign and verify; a key
+/// loaded from a public key can only verify. Likewise, an RSA-OAEP-256
+/// key needs the private key to decrypt a content encryption key.
+///
+/// # Example
+///
+/// ```no_run
+/// use cjwt::{Algorithm, RsaKey, Signer, Verifier};
+/// let pem = std::fs::read_to_string("private.pem").unwrap();
+/// let key = RsaKey::from_pem(Algorithm::PS256, &pem).unwrap();
+/// let signature = key.sign(b"header.claims").unwrap();
+/// assert!(key.to_public().verify(b"header.claims", &signature).is_ok());
+/// ```
+#[derive(Clone)]
+pub struct RsaKey {
+    algorithm: Algorithm,
+    private_key: Option<RsaPrivateKey>,
+    public_key: RsaPublicKey,
+}
+
+impl RsaKey {
+    /// Creates a signing key for `algorithm` from an RSA private key.
+    ///
+    /// Returns `JwtError::InvalidHeader` if `algorithm` is not an RSA
+    /// algorithm and `JwtError::KeyInvalid` if the modulus is shorter
+    /// than [`RSA_MIN_KEY_BITS`].
+    pub fn from_private_key(
+        algorithm: Algorithm,
+        private_key: RsaPrivateKey,
+    ) -> Result<RsaKey, JwtError> {
+        let public_key = private_key.to_public_key();
+        let mut key = RsaKey::from_public_key(algorithm, public_key)?;
+        key.private_key = Some(private_key);
+        Ok(key)
+    }
+
+    /// Creates a verification key for `algorithm` from an RSA public
+    /// key.
+    ///
+    /// Returns `JwtError::InvalidHeader` if `algorithm` is not an RSA
+    /// algorithm and `JwtError::KeyInvalid` if the modulus is shorter
+    /// than [`RSA_MIN_KEY_BITS`].
+    pub fn from_public_key(
+        algorithm: Algorithm,
+        public_key: RsaPublicKey,
+    ) -> Result<RsaKey, JwtError> {
+        if !is_rsa_algorithm(algorithm) {
+            return Err(JwtError::InvalidHeader(format!(
+                "{algorithm} is not an RSA algorithm"
+            )));
+        }
+        let bits = public_key.n().bits();
+        if bits < RSA_MIN_KEY_BITS {
+            return Err(JwtError::KeyInvalid(format!(
+                "RSA key of {bits} bits is shorter than {RSA_MIN_KEY_BITS} bits"
+            )));
+        }
+        Ok(RsaKey {
+            algorithm,
+            private_key: None,
+            public_key,
+        })
+    }
+
+    /// Creates a key for `algorithm` from a PEM document.
+    ///
+    /// Accepts PKCS#8 (`PRIVATE KEY`) and PKCS#1 (`RSA PRIVATE KEY`)
+    /// private keys as well as SubjectPublicKeyInfo (`PUBLIC KEY`) and
+    /// PKCS#1 (`RSA PUBLIC KEY`) public keys.
+    pub fn from_pem(algorithm: Algorithm, pem: &str) -> Result<RsaKey, JwtError> {
+        if let Ok(private_key) =
+            RsaPrivateKey::from_pkcs8_pem(pem).or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+        {
+            return RsaKey::from_private_key(algorithm, private_key);
+        }
+        let public_key = RsaPublicKey::from_public_key_pem(pem)
+            .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+            .map_err(|_| JwtError::KeyInvalid("Unsupported RSA PEM key".to_string()))?;
+        RsaKey::from_public_key(algorithm, public_key)
+    }
+
+    /// Creates a key for `algorithm` from a DER document.
+    ///
+    /// Accepts the same PKCS#8, PKCS#1 and SubjectPublicKeyInfo
+    /// structures as [`RsaKey::from_pem`].
+    pub fn from_der(algorithm: Algorithm, der: &[u8]) -> Result<RsaKey, JwtError> {
+        if let Ok(private_key) =
+            RsaPrivateKey::from_pkcs8_der(der).or_else(|_| RsaPrivateKey::from_pkcs1_der(der))
+        {
+            return RsaKey::from_private_key(algorithm, private_key);
+        }
+        let public_key = RsaPublicKey::from_public_key_der(der)
+            .or_else(|_| RsaPublicKey::from_pkcs1_der(der))
+            .map_err(|_| JwtError::KeyInvalid("Unsupported RSA DER key".to_string()))?;
+        RsaKey::from_public_key(algorithm, public_key)
+    }
+
+    /// Returns the RSA public key.
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Returns a copy of this key without the private part.
+    pub fn to_public(&self) -> RsaKey {
+        RsaKey {
+            algorithm: self.algorithm,
+            private_key: None,
+            public_key: self.public_key.clone(),
+        }
+    }
+
+    /// Returns `true` if this key holds a private key and can sign.
+    pub fn is_private(&self) -> bool {
+        self.private_key.is_some()
+    }
+
+    /// Returns the RSA private key, if any.
+    pub(crate) fn private_key(&self) -> Option<&RsaPrivateKey> {
+        self.private_key.as_ref()
+    }
+}
+
+impl fmt::Debug for RsaKey {
+    /// Formats the key without revealing the private key.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RsaKey")
+            .field("algorithm", &self.algorithm)
+            .field("bits", &self.public_key.n().bits())
+            .field("private", &self.is_private())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns `true` if `algorithm` is one of the RSA algorithms.
+fn is_rsa_algorithm(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512
+            | Algorithm::RsaOaep256
+    )
+}
+
+/// Returns `JwtError::InvalidHeader` unless `algorithm` is a signature
+/// algorithm.
+fn check_signature_algorithm(algorithm: Algorithm) -> Result<(), JwtError> {
+    if algorithm == Algorithm::RsaOaep256 {
+        return Err(JwtError::InvalidHeader(format!(
+            "{algorithm} is not a signature algorithm"
+        )));
+    }
+    Ok(())
+}
+
+/// Returns `JwtError::InvalidHeader` unless `algorithm` is
+/// RSA-OAEP-256.
+fn check_encryption_algorithm(algorithm: Algorithm) -> Result<(), JwtError> {
+    if algorithm != Algorithm::RsaOaep256 {
+        return Err(JwtError::InvalidHeader(format!(
+            "{algorithm} is not a key management algorithm"
+        )));
+    }
+    Ok(())
+}
+
+/// Hashes `input` with the digest used by `algorithm`.
+fn digest(algorithm: Algorithm, input: &[u8]) -> Vec<u8> {
+    match algorithm {
+        Algorithm::RS384 | Algorithm::PS384 => Sha384::digest(input).to_vec(),
+        Algorithm::RS512 | Algorithm::PS512 => Sha512::digest(input).to_vec(),
+        _ => Sha256::digest(input).to_vec(),
+    }
+}
+
+/// Returns the RSASSA-PKCS1-v1_5 scheme used by `algorithm`.
+fn pkcs1v15(algorithm: Algorithm) -> Pkcs1v15Sign {
+    match algorithm {
+        Algorithm::RS384 => Pkcs1v15Sign::new::<Sha384>(),
+        Algorithm::RS512 => Pkcs1v15Sign::new::<Sha512>(),
+        _ => Pkcs1v15Sign::new::<Sha256>(),
+    }
+}
+
+/// Returns the RSASSA-PSS scheme used by `algorithm`, with a salt as
+/// long as the digest output (RFC 7518, section 3.5).
+fn pss(algorithm: Algorithm) -> Pss {
+    match algorithm {
+        Algorithm::PS384 => Pss::new::<Sha384>(),
+        Algorithm::PS512 => Pss::new::<Sha512>(),
+        _ => Pss::new::<Sha256>(),
+    }
+}
+
+/// Returns `true` if `algorithm` uses RSASSA-PSS.
+fn is_pss(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512
+    )
+}
+
+impl Signer for RsaKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, JwtError> {
+        check_signature_algorithm(self.algorithm)?;
+        let private_key = self.private_key.as_ref().ok_or_else(|| {
+            JwtError::KeyInvalid("RSA public key cannot be used for signing".to_string())
+        })?;
+        let hashed = digest(self.algorithm, signing_input);
+        let signature = if is_pss(self.algorithm) {
+            private_key.sign_with_rng(&mut OsRng, pss(self.algorithm), &hashed)
+        } else {
+            private_key.sign_with_rng(&mut OsRng, pkcs1v15(self.algorithm), &hashed)
+        };
+        signature.map_err(|err| JwtError::KeyInvalid(err.to_string()))
+    }
+}
+
+impl Verifier for RsaKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), JwtError> {
+        check_signature_algorithm(self.algorithm)?;
+        let hashed = digest(self.algorithm, signing_input);
+        let verified = if is_pss(self.algorithm) {
+            self.public_key
+                .verify(pss(self.algorithm), &hashed, signature)
+        } else {
+            self.public_key
+                .verify(pkcs1v15(self.algorithm), &hashed, signature)
+        };
+        verified.map_err(|_| signature_invalid())
+    }
+}
+
+impl Encrypter for RsaKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn encrypt_key(&self, enc: Encryption) -> Result<(Vec<u8>, Vec<u8>), JwtError> {
+        check_encryption_algorithm(self.algorithm)?;
+        let cek = enc.generate_key();
+        let encrypted_key = self
+            .public_key
+            .encrypt(&mut OsRng, Oaep::new::<Sha256>(), &cek)
+            .map_err(|err| JwtError::KeyInvalid(err.to_string()))?;
+        Ok((cek, encrypted_key))
+    }
+}
+
+impl Decrypter for RsaKey {
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn decrypt_key(&self, encrypted_key: &[u8], enc: Encryption) -> Result<Vec<u8>, JwtError> {
+        check_encryption_algorithm(self.algorithm)?;
+        let private_key = self.private_key.as_ref().ok_or_else(|| {
+            JwtError::KeyInvalid("RSA public key cannot be used for decryption".to_string())
+        })?;
+        // Blinding keeps the timing of the private key operation from
+        // depending on the ciphertext (RUSTSEC-2023-0071)
+        let cek = private_key
+            .decrypt_blinded(&mut OsRng, Oaep::new::<Sha256>(), encrypted_key)
+            .map_err(|_| JwtError::DecryptionFailed("Decryption failed".to_string()))?;
+        enc.check_key(cek)
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::Header;
+use serde::{Deserialize, Serialize};
+
+/// The decoded parts of a token whose claims are deserialized into a
+/// user-defined type `C`.
+///
+/// Returned by [`JWT::decode_as`](crate::JWT::decode_as), which
+/// verifies the signature first, and by
+/// [`JWT::insecure_decode_as`](crate::JWT::insecure_decode_as), which
+/// does not.
+///
+/// # Example
+///
+/// ```
+/// use cjwt::{Algorithm, HmacKey, TokenData, JWT};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Session {
+///     sub: String,
+/// }
+///
+/// let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+/// let mut claims = JWT::claims();
+/// claims.set_subject("alice");
+/// let token = JWT::encode_with_key(Default::default(), claims, &key).unwrap();
+///
+/// let data: TokenData<Session> = JWT::decode_as(&token, &key).unwrap();
+/// assert_eq!(data.header.alg, Some(Algorithm::HS256));
+/// assert_eq!(data.claims.sub, "alice");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TokenData<C> {
+    /// The header of the token.
+    pub header: Header,
+    /// The claims of the token.
+    pub claims: C,
+    /// The raw signature bytes of the token.
+    pub signature: Vec<u8>,
+}
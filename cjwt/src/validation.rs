@@ -0,0 +1,353 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{JtiDenylist, ReplayStore};
+use cclm::{Audience, Claims, NumericDate};
+use dtt::DateTime;
+use idk::jwt::JwtError;
+use std::{fmt, sync::Arc};
+
+/// The source of the current time used when validating the time-based
+/// claims of a token.
+#[derive(Clone, Default)]
+pub enum Clock {
+    /// The system clock.
+    #[default]
+    System,
+    /// A fixed point in time, given as a `dtt::DateTime`.
+    Fixed(DateTime),
+    /// A closure returning the current time.
+    Custom(Arc<dyn Fn() -> NumericDate + Send + Sync>),
+}
+
+impl Clock {
+    /// Creates a clock that calls `now` to read the current time.
+    pub fn from_fn<F>(now: F) -> Clock
+    where
+        F: Fn() -> NumericDate + Send + Sync + 'static,
+    {
+        Clock::Custom(Arc::new(now))
+    }
+
+    /// Returns the current time according to this clock.
+    ///
+    /// Returns `JwtError::ExpirationInvalid` if a fixed `DateTime`
+    /// does not describe a valid date and time.
+    pub fn now(&self) -> Result<NumericDate, JwtError> {
+        match self {
+            Clock::System => Ok(NumericDate::now()),
+            Clock::Fixed(date_time) => date_time_to_numeric_date(date_time),
+            Clock::Custom(now) => Ok(now()),
+        }
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Clock::System => write!(f, "System"),
+            Clock::Fixed(date_time) => f.debug_tuple("Fixed").field(date_time).finish(),
+            Clock::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// The rules a token's claims must satisfy to be accepted.
+///
+/// The default validation checks `exp` and `nbf` when they are present
+/// against the system clock, with no leeway, does not require any claim
+/// and rejects unsecured tokens.
+///
+/// Each violated rule is reported with a specific `JwtError` variant:
+///
+/// - `SignatureExpired` if the token has expired or is older than
+///   `max_age`.
+/// - `ExpirationInvalid` if the token is not valid yet, was issued in
+///   the future, or a required time claim is missing.
+/// - `IssuerInvalid` if `iss` is missing or not an expected issuer.
+/// - `AudienceInvalid` if `aud` is missing or names no expected
+///   audience.
+/// - `InvalidPayload` if any other required claim is missing.
+/// - `TokenRevoked` if the token's `jti` is in the `denylist` or was
+///   already recorded by the `replay_store`.
+///
+/// # Example
+///
+/// ```
+/// use cclm::Claims;
+/// use cjwt::{Clock, Validation};
+/// use idk::jwt::JwtError;
+///
+/// let validation = Validation {
+///     required_claims: vec!["exp".to_string()],
+///     issuers: vec!["https://issuer.example".to_string()],
+///     leeway: 30,
+///     clock: Clock::from_fn(|| 1_000.into()),
+///     ..Validation::default()
+/// };
+///
+/// let mut claims = Claims::new();
+/// claims.set_issuer("https://issuer.example");
+/// claims.set_expiration(1_020);
+/// assert!(validation.validate(&claims).is_ok());
+///
+/// claims.set_expiration(900);
+/// assert!(matches!(
+///     validation.validate(&claims),
+///     Err(JwtError::SignatureExpired(_))
+/// ));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Validation {
+    /// The names of the claims that must be present.
+    pub required_claims: Vec<String>,
+    /// The accepted `iss` values. Any issuer is accepted when empty.
+    pub issuers: Vec<String>,
+    /// The accepted `aud` values. The token must name at least one of
+    /// them. Any audience is accepted when empty.
+    pub audiences: Vec<String>,
+    /// The clock skew, in seconds, tolerated when checking `exp`, `nbf`
+    /// and `iat`.
+    pub leeway: u64,
+    /// The maximum age of a token, in seconds, measured from its `iat`
+    /// claim. A token without `iat` is rejected when set.
+    pub max_age: Option<u64>,
+    /// Whether to reject tokens whose `exp` has passed.
+    pub validate_exp: bool,
+    /// Whether to reject tokens whose `nbf` has not been reached.
+    pub validate_nbf: bool,
+    /// The source of the current time.
+    pub clock: Clock,
+    /// Whether to accept unsecured tokens, whose `alg` is `none`. Only
+    /// enable this when the token is protected by other means.
+    pub allow_unsecured: bool,
+    /// The store recording the `jti` of every accepted token, so that
+    /// each token is accepted only once. Tokens must then have `jti` and
+    /// `exp` claims.
+    pub replay_store: Option<Arc<dyn ReplayStore>>,
+    /// The revoked `jti` values. Tokens must then have a `jti` claim.
+    pub denylist: Option<Arc<JtiDenylist>>,
+}
+
+impl Default for Validation {
+    /// Returns the default validation rules.
+    fn default() -> Self {
+        Validation {
+            required_claims: Vec::new(),
+            issuers: Vec::new(),
+            audiences: Vec::new(),
+            leeway: 0,
+            max_age: None,
+            validate_exp: true,
+            validate_nbf: true,
+            clock: Clock::System,
+            allow_unsecured: false,
+            replay_store: None,
+            denylist: None,
+        }
+    }
+}
+
+impl Validation {
+    /// Creates the default validation rules.
+    pub fn new() -> Validation {
+        Validation::default()
+    }
+
+    /// Validates `claims` against these rules.
+    pub fn validate(&self, claims: &Claims) -> Result<(), JwtError> {
+        for name in &self.required_claims {
+            if !claims.has_claim(name) {
+                return Err(missing_claim(name));
+            }
+        }
+
+        let now = self.clock.now()?.seconds();
+        let leeway = i64::try_from(self.leeway).unwrap_or(i64::MAX);
+
+        if self.validate_exp {
+            if let Some(exp) = time_claim(claims, "exp")? {
+                if now.saturating_sub(leeway) >= exp {
+                    return Err(JwtError::SignatureExpired(format!(
+                        "Token expired at {exp}"
+                    )));
+                }
+            }
+        }
+
+        if self.validate_nbf {
+            if let Some(nbf) = time_claim(claims, "nbf")? {
+                if now.saturating_add(leeway) < nbf {
+                    return Err(JwtError::ExpirationInvalid(format!(
+                        "Token is not valid before {nbf}"
+                    )));
+                }
+            }
+        }
+
+        if let Some(max_age) = self.max_age {
+            let iat = time_claim(claims, "iat")?.ok_or_else(|| missing_claim("iat"))?;
+            if now.saturating_add(leeway) < iat {
+                return Err(JwtError::ExpirationInvalid(format!(
+                    "Token was issued in the future at {iat}"
+                )));
+            }
+            let max_age = i64::try_from(max_age).unwrap_or(i64::MAX);
+            if now.saturating_sub(leeway) > iat.saturating_add(max_age) {
+                return Err(JwtError::SignatureExpired(format!(
+                    "Token issued at {iat} is older than {max_age} seconds"
+                )));
+            }
+        }
+
+        if !self.issuers.is_empty() {
+            let iss = claims
+                .get_claim("iss")
+                .ok_or_else(|| missing_claim("iss"))?;
+            if !self.issuers.iter().any(|issuer| issuer == iss) {
+                return Err(JwtError::IssuerInvalid(format!(
+                    "Issuer {iss} is not accepted"
+                )));
+            }
+        }
+
+        if !self.audiences.is_empty() {
+            let aud = claims
+                .get_claim_as::<Audience>("aud")
+                .map_err(|err| JwtError::AudienceInvalid(err.to_string()))?
+                .ok_or_else(|| missing_claim("aud"))?;
+            if !self.audiences.iter().any(|audience| aud.contains(audience)) {
+                return Err(JwtError::AudienceInvalid(format!(
+                    "Audience {aud} is not accepted"
+                )));
+            }
+        }
+
+        // Record the token last, so that rejected tokens are not
+        // counted as used
+        if self.denylist.is_some() || self.replay_store.is_some() {
+            let jti = claims.jwt_id().ok_or_else(|| missing_claim("jti"))?;
+            if let Some(denylist) = &self.denylist {
+                if denylist.is_revoked(jti) {
+                    return Err(JwtError::TokenRevoked(format!(
+                        "Token {jti} has been revoked"
+                    )));
+                }
+            }
+            if let Some(store) = &self.replay_store {
+                let exp = time_claim(claims, "exp")?.ok_or_else(|| missing_claim("exp"))?;
+                store.check_and_record(
+                    jti,
+                    NumericDate::from_seconds(exp.saturating_add(leeway)),
+                    NumericDate::from_seconds(now),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the error reported for a missing required claim.
+fn missing_claim(name: &str) -> JwtError {
+    let message = format!("Missing required claim {name}");
+    match name {
+        "exp" | "nbf" | "iat" => JwtError::ExpirationInvalid(message),
+        "iss" => JwtError::IssuerInvalid(message),
+        "aud" => JwtError::AudienceInvalid(message),
+        _ => JwtError::InvalidPayload(message),
+    }
+}
+
+/// Reads the time claim `name` as seconds since the epoch.
+fn time_claim(claims: &Claims, name: &str) -> Result<Option<i64>, JwtError> {
+    claims
+        .get_claim_as::<NumericDate>(name)
+        .map(|date| date.map(|date| date.seconds()))
+        .map_err(|err| JwtError::ExpirationInvalid(format!("Invalid {name} claim: {err}")))
+}
+
+/// Converts a `dtt::DateTime` into a `NumericDate`.
+///
+/// The month may be a name ("January") or a number ("01"), and the
+/// offset may be `+HH:MM` or `+HH:MM:SS`.
+fn date_time_to_numeric_date(date_time: &DateTime) -> Result<NumericDate, JwtError> {
+    let invalid = || JwtError::ExpirationInvalid(format!("Invalid date and time {date_time:?}"));
+    let month = month_number(&date_time.month).ok_or_else(invalid)?;
+    if !(1..=31).contains(&date_time.day)
+        || date_time.hour > 23
+        || date_time.minute > 59
+        || date_time.second > 60
+    {
+        return Err(invalid());
+    }
+    let offset = offset_seconds(&date_time.offset).ok_or_else(invalid)?;
+    let midnight =
+        NumericDate::from_ymd(i64::from(date_time.year), month, i64::from(date_time.day));
+    let seconds = midnight.seconds()
+        + i64::from(date_time.hour) * 3_600
+        + i64::from(date_time.minute) * 60
+        + i64::from(date_time.second)
+        - offset;
+    Ok(NumericDate::from_seconds(seconds))
+}
+
+/// Parses a month name or number into 1 to 12.
+fn month_number(month: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    let month = month.trim().to_ascii_lowercase();
+    match month.parse::<i64>() {
+        Ok(number) if (1..=12).contains(&number) => Some(number),
+        Ok(_) => None,
+        Err(_) => MONTHS
+            .iter()
+            .position(|name| *name == month)
+            .map(|index| index as i64 + 1),
+    }
+}
+
+/// Parses a UTC offset such as `+02:00`, `-05:30:00` or `Z` into
+/// seconds east of UTC.
+///
+/// Hours range from 0 to 23, and minutes and seconds from 0 to 59.
+fn offset_seconds(offset: &str) -> Option<i64> {
+    let offset = offset.trim();
+    if offset.is_empty() || offset == "Z" {
+        return Some(0);
+    }
+    let (sign, rest) = match offset.as_bytes()[0] {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    let mut parts = rest.split(':');
+    let mut seconds: i64 = 0;
+    for (max, unit) in [(23, 3_600), (59, 60), (59, 1)] {
+        let Some(part) = parts.next() else {
+            break;
+        };
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let value = part.parse::<i64>().ok().filter(|value| *value <= max)?;
+        seconds = seconds.checked_add(value.checked_mul(unit)?)?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(sign * seconds)
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    extern crate cclm;
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::cclm::{
+        vc::{
+            CredentialStatus, CredentialSubject, PresentedCredential, VerifiableCredential,
+            VerifiablePresentation,
+        },
+        NumericDate,
+    };
+    use self::cjwt::{Algorithm, Clock, EdDsaKey, Header, HmacKey, Validation, JWT};
+    use self::idk::jwt::JwtError;
+    use serde_json::json;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn key() -> EdDsaKey {
+        EdDsaKey::from_secret_bytes(&[7; 32]).unwrap()
+    }
+
+    fn header() -> Header {
+        Header {
+            alg: Some(Algorithm::EdDSA),
+            kid: Some("did:example:issuer#key-1".to_string()),
+            ..Header::default()
+        }
+    }
+
+    fn clock(seconds: i64) -> Clock {
+        Clock::from_fn(move || NumericDate::from_seconds(seconds))
+    }
+
+    fn validation() -> Validation {
+        Validation {
+            clock: clock(NOW),
+            ..Validation::default()
+        }
+    }
+
+    fn credential() -> VerifiableCredential {
+        let mut credential = VerifiableCredential::new(
+            "did:example:issuer",
+            "2023-11-14T22:13:20Z",
+            CredentialSubject::new("did:example:alice").with_claim("over18", true),
+        );
+        credential.id = Some("urn:uuid:0b8d7ac0-5d4f-4e6b-a4e0-4bbd2d6c5e27".to_string());
+        credential.expiration_date = Some("2023-11-15T22:13:20Z".to_string());
+        credential.credential_status = Some(CredentialStatus {
+            id: "https://issuer.example/status/24".to_string(),
+            status_type: "StatusList2021Entry".to_string(),
+            properties: Default::default(),
+        });
+        credential
+    }
+
+    /// Signs `claims` after replacing their `key` claim with `value`.
+    fn tampered(key: &str, value: serde_json::Value) -> String {
+        let mut claims = credential().to_claims().unwrap();
+        claims.set_claim_value(key, value);
+        JWT::encode_with_key(header(), claims, &self::key()).unwrap()
+    }
+
+    #[test]
+    fn test_issue_and_verify_credential() {
+        let token = JWT::issue_credential(header(), &credential(), &key()).unwrap();
+
+        let jwt = JWT::from_token(&token, &key()).unwrap();
+        assert_eq!(jwt.claims.issuer(), Some("did:example:issuer"));
+        assert_eq!(jwt.claims.subject(), Some("did:example:alice"));
+        assert_eq!(jwt.claims.not_before().unwrap().seconds(), NOW);
+        assert_eq!(jwt.claims.expiration().unwrap().seconds(), NOW + 86_400);
+        assert_eq!(
+            jwt.claims.get_claim_value("vc").unwrap()["credentialSubject"]["over18"],
+            json!(true)
+        );
+
+        let verified = JWT::verify_credential(&token, &key(), &validation()).unwrap();
+        assert_eq!(verified, credential());
+    }
+
+    #[test]
+    fn test_verify_credential_checks_the_token() {
+        let token = JWT::issue_credential(header(), &credential(), &key()).unwrap();
+        let other_key = EdDsaKey::from_secret_bytes(&[8; 32]).unwrap();
+        assert!(matches!(
+            JWT::verify_credential(&token, &other_key, &validation()),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+
+        let expired = Validation {
+            clock: clock(NOW + 2 * 86_400),
+            ..Validation::default()
+        };
+        assert!(matches!(
+            JWT::verify_credential(&token, &key(), &expired),
+            Err(JwtError::SignatureExpired(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_credential_rejects_mismatched_claims() {
+        for token in [
+            tampered("iss", json!("did:example:mallory")),
+            tampered("sub", json!("did:example:mallory")),
+            tampered("vc", json!({"type": ["VerifiableCredential"]})),
+            tampered("vc", json!("not a credential")),
+        ] {
+            assert!(matches!(
+                JWT::verify_credential(&token, &key(), &validation()),
+                Err(JwtError::InvalidPayload(_))
+            ));
+        }
+
+        let plain = JWT::encode_with_key(header(), JWT::claims(), &key()).unwrap();
+        assert!(matches!(
+            JWT::verify_credential(&plain, &key(), &validation()),
+            Err(JwtError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_credential_rejects_invalid_dates() {
+        let mut credential = credential();
+        credential.issuance_date = "14/11/2023".to_string();
+        assert!(matches!(
+            JWT::issue_credential(header(), &credential, &key()),
+            Err(JwtError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_and_verify_presentation() {
+        let credential = JWT::issue_credential(header(), &credential(), &key()).unwrap();
+        let holder_key = HmacKey::new(Algorithm::HS256, b"holder secret").unwrap();
+        let presentation = VerifiablePresentation::new(
+            "did:example:alice",
+            vec![PresentedCredential::Jwt(credential.clone())],
+        );
+        let token = JWT::issue_presentation(Header::default(), &presentation, &holder_key).unwrap();
+
+        let verified = JWT::verify_presentation(&token, &holder_key, &validation()).unwrap();
+        assert_eq!(verified, presentation);
+        let PresentedCredential::Jwt(presented) = &verified.verifiable_credential[0] else {
+            panic!("expected a JWT-VC");
+        };
+        assert_eq!(
+            JWT::verify_credential(presented, &key(), &validation())
+                .unwrap()
+                .credential_subject
+                .id
+                .as_deref(),
+            Some("did:example:alice")
+        );
+
+        let mut claims = presentation.to_claims().unwrap();
+        claims.set_issuer("did:example:mallory");
+        let forged = JWT::encode_with_key(Header::default(), claims, &holder_key).unwrap();
+        assert!(matches!(
+            JWT::verify_presentation(&forged, &holder_key, &validation()),
+            Err(JwtError::InvalidPayload(_))
+        ));
+    }
+}
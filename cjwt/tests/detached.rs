@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    extern crate base64;
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use self::cjwt::{Algorithm, Header, HmacKey, JWT};
+    use self::idk::jwt::JwtError;
+    use serde_json::json;
+
+    // The HMAC key and unencoded payload example of RFC 7797, section 4.
+    const RFC_7797_KEY: &str =
+        "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow";
+    const RFC_7797_PAYLOAD: &[u8] = b"$.02";
+    const RFC_7797_ENCODED: &str =
+        "eyJhbGciOiJIUzI1NiJ9..5mvfOroL-g7HyqJoozehmsaqmvTYGEq5jTI1gVvoEoQ";
+    const RFC_7797_UNENCODED: &str =
+        "eyJhbGciOiJIUzI1NiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19..A5dxf2s96_n5FLueVuW1Z_vh161FwXZC4YLPff6dmDY";
+
+    fn rfc_key() -> HmacKey {
+        HmacKey::new(
+            Algorithm::HS256,
+            &URL_SAFE_NO_PAD.decode(RFC_7797_KEY).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn unencoded_header() -> Header {
+        Header {
+            b64: Some(false),
+            typ: None,
+            ..Header::default()
+        }
+    }
+
+    #[test]
+    fn test_rfc_7797_examples() {
+        let key = rfc_key();
+        let header = JWT::verify_detached(RFC_7797_UNENCODED, RFC_7797_PAYLOAD, &key).unwrap();
+        assert_eq!(header.b64, Some(false));
+        assert_eq!(header.crit, Some(vec!["b64".to_string()]));
+        assert!(JWT::verify_detached(RFC_7797_ENCODED, RFC_7797_PAYLOAD, &key).is_ok());
+
+        let encoded = Header {
+            typ: None,
+            ..Header::default()
+        };
+        assert_eq!(
+            JWT::encode_detached(encoded, RFC_7797_PAYLOAD, &key).unwrap(),
+            RFC_7797_ENCODED
+        );
+        // The header parameters are serialized in a different order than
+        // in the RFC, so only the round trip is checked.
+        let token = JWT::encode_detached(unencoded_header(), RFC_7797_PAYLOAD, &key).unwrap();
+        assert!(JWT::verify_detached(&token, RFC_7797_PAYLOAD, &key).is_ok());
+    }
+
+    #[test]
+    fn test_detached_payload_is_verified() {
+        let key = rfc_key();
+        for token in [RFC_7797_ENCODED, RFC_7797_UNENCODED] {
+            assert!(matches!(
+                JWT::verify_detached(token, b"$.03", &key),
+                Err(JwtError::SignatureInvalid(_))
+            ));
+            let other = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+            assert!(matches!(
+                JWT::verify_detached(token, RFC_7797_PAYLOAD, &other),
+                Err(JwtError::SignatureInvalid(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_attached_payload_is_rejected() {
+        let key = rfc_key();
+        let token = RFC_7797_ENCODED.replace("..", ".JC4wMg.");
+        assert!(matches!(
+            JWT::verify_detached(&token, RFC_7797_PAYLOAD, &key),
+            Err(JwtError::FormatInvalid(_))
+        ));
+        // An attached base64url payload is still an ordinary JWS.
+        assert!(JWT::decode_header(&token).is_ok());
+    }
+
+    #[test]
+    fn test_b64_must_be_critical() {
+        let key = rfc_key();
+        let header: Header = serde_json::from_value(json!({"alg": "HS256", "b64": false})).unwrap();
+        let header_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_string(&header).unwrap());
+        let token = format!("{header_b64}..AAAA");
+        assert!(matches!(
+            JWT::verify_detached(&token, RFC_7797_PAYLOAD, &key),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_unencoded_payload_is_not_decoded_as_jwt() {
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let token = JWT::encode_detached(unencoded_header(), br#"{"sub":"alice"}"#, &key).unwrap();
+        let attached = token.replace(
+            "..",
+            &format!(".{}.", URL_SAFE_NO_PAD.encode(r#"{"sub":"alice"}"#)),
+        );
+        assert!(matches!(
+            JWT::from_token(&attached, &key),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_detached_checks_algorithm() {
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let header = Header {
+            alg: Some(Algorithm::HS512),
+            ..Header::default()
+        };
+        assert!(matches!(
+            JWT::encode_detached(header, b"payload", &key),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+#[cfg(test)]
+mod tests {
+    extern crate base64;
+    extern crate cclm;
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use self::cclm::Did;
+    use self::cjwt::{
+        Algorithm, DidJwkResolver, DidKeyResolver, DidResolver, EcdsaKey, EdDsaKey, Header,
+        HmacKey, Jwk, OfflineDidResolver, Signer, Validation, JWT,
+    };
+    use self::idk::jwt::JwtError;
+
+    // The did:key of the Ed25519 key whose seed is 32 zero bytes.
+    const ED25519_DID: &str = "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp";
+
+    // The P-256 did:key example of the did:key method specification.
+    const P256_DID: &str = "did:key:zDnaerDaTF5BXEavCrfRZEk316dpbLsfPDZ3WJ5hRTPFU2169";
+
+    // The P-256 did:jwk example of the did:jwk method specification.
+    const P256_DID_JWK: &str = "did:jwk:eyJjcnYiOiJQLTI1NiIsImt0eSI6IkVDIiwieCI6ImFjYklRaXVNczNpOF91c3pFakoydHBUdFJNNEVVM3l6OTFQSDZDZEgyVjAiLCJ5IjoiX0tjeUxqOXZXTXB0bm1LdG00NkdxRHo4d2Y3NEk1TEtncmwyR3pIM25TRSJ9";
+
+    fn did(value: &str) -> Did {
+        Did::parse(value).unwrap()
+    }
+
+    fn sign<K: Signer>(key: &K, iss: &str, kid: Option<&str>) -> String {
+        let mut claims = JWT::claims();
+        claims.set_issuer(iss);
+        claims.set_subject("alice");
+        let header = Header {
+            alg: Some(key.algorithm()),
+            kid: kid.map(str::to_string),
+            ..Header::default()
+        };
+        JWT::encode_with_key(header, claims, key).unwrap()
+    }
+
+    fn decode(token: &str) -> Result<String, JwtError> {
+        let mut jwt = JWT {
+            token: token.to_string(),
+            ..JWT::default()
+        };
+        jwt.decode_with_did(&OfflineDidResolver, &Validation::default())
+    }
+
+    #[test]
+    fn test_did_key_vectors() {
+        let ed25519 = EdDsaKey::from_secret_bytes(&[0; 32]).unwrap();
+        assert_eq!(
+            DidKeyResolver::did(&Jwk::from(&ed25519)).unwrap(),
+            did(ED25519_DID)
+        );
+        let jwk = DidKeyResolver.resolve(&did(ED25519_DID)).unwrap();
+        assert_eq!(jwk, {
+            let mut expected = Jwk::from(&ed25519.to_public());
+            expected.kid = Some(format!(
+                "{ED25519_DID}#z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp"
+            ));
+            expected
+        });
+
+        let jwk = DidKeyResolver.resolve(&did(P256_DID)).unwrap();
+        assert_eq!(jwk.crv.as_deref(), Some("P-256"));
+        assert_eq!(
+            jwk.x.as_deref(),
+            Some("fyNYMN0976ci7xqiSdag3buk-ZCwgXU4kz9XNkBlNUI")
+        );
+        assert_eq!(
+            jwk.y.as_deref(),
+            Some("hW2ojTNfH7Jbi8--CJUo3OCbH3y5n91g-IMA9MLMbTU")
+        );
+        assert_eq!(DidKeyResolver::did(&jwk).unwrap(), did(P256_DID));
+
+        for (algorithm, size, expected) in [
+            (
+                Algorithm::ES256,
+                32,
+                "did:key:zDnaejgmAHMLkBPMBWnkBxyGxpXx8LgE4WJAYDhwZzyoRAddF",
+            ),
+            (
+                Algorithm::ES384,
+                48,
+                "did:key:z82Lm2NURF6Xct4dKwEByDTBCYuqSDj16fStomTcBE2HsfJD2oXLZFDqdfH1eEEx7YHsBDc",
+            ),
+        ] {
+            let key = EcdsaKey::from_secret_bytes(algorithm, &vec![7; size]).unwrap();
+            let encoded = DidKeyResolver::did(&Jwk::from(&key)).unwrap();
+            assert_eq!(encoded, did(expected));
+            let jwk = DidKeyResolver.resolve(&encoded).unwrap();
+            assert_eq!(jwk.x, Jwk::from(&key).x);
+            assert_eq!(jwk.y, Jwk::from(&key).y);
+        }
+    }
+
+    #[test]
+    fn test_did_jwk_vector() {
+        let jwk = DidJwkResolver.resolve(&did(P256_DID_JWK)).unwrap();
+        assert_eq!(jwk.kty, "EC");
+        assert_eq!(
+            jwk.x.as_deref(),
+            Some("acbIQiuMs3i8_uszEjJ2tpTtRM4EU3yz91PH6CdH2V0")
+        );
+        assert_eq!(jwk.kid, Some(format!("{P256_DID_JWK}#0")));
+        assert!(jwk.to_key(Algorithm::ES256).is_ok());
+
+        let with_fragment = did(&format!("{P256_DID_JWK}#0"));
+        assert!(OfflineDidResolver.resolve(&with_fragment).is_ok());
+    }
+
+    #[test]
+    fn test_resolvers_reject_invalid_dids() {
+        let secret = Jwk::from(&EdDsaKey::from_secret_bytes(&[0; 32]).unwrap());
+        let private_did_jwk = format!(
+            "did:jwk:{}",
+            URL_SAFE_NO_PAD.encode(secret.to_json().unwrap())
+        );
+        for value in [
+            "did:web:example.com",
+            "did:key:6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp",
+            "did:key:z0OIl",
+            "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDoo",
+            "did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme",
+            "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp#other",
+            "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp/path",
+            "did:jwk:not.base64",
+            "did:jwk:bm90IGpzb24",
+            &private_did_jwk,
+            &format!("{P256_DID_JWK}#1"),
+        ] {
+            assert!(
+                matches!(
+                    OfflineDidResolver.resolve(&did(value)),
+                    Err(JwtError::KeyInvalid(_))
+                ),
+                "{value}"
+            );
+        }
+        assert!(DidKeyResolver.resolve(&did(P256_DID_JWK)).is_err());
+        assert!(DidJwkResolver.resolve(&did(P256_DID)).is_err());
+
+        let hmac = Jwk::from(&HmacKey::new(Algorithm::HS256, b"secret").unwrap());
+        assert!(matches!(
+            DidKeyResolver::did(&hmac),
+            Err(JwtError::KeyInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_with_did() {
+        let ed25519 = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+        let ed25519_did = DidKeyResolver::did(&Jwk::from(&ed25519))
+            .unwrap()
+            .to_string();
+        let es256 = EcdsaKey::from_secret_bytes(Algorithm::ES256, &[7; 32]).unwrap();
+        let es256_did = DidJwkResolver::did(&Jwk::from(&es256)).unwrap().to_string();
+        let fragment = ed25519_did.strip_prefix("did:key:").unwrap();
+
+        for token in [
+            sign(&ed25519, &ed25519_did, None),
+            sign(
+                &ed25519,
+                &ed25519_did,
+                Some(&format!("{ed25519_did}#{fragment}")),
+            ),
+            sign(&ed25519, &ed25519_did, Some(&format!("#{fragment}"))),
+            sign(&es256, &es256_did, None),
+            sign(&es256, &es256_did, Some(&format!("{es256_did}#0"))),
+            sign(&es256, &es256_did, Some("#0")),
+            sign(&es256, &es256_did, Some("key-1")),
+        ] {
+            assert!(decode(&token).is_ok(), "{token}");
+        }
+    }
+
+    #[test]
+    fn test_decode_with_did_rejects_wrong_issuer_or_key() {
+        let ed25519 = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+        let other = EdDsaKey::from_secret_bytes(&[8; 32]).unwrap();
+        let ed25519_did = DidKeyResolver::did(&Jwk::from(&ed25519))
+            .unwrap()
+            .to_string();
+        let other_did = DidKeyResolver::did(&Jwk::from(&other)).unwrap().to_string();
+
+        // Signed by another key than the one of the issuer DID
+        assert!(matches!(
+            decode(&sign(&other, &ed25519_did, None)),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        // A kid naming another DID than the issuer
+        assert!(matches!(
+            decode(&sign(&other, &ed25519_did, Some(&other_did))),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        // A kid naming no verification method of the issuer
+        assert!(matches!(
+            decode(&sign(&ed25519, &ed25519_did, Some("#key-2"))),
+            Err(JwtError::KeyInvalid(_))
+        ));
+        // An issuer that is not a DID, or cannot be resolved offline
+        assert!(matches!(
+            decode(&sign(&ed25519, "https://issuer.example", None)),
+            Err(JwtError::IssuerInvalid(_))
+        ));
+        assert!(matches!(
+            decode(&sign(&ed25519, "did:web:issuer.example", None)),
+            Err(JwtError::KeyInvalid(_))
+        ));
+        // An algorithm the resolved key cannot be used with
+        let hmac = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        assert!(matches!(
+            decode(&sign(&hmac, &ed25519_did, None)),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_with_did_checks_claims() {
+        let key = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+        let did = DidKeyResolver::did(&Jwk::from(&key)).unwrap().to_string();
+        let mut jwt = JWT {
+            token: sign(&key, &did, None),
+            ..JWT::default()
+        };
+        let validation = Validation {
+            issuers: vec!["did:key:other".to_string()],
+            ..Validation::default()
+        };
+        assert!(matches!(
+            jwt.decode_with_did(&OfflineDidResolver, &validation),
+            Err(JwtError::IssuerInvalid(_))
+        ));
+        assert!(jwt
+            .decode_with_did(&OfflineDidResolver, &Validation::default())
+            .is_ok());
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+    }
+}
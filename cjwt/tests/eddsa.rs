@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    extern crate base64;
+    extern crate cjwt;
+    extern crate ed25519_dalek;
+    extern crate idk;
+
+    use self::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use self::cjwt::{Algorithm, EdDsaKey, Header, Signer, Verifier, JWT};
+    use self::ed25519_dalek::{
+        pkcs8::{spki::der::pem::LineEnding, EncodePrivateKey, EncodePublicKey},
+        SigningKey,
+    };
+    use self::idk::jwt::JwtError;
+    use cclm::Claims;
+
+    // RFC 8037, Appendix A.4: Ed25519 Signing.
+    const A4_D: &str = "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A";
+    const A4_X: &str = "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo";
+    const A4_SIGNING_INPUT: &str = "eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc";
+    const A4_SIGNATURE: &str =
+        "hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg";
+
+    fn a4_key() -> EdDsaKey {
+        EdDsaKey::from_secret_bytes(&URL_SAFE_NO_PAD.decode(A4_D).unwrap()).unwrap()
+    }
+
+    fn jwt(token: &str) -> JWT {
+        JWT {
+            token: token.to_string(),
+            ..JWT::default()
+        }
+    }
+
+    #[test]
+    fn test_algorithm_eddsa_name() {
+        assert_eq!(Algorithm::EdDSA.to_string(), "EdDSA");
+        assert_eq!(
+            serde_json::to_string(&Algorithm::EdDSA).unwrap(),
+            "\"EdDSA\""
+        );
+    }
+
+    #[test]
+    fn test_sign_rfc8037_a4() {
+        let key = a4_key();
+        assert_eq!(
+            key.public_key_bytes(),
+            URL_SAFE_NO_PAD.decode(A4_X).unwrap()
+        );
+        let signature = key.sign(A4_SIGNING_INPUT.as_bytes()).unwrap();
+        assert_eq!(URL_SAFE_NO_PAD.encode(signature), A4_SIGNATURE);
+    }
+
+    #[test]
+    fn test_verify_rfc8037_a5() {
+        let key = EdDsaKey::from_public_bytes(&URL_SAFE_NO_PAD.decode(A4_X).unwrap()).unwrap();
+        let signature = URL_SAFE_NO_PAD.decode(A4_SIGNATURE).unwrap();
+        assert!(key.verify(A4_SIGNING_INPUT.as_bytes(), &signature).is_ok());
+        assert!(key.verify(b"something else", &signature).is_err());
+        assert!(key
+            .sign(A4_SIGNING_INPUT.as_bytes())
+            .unwrap_err()
+            .is_key_error());
+    }
+
+    #[test]
+    fn test_round_trip_eddsa_token() {
+        let key = a4_key();
+        let mut claims = Claims::new();
+        claims.set_issuer("joe");
+        let header = Header {
+            alg: Some(Algorithm::EdDSA),
+            ..Header::default()
+        };
+        let token = JWT::encode_with_key(header, claims, &key).unwrap();
+        assert!(token.starts_with(&URL_SAFE_NO_PAD.encode("{\"alg\":\"EdDSA\",\"typ\":\"JWT\"}")));
+        assert_eq!(
+            jwt(&token).decode_with_key(&key.to_public()).unwrap(),
+            token
+        );
+
+        let tampered = format!("{}x", &token[..token.len() - 1]);
+        assert!(jwt(&tampered).decode_with_key(&key).is_err());
+    }
+
+    #[test]
+    fn test_from_pem_and_der() {
+        let seed: [u8; 32] = URL_SAFE_NO_PAD.decode(A4_D).unwrap().try_into().unwrap();
+        let signing_key = SigningKey::from_bytes(&seed);
+        let private_pem = signing_key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let public_pem = signing_key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+
+        let private = EdDsaKey::from_pem(&private_pem).unwrap();
+        let public = EdDsaKey::from_pem(&public_pem).unwrap();
+        assert!(private.is_private());
+        assert!(!public.is_private());
+        assert_eq!(private.public_key_bytes(), public.public_key_bytes());
+
+        let private_der = signing_key.to_pkcs8_der().unwrap();
+        let public_der = signing_key.verifying_key().to_public_key_der().unwrap();
+        assert!(EdDsaKey::from_der(private_der.as_bytes())
+            .unwrap()
+            .is_private());
+        assert!(!EdDsaKey::from_der(public_der.as_bytes())
+            .unwrap()
+            .is_private());
+
+        assert!(EdDsaKey::from_pem("not a key").unwrap_err().is_key_error());
+    }
+
+    #[test]
+    fn test_eddsa_key_rejects_bad_lengths() {
+        assert!(EdDsaKey::from_secret_bytes(&[1; 31])
+            .unwrap_err()
+            .is_key_error());
+        assert!(EdDsaKey::from_public_bytes(&[1; 33])
+            .unwrap_err()
+            .is_key_error());
+        let signature = URL_SAFE_NO_PAD.decode(A4_SIGNATURE).unwrap();
+        let result = a4_key().verify(A4_SIGNING_INPUT.as_bytes(), &signature[..63]);
+        assert!(matches!(result, Err(JwtError::SignatureInvalid(_))));
+    }
+
+    #[test]
+    fn test_decode_rejects_eddsa_algorithm_confusion() {
+        // A token claiming EdDSA must not be accepted with an HMAC secret.
+        let token = format!("{A4_SIGNING_INPUT}.{A4_SIGNATURE}");
+        let result = jwt(&token).decode(b"secret");
+        assert!(matches!(result, Err(JwtError::InvalidHeader(_))));
+    }
+}
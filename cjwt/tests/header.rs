@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::cjwt::{Algorithm, DirectKey, Encryption, Header, HmacKey, Jwe, Jwk, JWT};
+    use self::idk::jwt::JwtError;
+    use serde_json::json;
+
+    // The leading bytes of a chain of two base64-encoded DER
+    // certificates.
+    const X5C: [&str; 2] = [
+        "MIIE3jCCA8agAwIBAgICAwEwDQYJKoZIhvcNAQEFBQAwYzELMAkGA1UEBhMCVVM",
+        "MIIE+zCCBGSgAwIBAgICAQ0wDQYJKoZIhvcNAQEFBQAwgbsxJDAiBgNVBAcTG1Zh",
+    ];
+
+    fn key() -> HmacKey {
+        HmacKey::new(Algorithm::HS256, b"secret").unwrap()
+    }
+
+    fn header(value: serde_json::Value) -> Header {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn sign(header: Header) -> String {
+        JWT::encode_with_key(header, JWT::claims(), &key()).unwrap()
+    }
+
+    #[test]
+    fn test_default_header_serialization_is_unchanged() {
+        assert_eq!(
+            serde_json::to_string(&Header::default()).unwrap(),
+            r#"{"alg":"HS256","typ":"JWT"}"#
+        );
+        assert!(Header::default().extra.is_empty());
+    }
+
+    #[test]
+    fn test_registered_parameters_round_trip() {
+        let value = json!({
+            "alg": "RS256",
+            "kid": "k1",
+            "typ": "JWT",
+            "jku": "https://issuer.example/jwks.json",
+            "jwk": {"kty": "oct", "k": "c2VjcmV0"},
+            "x5u": "https://issuer.example/chain.pem",
+            "x5c": X5C,
+            "x5t": "dGhpcyBpcyBhIFNIQTEgdGVzdA",
+            "x5t#S256": "dGhpcyBpcyBhIFNIQTI1NiB0ZXN0IHRodW1icHJpbnQ",
+            "crit": ["exp"],
+            "exp": 1363284000
+        });
+        let header = header(value.clone());
+        assert_eq!(header.alg, Some(Algorithm::RS256));
+        assert_eq!(
+            header.jku.as_deref(),
+            Some("https://issuer.example/jwks.json")
+        );
+        assert_eq!(header.jwk.as_ref().map(|jwk| jwk.kty.as_str()), Some("oct"));
+        assert_eq!(
+            header.x5u.as_deref(),
+            Some("https://issuer.example/chain.pem")
+        );
+        assert_eq!(header.x5c.as_deref(), Some(&X5C.map(String::from)[..]));
+        assert_eq!(header.x5t.as_deref(), Some("dGhpcyBpcyBhIFNIQTEgdGVzdA"));
+        assert!(header.x5t_s256.is_some());
+        assert_eq!(header.crit, Some(vec!["exp".to_string()]));
+        assert_eq!(header.extra.get("exp"), Some(&json!(1363284000)));
+        assert_eq!(header.extra.len(), 1);
+
+        assert_eq!(serde_json::to_value(&header).unwrap(), value);
+    }
+
+    #[test]
+    fn test_extra_parameters_round_trip() {
+        let mut header = Header::default();
+        header.extra.insert("tenant".to_string(), json!("acme"));
+        header.extra.insert("version".to_string(), json!(2));
+        header.extra.insert(
+            "ctx".to_string(),
+            json!({"region": "eu", "tags": ["a", "b"]}),
+        );
+
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(
+            json,
+            r#"{"alg":"HS256","typ":"JWT","ctx":{"region":"eu","tags":["a","b"]},"tenant":"acme","version":2}"#
+        );
+        assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+    }
+
+    #[test]
+    fn test_header_partial_ord() {
+        use std::cmp::Ordering;
+
+        let hs256 = Header::default();
+        let hs384 = Header {
+            alg: Some(Algorithm::HS384),
+            ..Header::default()
+        };
+        assert!(hs256 < hs384);
+        assert_eq!(hs256.partial_cmp(&hs256.clone()), Some(Ordering::Equal));
+
+        let mut kid = hs256.clone();
+        kid.kid = Some("key-1".to_string());
+        kid.extra.insert("tenant".to_string(), json!("acme"));
+        assert!(hs256 < kid);
+        assert!(kid < hs384);
+
+        // Headers differing only in their extra parameters are not
+        // comparable.
+        let mut tenant = hs256.clone();
+        tenant.extra.insert("tenant".to_string(), json!("acme"));
+        assert_eq!(hs256.partial_cmp(&tenant), None);
+    }
+
+    #[test]
+    fn test_decode_keeps_x5c_and_custom_parameters() {
+        let mut header = header(json!({"alg": "HS256", "x5c": X5C, "tenant": "acme"}));
+        header.jwk = Some(Jwk::from(&key()).to_public());
+        let token = sign(header.clone());
+
+        let jwt = JWT::from_token(&token, &key()).unwrap();
+        assert_eq!(jwt.header, header);
+        assert_eq!(jwt.header.x5c.unwrap().len(), 2);
+        assert_eq!(jwt.header.extra.get("tenant"), Some(&json!("acme")));
+        assert_eq!(JWT::decode_header(&token).unwrap().extra.len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_critical_extension_is_rejected() {
+        let token = sign(header(json!({
+            "alg": "HS256",
+            "crit": ["http://example.invalid/UNDEFINED"],
+            "http://example.invalid/UNDEFINED": true
+        })));
+        assert!(matches!(
+            JWT::from_token(&token, &key()),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        let mut jwt = JWT {
+            token: token.clone(),
+            ..JWT::default()
+        };
+        assert!(matches!(
+            jwt.decode(b"secret"),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            jwt.validate(b"secret"),
+            Err(JwtError::InvalidHeader(_))
+        ));
+
+        // The token can still be inspected without verification.
+        assert!(JWT::insecure_decode(&token).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_critical_extension_is_rejected_by_jwe() {
+        let key = DirectKey::new(&[7; 16]);
+        let jwe = Jwe {
+            header: header(json!({
+                "alg": "dir",
+                "enc": "A128GCM",
+                "crit": ["tenant"],
+                "tenant": "acme"
+            })),
+            plaintext: b"secret".to_vec(),
+        };
+        let token = jwe.encrypt(&key).unwrap();
+        assert!(matches!(
+            Jwe::decrypt(&token, &key),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        assert_eq!(jwe.header.enc, Some(Encryption::A128GCM));
+    }
+
+    #[test]
+    fn test_check_critical() {
+        let understood = ["tenant"];
+        let valid = header(json!({"alg": "HS256", "crit": ["tenant"], "tenant": "acme"}));
+        assert!(valid.check_critical(&understood).is_ok());
+        assert!(Header::default().check_critical(&[]).is_ok());
+
+        for invalid in [
+            json!({"alg": "HS256", "crit": []}),
+            json!({"alg": "HS256", "crit": ["alg"]}),
+            json!({"alg": "HS256", "crit": ["x5t#S256"], "x5t#S256": "AA"}),
+            json!({"alg": "HS256", "crit": ["tenant"]}),
+            json!({"alg": "HS256", "crit": ["tenant", "other"], "tenant": "acme", "other": 1}),
+        ] {
+            assert!(matches!(
+                header(invalid).check_critical(&understood),
+                Err(JwtError::InvalidHeader(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,426 @@
+#[cfg(test)]
+mod tests {
+    extern crate base64;
+    extern crate cjwt;
+    extern crate idk;
+    extern crate rsa;
+
+    use self::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use self::cjwt::{
+        AesKwKey, Algorithm, Decrypter, DirectKey, Encrypter, Encryption, Header, HmacKey, Jwe,
+        RsaKey, Signer, JWT, MAX_DECOMPRESSED_LEN,
+    };
+    use self::idk::jwt::JwtError;
+    use self::rsa::{rand_core::OsRng, sha2::Sha256, BigUint, Oaep, RsaPrivateKey};
+
+    // RFC 7516, Appendix A.3: Example JWE Using AES Key Wrap and
+    // AES_128_CBC_HMAC_SHA_256.
+    const A3_TOKEN: &str = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.AxY8DCtDaGlsbGljb3RoZQ.KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.U0m_YmjN04DJvceFICbCVQ";
+    const A3_KEY: &str = "GawgguFyGrWKav7AX4VKUg";
+
+    // RFC 7515, Appendix A.2 RSA key.
+    const A2_N: &str = "ofgWCuLjybRlzo0tZWJjNiuSfb4p4fAkd_wWJcyQoTbji9k0l8W26mPddxHmfHQp-Vaw-4qPCJrcS2mJPMEzP1Pt0Bm4d4QlL-yRT-SFd2lZS-pCgNMsD1W_YpRPEwOWvG6b32690r2jZ47soMZo9wGzjb_7OMg0LOL-bSf63kpaSHSXndS5z5rexMdbBYUsLA9e-KXBdQOS-UTo7WTBEMa2R2CapHg665xsmtdVMTBQY4uDZlxvb3qCo5ZwKh9kG4LT6_I5IhlJH7aGhyxXFvUK-DWNmoudF8NAco9_h9iaGNj8q2ethFkMLs91kzk2PAcDTW9gb54h4FRWyuXpoQ";
+    const A2_D: &str = "Eq5xpGnNCivDflJsRQBXHx1hdR1k6Ulwe2JZD50LpXyWPEAeP88vLNO97IjlA7_GQ5sLKMgvfTeXZx9SE-7YwVol2NXOoAJe46sui395IW_GO-pWJ1O0BkTGoVEn2bKVRUCgu-GjBVaYLU6f3l9kJfFNS3E0QbVdxzubSu3Mkqzjkn439X0M_V51gfpRLI9JYanrC4D4qAdGcopV_0ZHHzQlBjudU2QvXt4ehNYTCBr6XCLQUShb1juUO1ZdiYoFaFQT5Tw8bGUl_x_jTj3ccPDVZFD9pIuhLhBOneufuBiB4cS98l2SR_RQyGWSeWjnczT0QU91p1DhOVRuOopznQ";
+    const A2_P: &str = "4BzEEOtIpmVdVEZNCqS7baC4crd0pqnRH_5IB3jw3bcxGn6QLvnEtfdUdiYrqBdss1l58BQ3KhooKeQTa9AB0Hw_Py5PJdTJNPY8cQn7ouZ2KKDcmnPGBY5t7yLc1QlQ5xHdwW1VhvKn-nXqhJTBgIPgtldC-KDV5z-y2XDwGUc";
+    const A2_Q: &str = "uQPEfgmVtjL0Uyyx88GZFF1fOunH3-7cepKmtH4pxhtCoHqpWmT8YAmZxaewHgHAjLYsp1ZSe7zFYHj7C6ul7TjeLQeZD_YwD66t62wDmpe_HlB-TnBA-njbglfIsRLtXlnDzQkv5dTltRJ11BKBBypeeF6689rjcJIDEz9RWdc";
+
+    // Tokens encrypted by an independent implementation (Python
+    // `cryptography`).
+    const RSA_OAEP_256_TOKEN: &str = "eyJhbGciOiJSU0EtT0FFUC0yNTYiLCJlbmMiOiJBMjU2R0NNIn0.nrk_859HhAR6MPvqdw-qSvQk6axenqfkUvXIk2583HVqFWOfm__M8fV2UAEWcsGR92gdcGRnqmbIzN66opjzRtDsyXCNepDINeDekzO9f4czIP9itH-N5J2CT0GuQMwjFiALXWU6XvC6UvOebVyNzKHiHn-icreLL_FRhfH0dIUJV4w7vIIH9lls7RPQpeukUTs8dYIWcy_ptp4tCT3CL3nYkqYPrW7boMHvylFLXuepm2AsUvDWEupRhoAhMgh2PBOn-2EEqBTw4NfwDUF3kwra875IapUwKUwk0U-3vv4DnGtnGPPhbVMm-1anatQVapeYZjrPJdt1KEVJfvuPwQ.9mtg6PaNsAQRp24Q.LcdM-aP3wBQUBh9eYKj2l3ObsOdt4vRi98zEZ8FXAkJ7-vLieN9vv4Q67S5zmv7Z7deBb0mcsJ5z7h9d_5cH.NAvCk-JywmXYpLDgoOJecw";
+    const DIR_DEF_TOKEN: &str = "eyJhbGciOiJkaXIiLCJlbmMiOiJBMTI4R0NNIiwiemlwIjoiREVGIn0..JbQJRJnviKVJn4EL.Wd9r8a_8QVKWGA5trO3iaA9zf42hZOrQQejRSIwkjlU9OW9a.ME7ClVHWw-6N-KLWgO8XvQ";
+    const NESTED_TOKEN: &str = "eyJhbGciOiJBMjU2S1ciLCJlbmMiOiJBMjU2R0NNIiwiY3R5IjoiSldUIn0.8rQdMCzGqvAr8Mtxhg6V0p3hw5ukV-q2QECHiACKZozKrmeNd0oHrg.Qsdudtfkx-T8X52Z.kIMCowNWxffk9MSl41hXSWM6_V_8tKV_odQ94LjTJHq0KkRtWM5uFjktKp96sTp9rwqYUJvG2G0arerNU4bInRxQ8sw-GdPTn__nR_bxKWVPATBv59Y1V45wQchVr16JkWk0c_2pNs86_DxN28xWVJF3m315.dQTWntxeP6BjHYKi2yMt7g";
+
+    const ENCRYPTIONS: [Encryption; 3] = [
+        Encryption::A128GCM,
+        Encryption::A256GCM,
+        Encryption::A128CbcHs256,
+    ];
+
+    fn uint(value: &str) -> BigUint {
+        BigUint::from_bytes_be(&URL_SAFE_NO_PAD.decode(value).unwrap())
+    }
+
+    fn rsa_key() -> RsaKey {
+        let private_key = RsaPrivateKey::from_components(
+            uint(A2_N),
+            uint("AQAB"),
+            uint(A2_D),
+            vec![uint(A2_P), uint(A2_Q)],
+        )
+        .unwrap();
+        RsaKey::from_private_key(Algorithm::RsaOaep256, private_key).unwrap()
+    }
+
+    fn header(enc: Encryption) -> Header {
+        Header {
+            alg: None,
+            enc: Some(enc),
+            typ: None,
+            ..Header::default()
+        }
+    }
+
+    fn jwe(enc: Encryption, plaintext: &[u8]) -> Jwe {
+        Jwe {
+            header: header(enc),
+            plaintext: plaintext.to_vec(),
+        }
+    }
+
+    fn round_trip<K: Encrypter + Decrypter>(key: &K, enc: Encryption) {
+        for plaintext in [&b""[..], b"Live long and prosper.", &[0xa5; 1000]] {
+            let token = jwe(enc, plaintext).encrypt(key).unwrap();
+            let decrypted = Jwe::decrypt(&token, key).unwrap();
+            assert_eq!(decrypted.plaintext, plaintext);
+            assert_eq!(decrypted.header.alg, Some(Encrypter::algorithm(key)));
+            assert_eq!(decrypted.header.enc, Some(enc));
+        }
+    }
+
+    #[test]
+    fn test_decrypt_rfc7516_a3() {
+        let key = AesKwKey::new(Algorithm::A128KW, &URL_SAFE_NO_PAD.decode(A3_KEY).unwrap());
+        let jwe = Jwe::decrypt(A3_TOKEN, &key.unwrap()).unwrap();
+        assert_eq!(jwe.plaintext, b"Live long and prosper.");
+        assert_eq!(jwe.header.alg, Some(Algorithm::A128KW));
+        assert_eq!(jwe.header.enc, Some(Encryption::A128CbcHs256));
+    }
+
+    #[test]
+    fn test_decrypt_interop_tokens() {
+        let jwe = Jwe::decrypt(RSA_OAEP_256_TOKEN, &rsa_key()).unwrap();
+        assert_eq!(
+            jwe.plaintext,
+            b"The true sign of intelligence is not knowledge but imagination."
+        );
+
+        let key = DirectKey::new(&(0..16).collect::<Vec<u8>>());
+        let jwt = JWT::decode_encrypted(DIR_DEF_TOKEN, &key).unwrap();
+        assert_eq!(jwt.header.zip.as_deref(), Some("DEF"));
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+        assert!(jwt.signature.is_empty());
+    }
+
+    #[test]
+    fn test_rsa_oaep_256_key_unwrap() {
+        let private_key = RsaPrivateKey::from_components(
+            uint(A2_N),
+            uint("AQAB"),
+            uint(A2_D),
+            vec![uint(A2_P), uint(A2_Q)],
+        )
+        .unwrap();
+        let cek = [0x5a; 32];
+        let encrypted_key = private_key
+            .to_public_key()
+            .encrypt(&mut OsRng, Oaep::new::<Sha256>(), &cek)
+            .unwrap();
+
+        // The key is unwrapped with blinding, and gives the same key as
+        // the unblinded operation
+        let unwrapped = rsa_key()
+            .decrypt_key(&encrypted_key, Encryption::A256GCM)
+            .unwrap();
+        assert_eq!(unwrapped, cek);
+        assert_eq!(
+            private_key
+                .decrypt(Oaep::new::<Sha256>(), &encrypted_key)
+                .unwrap(),
+            unwrapped
+        );
+
+        let mut tampered = encrypted_key;
+        tampered[0] ^= 1;
+        assert!(matches!(
+            rsa_key().decrypt_key(&tampered, Encryption::A256GCM),
+            Err(JwtError::DecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_every_algorithm() {
+        for enc in ENCRYPTIONS {
+            round_trip(&DirectKey::new(&vec![7; enc.key_len()]), enc);
+            round_trip(&AesKwKey::new(Algorithm::A128KW, &[7; 16]).unwrap(), enc);
+            round_trip(&AesKwKey::new(Algorithm::A256KW, &[7; 32]).unwrap(), enc);
+            round_trip(&rsa_key(), enc);
+        }
+    }
+
+    #[test]
+    fn test_deflate_compression() {
+        let key = AesKwKey::new(Algorithm::A256KW, &[7; 32]).unwrap();
+        let mut jwe = jwe(Encryption::A256GCM, &[b'a'; 4096]);
+        let uncompressed = jwe.encrypt(&key).unwrap();
+        jwe.header.zip = Some("DEF".to_string());
+        let compressed = jwe.encrypt(&key).unwrap();
+        assert!(compressed.len() < uncompressed.len() / 10);
+        let decrypted = Jwe::decrypt(&compressed, &key).unwrap();
+        assert_eq!(decrypted.plaintext, jwe.plaintext);
+        assert_eq!(decrypted.header.zip.as_deref(), Some("DEF"));
+
+        jwe.header.zip = Some("GZIP".to_string());
+        assert!(matches!(jwe.encrypt(&key), Err(JwtError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_deflate_decompression_is_bounded() {
+        // Anyone holding the public key can encrypt a zip bomb
+        let key = rsa_key();
+        let mut jwe = jwe(Encryption::A256GCM, &vec![0; MAX_DECOMPRESSED_LEN]);
+        jwe.header.zip = Some("DEF".to_string());
+        let token = jwe.encrypt(&key).unwrap();
+        assert_eq!(
+            Jwe::decrypt(&token, &key).unwrap().plaintext.len(),
+            MAX_DECOMPRESSED_LEN
+        );
+
+        jwe.plaintext.push(0);
+        let token = jwe.encrypt(&key).unwrap();
+        assert!(token.len() < 10_000);
+        assert!(matches!(
+            Jwe::decrypt(&token, &key),
+            Err(JwtError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampered_token_fails_to_decrypt() {
+        for enc in ENCRYPTIONS {
+            let key = AesKwKey::new(Algorithm::A128KW, &[7; 16]).unwrap();
+            let token = jwe(enc, b"Live long and prosper.").encrypt(&key).unwrap();
+            let segments: Vec<&str> = token.split('.').collect();
+
+            for index in 1..5 {
+                let mut bytes = URL_SAFE_NO_PAD.decode(segments[index]).unwrap();
+                bytes[0] ^= 1;
+                let mut tampered = segments.clone();
+                let replaced = URL_SAFE_NO_PAD.encode(bytes);
+                tampered[index] = &replaced;
+                assert!(matches!(
+                    Jwe::decrypt(&tampered.join("."), &key),
+                    Err(JwtError::DecryptionFailed(_))
+                ));
+            }
+
+            let mut header = header(enc);
+            header.alg = Some(Algorithm::A128KW);
+            header.kid = Some("k1".to_string());
+            let header_b64 = URL_SAFE_NO_PAD.encode(serde_json::to_string(&header).unwrap());
+            let tampered = [
+                &header_b64[..],
+                segments[1],
+                segments[2],
+                segments[3],
+                segments[4],
+            ];
+            assert!(matches!(
+                Jwe::decrypt(&tampered.join("."), &key),
+                Err(JwtError::DecryptionFailed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_wrong_key_fails_to_decrypt() {
+        let token = jwe(Encryption::A128GCM, b"secret")
+            .encrypt(&AesKwKey::new(Algorithm::A128KW, &[7; 16]).unwrap())
+            .unwrap();
+        let other = AesKwKey::new(Algorithm::A128KW, &[8; 16]).unwrap();
+        assert!(matches!(
+            Jwe::decrypt(&token, &other),
+            Err(JwtError::DecryptionFailed(_))
+        ));
+
+        let token = jwe(Encryption::A128GCM, b"secret")
+            .encrypt(&DirectKey::new(&[7; 16]))
+            .unwrap();
+        assert!(matches!(
+            Jwe::decrypt(&token, &DirectKey::new(&[8; 16])),
+            Err(JwtError::DecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_header_errors() {
+        let key = DirectKey::new(&[7; 16]);
+        let mut jwe = jwe(Encryption::A128GCM, b"secret");
+
+        jwe.header.alg = Some(Algorithm::A128KW);
+        assert!(matches!(jwe.encrypt(&key), Err(JwtError::InvalidHeader(_))));
+
+        jwe.header.alg = None;
+        let token = jwe.encrypt(&key).unwrap();
+        let kw = AesKwKey::new(Algorithm::A128KW, &[7; 16]).unwrap();
+        assert!(matches!(
+            Jwe::decrypt(&token, &kw),
+            Err(JwtError::InvalidHeader(_))
+        ));
+
+        jwe.header.enc = None;
+        assert!(matches!(jwe.encrypt(&key), Err(JwtError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_malformed_tokens() {
+        let key = DirectKey::new(&[7; 16]);
+        for token in ["", "a.b.c", "a.b.c.d.e.f", ".a.b.c.d"] {
+            assert!(matches!(
+                Jwe::decrypt(token, &key),
+                Err(JwtError::FormatInvalid(_))
+            ));
+        }
+        let token = jwe(Encryption::A128GCM, b"secret").encrypt(&key).unwrap();
+        let (header, rest) = token.split_once('.').unwrap();
+        let with_key = format!("{header}.AAAA{rest}");
+        assert!(matches!(
+            Jwe::decrypt(&with_key, &key),
+            Err(JwtError::FormatInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_errors() {
+        assert!(matches!(
+            AesKwKey::new(Algorithm::HS256, &[7; 16]),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            AesKwKey::new(Algorithm::A256KW, &[7; 16]),
+            Err(JwtError::KeyInvalid(_))
+        ));
+        assert!(matches!(
+            jwe(Encryption::A256GCM, b"").encrypt(&DirectKey::new(&[7; 16])),
+            Err(JwtError::KeyInvalid(_))
+        ));
+
+        let token = jwe(Encryption::A128GCM, b"secret")
+            .encrypt(&rsa_key().to_public())
+            .unwrap();
+        assert!(matches!(
+            Jwe::decrypt(&token, &rsa_key().to_public()),
+            Err(JwtError::KeyInvalid(_))
+        ));
+        assert!(Jwe::decrypt(&token, &rsa_key()).is_ok());
+
+        assert!(matches!(
+            rsa_key().sign(b"header.claims"),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        let rs256 = RsaKey::from_public_key(Algorithm::RS256, rsa_key().public_key().clone());
+        assert!(matches!(
+            jwe(Encryption::A128GCM, b"").encrypt(&rs256.unwrap()),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_debug_hides_keys() {
+        let debug = format!("{:?}", AesKwKey::new(Algorithm::A128KW, &[7; 16]).unwrap());
+        assert!(debug.contains("A128KW"));
+        assert!(!debug.contains('7'));
+        assert_eq!(
+            format!("{:?}", DirectKey::new(&[7; 16])),
+            "DirectKey { algorithm: Dir, .. }"
+        );
+    }
+
+    #[test]
+    fn test_header_parameter_names() {
+        let header = Header {
+            alg: Some(Algorithm::RsaOaep256),
+            enc: Some(Encryption::A128CbcHs256),
+            zip: Some("DEF".to_string()),
+            typ: None,
+            ..Header::default()
+        };
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(
+            json,
+            r#"{"alg":"RSA-OAEP-256","enc":"A128CBC-HS256","zip":"DEF"}"#
+        );
+        assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+        assert_eq!(Algorithm::Dir.to_string(), "dir");
+        assert_eq!(Encryption::A128CbcHs256.to_string(), "A128CBC-HS256");
+
+        let epk =
+            r#"{"alg":"dir","enc":"A128GCM","epk":{"kty":"EC","crv":"P-256","x":"eA","y":"eQ"}}"#;
+        let header: Header = serde_json::from_str(epk).unwrap();
+        assert_eq!(header.epk.unwrap().crv.as_deref(), Some("P-256"));
+    }
+
+    #[test]
+    fn test_encrypted_jwt_round_trip() {
+        let key = AesKwKey::new(Algorithm::A256KW, &[7; 32]).unwrap();
+        let mut claims = JWT::claims();
+        claims.set_subject("alice");
+        let token = JWT::encode_encrypted(header(Encryption::A256GCM), claims, &key).unwrap();
+        let jwt = JWT::decode_encrypted(&token, &key).unwrap();
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+        assert_eq!(jwt.header.alg, Some(Algorithm::A256KW));
+        assert_eq!(jwt.token, token);
+    }
+
+    #[test]
+    fn test_decode_nested_interop_token() {
+        let decrypter = AesKwKey::new(Algorithm::A256KW, &(0..32).collect::<Vec<u8>>()).unwrap();
+        let verifier = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let jwt = JWT::decode_nested(NESTED_TOKEN, &decrypter, &verifier).unwrap();
+        assert_eq!(jwt.header.alg, Some(Algorithm::HS256));
+        assert_eq!(jwt.claims.issuer(), Some("joe"));
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+        assert_eq!(jwt.token.split('.').count(), 3);
+
+        let other = HmacKey::new(Algorithm::HS256, b"other").unwrap();
+        assert!(matches!(
+            JWT::decode_nested(NESTED_TOKEN, &decrypter, &other),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_nested_round_trip() {
+        let signer = HmacKey::new(Algorithm::HS512, b"secret").unwrap();
+        let encrypter = rsa_key();
+        let mut claims = JWT::claims();
+        claims.set_subject("alice");
+        let signing_header = Header {
+            alg: Some(Algorithm::HS512),
+            ..Header::default()
+        };
+        let token = JWT::encode_nested(
+            signing_header,
+            claims,
+            &signer,
+            header(Encryption::A128CbcHs256),
+            &encrypter.to_public(),
+        )
+        .unwrap();
+        assert_eq!(token.split('.').count(), 5);
+
+        let jwe = Jwe::decrypt(&token, &encrypter).unwrap();
+        assert_eq!(jwe.header.cty.as_deref(), Some("JWT"));
+
+        let jwt = JWT::decode_nested(&token, &encrypter, &signer).unwrap();
+        assert_eq!(jwt.header.alg, Some(Algorithm::HS512));
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+        assert_eq!(jwt.token, String::from_utf8(jwe.plaintext).unwrap());
+    }
+
+    #[test]
+    fn test_decode_nested_requires_jwt_content_type() {
+        let key = DirectKey::new(&[7; 16]);
+        let signer = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let jws = JWT::encode_with_key(Header::default(), JWT::claims(), &signer).unwrap();
+        let token = Jwe {
+            header: header(Encryption::A128GCM),
+            plaintext: jws.into_bytes(),
+        }
+        .encrypt(&key)
+        .unwrap();
+        assert!(matches!(
+            JWT::decode_nested(&token, &key, &signer),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+}
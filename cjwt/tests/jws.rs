@@ -0,0 +1,339 @@
+#[cfg(test)]
+mod tests {
+    extern crate base64;
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use self::cjwt::{
+        Algorithm, EcdsaKey, Header, HmacKey, JwkKey, JwkSet, Jws, JwsSignature, SignaturePolicy,
+        Verifier,
+    };
+    use self::idk::jwt::JwtError;
+    use serde_json::json;
+
+    // RFC 7515, Appendix A.6: Example JWS Using General JWS JSON
+    // Serialization. Its signatures are those of Appendices A.2 and A.3.
+    const PAYLOAD: &str = "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+    const RS256_SIGNATURE: &str = "cC4hiUPoj9Eetdgtv3hF80EGrhuB__dzERat0XF9g2VtQgr9PJbu3XOiZj5RZmh7AAuHIm4Bh-0Qc_lF5YKt_O8W2Fp5jujGbds9uJdbF9CUAr7t1dnZcAcQjbKBYNX4BAynRFdiuB--f_nZLgrnbyTyWzO75vRK5h6xBArLIARNPvkSjtQBMHlb1L07Qe7K0GarZRmB_eSN9383LcOLn6_dO--xi12jzDwusC-eOkHWEsqtFZESc6BfI7noOPqvhJ1phCnvWh6IeYI2w9QOYEUipUTI8np6LbgGY9Fs98rqVt5AXLIhWkWywlVmtVrBp0igcN_IoypGlUPQGe77Rw";
+    const ES256_SIGNATURE: &str =
+        "DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQxfKTUJqPP3-Kg6NU1Q";
+    const A2_N: &str = "ofgWCuLjybRlzo0tZWJjNiuSfb4p4fAkd_wWJcyQoTbji9k0l8W26mPddxHmfHQp-Vaw-4qPCJrcS2mJPMEzP1Pt0Bm4d4QlL-yRT-SFd2lZS-pCgNMsD1W_YpRPEwOWvG6b32690r2jZ47soMZo9wGzjb_7OMg0LOL-bSf63kpaSHSXndS5z5rexMdbBYUsLA9e-KXBdQOS-UTo7WTBEMa2R2CapHg665xsmtdVMTBQY4uDZlxvb3qCo5ZwKh9kG4LT6_I5IhlJH7aGhyxXFvUK-DWNmoudF8NAco9_h9iaGNj8q2ethFkMLs91kzk2PAcDTW9gb54h4FRWyuXpoQ";
+    const A3_X: &str = "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU";
+    const A3_Y: &str = "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0";
+    const RSA_KID: &str = "2010-12-29";
+    const EC_KID: &str = "e9bc097a-ce51-4036-9562-d2ade882db0d";
+
+    fn general() -> String {
+        json!({
+            "payload": PAYLOAD,
+            "signatures": [
+                {
+                    "protected": "eyJhbGciOiJSUzI1NiJ9",
+                    "header": {"kid": RSA_KID},
+                    "signature": RS256_SIGNATURE
+                },
+                {
+                    "protected": "eyJhbGciOiJFUzI1NiJ9",
+                    "header": {"kid": EC_KID},
+                    "signature": ES256_SIGNATURE
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    // RFC 7515, Appendix A.7: Example JWS Using Flattened JWS JSON
+    // Serialization.
+    fn flattened() -> String {
+        json!({
+            "payload": PAYLOAD,
+            "protected": "eyJhbGciOiJFUzI1NiJ9",
+            "header": {"kid": EC_KID},
+            "signature": ES256_SIGNATURE
+        })
+        .to_string()
+    }
+
+    fn jwks() -> JwkSet {
+        JwkSet::from_json(
+            &json!({"keys": [
+                {"kty": "RSA", "kid": RSA_KID, "n": A2_N, "e": "AQAB"},
+                {"kty": "EC", "kid": EC_KID, "crv": "P-256", "x": A3_X, "y": A3_Y}
+            ]})
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn keys() -> (JwkKey, JwkKey) {
+        let jwks = jwks();
+        (
+            jwks.find(RSA_KID)
+                .unwrap()
+                .to_key(Algorithm::RS256)
+                .unwrap(),
+            jwks.find(EC_KID).unwrap().to_key(Algorithm::ES256).unwrap(),
+        )
+    }
+
+    fn header(value: serde_json::Value) -> Header {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn empty_header() -> Header {
+        Header {
+            alg: None,
+            typ: None,
+            ..Header::default()
+        }
+    }
+
+    #[test]
+    fn test_rfc7515_a6_general() {
+        let jws = Jws::from_json(&general()).unwrap();
+        assert_eq!(jws.signatures.len(), 2);
+        assert_eq!(
+            jws.payload().unwrap(),
+            URL_SAFE_NO_PAD.decode(PAYLOAD).unwrap()
+        );
+
+        let (rsa, ec) = keys();
+        let headers = jws.verify(&[&rsa, &ec], SignaturePolicy::All).unwrap();
+        assert_eq!(headers[0].alg, Some(Algorithm::RS256));
+        assert_eq!(headers[0].kid.as_deref(), Some(RSA_KID));
+        assert_eq!(headers[1].alg, Some(Algorithm::ES256));
+        assert_eq!(headers[1].kid.as_deref(), Some(EC_KID));
+
+        let headers = jws
+            .verify_with_jwk_set(&jwks(), SignaturePolicy::All)
+            .unwrap();
+        assert_eq!(headers.len(), 2);
+
+        let json: serde_json::Value = serde_json::from_str(&jws.to_json().unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::from_str::<serde_json::Value>(&general()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rfc7515_a7_flattened() {
+        let jws = Jws::from_json(&flattened()).unwrap();
+        assert_eq!(jws.signatures.len(), 1);
+        let (_, ec) = keys();
+        assert!(jws.verify(&[&ec], SignaturePolicy::All).is_ok());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&jws.to_flattened_json().unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::from_str::<serde_json::Value>(&flattened()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_signature_policy() {
+        let jws = Jws::from_json(&general()).unwrap();
+        let (rsa, ec) = keys();
+
+        let headers = jws.verify(&[&ec], SignaturePolicy::Any).unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].kid.as_deref(), Some(EC_KID));
+        assert!(matches!(
+            jws.verify(&[&ec], SignaturePolicy::All),
+            Err(JwtError::KeyInvalid(_))
+        ));
+        assert!(matches!(
+            jws.verify(&[], SignaturePolicy::Any),
+            Err(JwtError::KeyInvalid(_))
+        ));
+
+        // Tamper with the RS256 signature only.
+        let mut tampered = jws.clone();
+        tampered.signatures[0].signature = ES256_SIGNATURE.to_string();
+        assert!(matches!(
+            tampered.verify(&[&rsa, &ec], SignaturePolicy::All),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        assert_eq!(
+            tampered
+                .verify(&[&rsa, &ec], SignaturePolicy::Any)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Tampering with the payload breaks every signature.
+        let mut tampered = jws;
+        tampered.payload = URL_SAFE_NO_PAD.encode(r#"{"iss":"mallory"}"#);
+        assert!(matches!(
+            tampered.verify(&[&rsa, &ec], SignaturePolicy::Any),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_co_signed_round_trip() {
+        let service_a = HmacKey::new(Algorithm::HS256, b"service a").unwrap();
+        let service_b = EcdsaKey::from_secret_bytes(Algorithm::ES256, &[7; 32]).unwrap();
+
+        let mut jws = Jws::new(br#"{"order":42}"#);
+        jws.sign(
+            empty_header(),
+            Some(header(json!({"kid": "service-a"}))),
+            &service_a,
+        )
+        .unwrap()
+        .sign(
+            empty_header(),
+            Some(header(json!({"kid": "service-b"}))),
+            &service_b,
+        )
+        .unwrap();
+        assert_eq!(
+            jws.signatures[0].protected_header().unwrap(),
+            Some(header(json!({"alg": "HS256"})))
+        );
+
+        let jws = Jws::from_json(&jws.to_json().unwrap()).unwrap();
+        assert_eq!(jws.payload().unwrap(), br#"{"order":42}"#);
+        let keys: [&dyn Verifier; 2] = [&service_b, &service_a];
+        let headers = jws.verify(&keys, SignaturePolicy::All).unwrap();
+        assert_eq!(headers[0].kid.as_deref(), Some("service-a"));
+        assert_eq!(headers[1].kid.as_deref(), Some("service-b"));
+
+        let other = HmacKey::new(Algorithm::HS256, b"other").unwrap();
+        let keys: [&dyn Verifier; 2] = [&other, &service_b];
+        assert!(matches!(
+            jws.verify(&keys, SignaturePolicy::All),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        assert!(jws.verify(&keys, SignaturePolicy::Any).is_ok());
+    }
+
+    #[test]
+    fn test_stripped_signature_is_rejected() {
+        let (rsa, ec) = keys();
+        let keys: [&dyn Verifier; 2] = [&rsa, &ec];
+        let mut jws = Jws::from_json(&general()).unwrap();
+        let _ = jws.signatures.remove(1);
+
+        assert!(matches!(
+            jws.verify(&keys, SignaturePolicy::All),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        assert!(matches!(
+            jws.verify_with_jwk_set(&jwks(), SignaturePolicy::All),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        assert_eq!(jws.verify(&keys, SignaturePolicy::Any).unwrap().len(), 1);
+        assert_eq!(
+            jws.verify(&keys[..1], SignaturePolicy::All).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_unprotected_header_is_not_signed() {
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let mut jws = Jws::new(b"payload");
+        jws.sign(empty_header(), Some(header(json!({"kid": "a"}))), &key)
+            .unwrap();
+        jws.signatures[0].header = Some(header(json!({"kid": "b"})));
+
+        let headers = jws.verify(&[&key], SignaturePolicy::All).unwrap();
+        assert_eq!(headers[0].kid.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_invalid_headers_are_rejected() {
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let signed = |protected: Header, unprotected: Option<Header>| {
+            let mut jws = Jws::new(b"payload");
+            jws.sign(protected, unprotected, &key)
+                .map(|jws| jws.clone())
+        };
+
+        // Duplicate parameters.
+        assert!(matches!(
+            signed(
+                header(json!({"kid": "a"})),
+                Some(header(json!({"kid": "b"})))
+            ),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        // Unprotected crit.
+        assert!(matches!(
+            signed(
+                empty_header(),
+                Some(header(json!({"crit": ["tenant"], "tenant": "acme"})))
+            ),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        // Algorithm mismatch.
+        assert!(matches!(
+            signed(header(json!({"alg": "HS512"})), None),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        // Unsupported critical extension.
+        assert!(matches!(
+            signed(header(json!({"crit": ["tenant"], "tenant": "acme"})), None),
+            Err(JwtError::InvalidHeader(_))
+        ));
+
+        // The same checks apply when verifying.
+        let mut jws = signed(empty_header(), None).unwrap();
+        jws.signatures[0].header = Some(header(json!({"alg": "HS256"})));
+        assert!(matches!(
+            jws.verify(&[&key], SignaturePolicy::Any),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        jws.signatures[0] = JwsSignature {
+            protected: None,
+            header: Some(header(json!({"alg": "HS256"}))),
+            signature: jws.signatures[0].signature.clone(),
+        };
+        assert!(matches!(
+            jws.verify(&[&key], SignaturePolicy::Any),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_alg_may_be_unprotected() {
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let mut jws = Jws::new(b"payload");
+        jws.sign(empty_header(), Some(header(json!({"alg": "HS256"}))), &key)
+            .unwrap();
+        assert_eq!(jws.signatures[0].protected.as_deref(), Some("e30"));
+        assert!(jws.verify(&[&key], SignaturePolicy::All).is_ok());
+    }
+
+    #[test]
+    fn test_serialization_errors() {
+        assert!(matches!(
+            Jws::new(b"payload").to_flattened_json(),
+            Err(JwtError::FormatInvalid(_))
+        ));
+        assert!(matches!(
+            Jws::new(b"payload").verify(&[], SignaturePolicy::Any),
+            Err(JwtError::FormatInvalid(_))
+        ));
+        assert!(matches!(
+            Jws::from_json(&json!({"payload": PAYLOAD, "signatures": []}).to_string()),
+            Err(JwtError::FormatInvalid(_))
+        ));
+
+        let mut mixed: serde_json::Value = serde_json::from_str(&general()).unwrap();
+        mixed["signature"] = json!(ES256_SIGNATURE);
+        assert!(matches!(
+            Jws::from_json(&mixed.to_string()),
+            Err(JwtError::FormatInvalid(_))
+        ));
+        assert!(matches!(
+            Jws::from_json(r#"{"payload": "e30"}"#),
+            Err(JwtError::InvalidPayload(_))
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    extern crate base64;
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use self::cjwt::{Algorithm, Header, HmacKey, Signer, Verifier, JWT};
+    use self::idk::jwt::JwtError;
+    use cclm::Claims;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    fn signature_len(token: &str) -> usize {
+        let signature = token.rsplit('.').next().unwrap();
+        URL_SAFE_NO_PAD.decode(signature).unwrap().len()
+    }
+
+    fn header(alg: Algorithm) -> Header {
+        Header {
+            alg: Some(alg),
+            ..Header::default()
+        }
+    }
+
+    #[test]
+    fn test_hmac_key_rfc4231_vectors() {
+        // RFC 4231, Test Case 2.
+        let data = b"what do ya want for nothing?";
+        let hs384 = HmacKey::new(Algorithm::HS384, b"Jefe").unwrap();
+        assert_eq!(
+            hex(&hs384.sign(data).unwrap()),
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649"
+        );
+        let hs512 = HmacKey::new(Algorithm::HS512, b"Jefe").unwrap();
+        assert_eq!(
+            hex(&hs512.sign(data).unwrap()),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    fn test_hmac_key_rejects_non_hmac_algorithm() {
+        assert!(matches!(
+            HmacKey::new(Algorithm::RS256, b"secret"),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_hmac_key_verify() {
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let signature = key.sign(b"input").unwrap();
+        assert!(key.verify(b"input", &signature).is_ok());
+        assert!(matches!(
+            key.verify(b"other", &signature),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_dispatches_on_header_alg() {
+        let secret = b"secret";
+        for (alg, len) in [
+            (Algorithm::HS256, 32),
+            (Algorithm::HS384, 48),
+            (Algorithm::HS512, 64),
+        ] {
+            let token = JWT::encode(header(alg), Claims::default(), secret).unwrap();
+            assert_eq!(signature_len(&token), len);
+
+            let mut jwt = JWT {
+                token: token.clone(),
+                ..JWT::default()
+            };
+            assert_eq!(jwt.decode(secret).unwrap(), token);
+            let key = HmacKey::new(alg, secret).unwrap();
+            assert_eq!(jwt.decode_with_key(&key).unwrap(), token);
+        }
+    }
+
+    #[test]
+    fn test_encode_with_key_fills_in_missing_alg() {
+        let key = HmacKey::new(Algorithm::HS512, b"secret").unwrap();
+        let header = Header {
+            alg: None,
+            ..Header::default()
+        };
+        let token = JWT::encode_with_key(header, Claims::default(), &key).unwrap();
+        let header_json = URL_SAFE_NO_PAD
+            .decode(token.split('.').next().unwrap())
+            .unwrap();
+        let header: Header = serde_json::from_slice(&header_json).unwrap();
+        assert_eq!(header.alg, Some(Algorithm::HS512));
+    }
+
+    #[test]
+    fn test_encode_with_key_rejects_mismatched_alg() {
+        let key = HmacKey::new(Algorithm::HS512, b"secret").unwrap();
+        assert!(matches!(
+            JWT::encode_with_key(header(Algorithm::HS256), Claims::default(), &key),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_with_key_rejects_algorithm_confusion() {
+        let secret = b"secret";
+        let token = JWT::encode(header(Algorithm::HS256), Claims::default(), secret).unwrap();
+        let mut jwt = JWT {
+            token,
+            ..JWT::default()
+        };
+        let key = HmacKey::new(Algorithm::HS512, secret).unwrap();
+        assert!(matches!(
+            jwt.decode_with_key(&key),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_non_hmac_header_with_secret() {
+        let header_b64 = URL_SAFE_NO_PAD.encode(br#"{"alg":"RS256"}"#);
+        let claims_b64 = URL_SAFE_NO_PAD.encode(b"{}");
+        let mut jwt = JWT {
+            token: format!("{header_b64}.{claims_b64}.c2ln"),
+            ..JWT::default()
+        };
+        assert!(matches!(
+            jwt.decode(b"secret"),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+#[cfg(test)]
+mod tests {
+    extern crate cjwt;
+    extern crate dtt;
+    extern crate idk;
+
+    use self::cjwt::{
+        Algorithm, Clock, EdDsaKey, Header, HmacKey, RefreshPolicy, Unsecured, Validation, JWT,
+        ORIGINAL_ISSUED_AT,
+    };
+    use self::dtt::DateTime;
+    use self::idk::jwt::JwtError;
+    use cclm::{Claims, NumericDate};
+    use serde_json::json;
+
+    const ISSUED_AT: i64 = 1_700_000_000;
+
+    fn key() -> HmacKey {
+        HmacKey::new(Algorithm::HS256, b"secret").unwrap()
+    }
+
+    fn at(seconds: i64) -> Clock {
+        Clock::from_fn(move || NumericDate::from_seconds(seconds))
+    }
+
+    fn policy(now: i64) -> RefreshPolicy {
+        RefreshPolicy {
+            lifetime: 600,
+            max_lifetime: Some(3_600),
+            clock: at(now),
+            ..RefreshPolicy::default()
+        }
+    }
+
+    fn claims() -> Claims {
+        let mut claims = Claims::new();
+        claims.set_issuer("https://issuer.example");
+        claims.set_subject("alice");
+        claims.set_claim_value("scope", json!(["read", "write"]));
+        claims.set_issued_at(ISSUED_AT);
+        claims.set_not_before(ISSUED_AT);
+        claims.set_expiration(ISSUED_AT + 600);
+        claims.set_jwt_id("original");
+        claims
+    }
+
+    fn validation(now: i64) -> Validation {
+        Validation {
+            clock: at(now),
+            ..Validation::default()
+        }
+    }
+
+    fn validated(claims: Claims, now: i64) -> JWT {
+        let token = JWT::encode_with_key(Header::default(), claims, &key()).unwrap();
+        let mut jwt = JWT {
+            token,
+            ..JWT::default()
+        };
+        jwt.decode_with_validation(&key(), &validation(now))
+            .unwrap();
+        jwt
+    }
+
+    #[test]
+    fn test_refresh_reissues_time_and_id_claims() {
+        let now = ISSUED_AT + 300;
+        let jwt = validated(claims(), now);
+        let token = jwt
+            .refresh(&key(), &validation(now), &key(), &policy(now))
+            .unwrap();
+        let refreshed = JWT::from_token(&token, &key()).unwrap().claims;
+
+        assert_eq!(refreshed.issuer(), Some("https://issuer.example"));
+        assert_eq!(refreshed.subject(), Some("alice"));
+        assert_eq!(
+            refreshed.get_claim_value("scope"),
+            Some(&json!(["read", "write"]))
+        );
+        assert_eq!(refreshed.issued_at(), Some(NumericDate::from_seconds(now)));
+        assert_eq!(
+            refreshed.expiration(),
+            Some(NumericDate::from_seconds(now + 600))
+        );
+        assert_eq!(refreshed.not_before(), None);
+        assert_ne!(refreshed.jwt_id(), Some("original"));
+        assert_eq!(refreshed.jwt_id().unwrap().len(), 22);
+        assert_eq!(
+            refreshed.get_claim_value(ORIGINAL_ISSUED_AT),
+            Some(&json!(ISSUED_AT))
+        );
+    }
+
+    #[test]
+    fn test_refresh_verifies_the_token() {
+        let now = ISSUED_AT + 300;
+        let mut jwt = validated(claims(), now);
+        jwt.claims.set_subject("mallory");
+        let token = jwt
+            .refresh(&key(), &validation(now), &key(), &policy(now))
+            .unwrap();
+        let refreshed = JWT::from_token(&token, &key()).unwrap().claims;
+        assert_eq!(refreshed.subject(), Some("alice"));
+
+        let other = HmacKey::new(Algorithm::HS256, b"other").unwrap();
+        assert!(matches!(
+            jwt.refresh(&other, &validation(now), &key(), &policy(now)),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        assert!(matches!(
+            jwt.refresh(&key(), &validation(ISSUED_AT + 600), &key(), &policy(now)),
+            Err(JwtError::SignatureExpired(_))
+        ));
+
+        let header = Header {
+            alg: Some(Algorithm::None),
+            ..Header::default()
+        };
+        let unsecured = JWT {
+            token: JWT::encode_with_key(header, claims(), &Unsecured).unwrap(),
+            ..JWT::default()
+        };
+        let validation = Validation {
+            allow_unsecured: true,
+            ..validation(now)
+        };
+        assert!(matches!(
+            unsecured.refresh(&key(), &validation, &key(), &policy(now)),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_issues_unique_ids() {
+        let jwt = validated(claims(), ISSUED_AT);
+        let policy = policy(ISSUED_AT);
+        let first = policy.refresh_claims(&jwt.claims).unwrap();
+        let second = policy.refresh_claims(&jwt.claims).unwrap();
+        assert_ne!(first.jwt_id(), second.jwt_id());
+    }
+
+    #[test]
+    fn test_refresh_copies_selected_claims() {
+        let jwt = validated(claims(), ISSUED_AT);
+        let policy = RefreshPolicy {
+            copy_claims: Some(vec!["sub".to_string(), "jti".to_string()]),
+            ..policy(ISSUED_AT)
+        };
+        let refreshed = policy.refresh_claims(&jwt.claims).unwrap();
+        assert_eq!(refreshed.subject(), Some("alice"));
+        assert_eq!(refreshed.issuer(), None);
+        assert!(!refreshed.has_claim("scope"));
+        assert_ne!(refreshed.jwt_id(), Some("original"));
+    }
+
+    #[test]
+    fn test_sliding_expiry_is_capped_by_max_lifetime() {
+        let mut jwt = validated(claims(), ISSUED_AT);
+        let mut expirations = Vec::new();
+        for offset in [500, 1_000, 1_500, 2_000, 2_500, 3_050] {
+            let now = ISSUED_AT + offset;
+            let token = jwt
+                .refresh(&key(), &validation(now), &key(), &policy(now))
+                .unwrap();
+            jwt = validated(JWT::from_token(&token, &key()).unwrap().claims, now);
+            expirations.push(jwt.claims.expiration().unwrap().seconds() - ISSUED_AT);
+        }
+        // Every refresh slides the expiry until the chain reaches its
+        // maximum lifetime
+        assert_eq!(expirations, [1_100, 1_600, 2_100, 2_600, 3_100, 3_600]);
+
+        assert!(matches!(
+            jwt.refresh(
+                &key(),
+                &validation(ISSUED_AT + 3_500),
+                &key(),
+                &policy(ISSUED_AT + 3_600),
+            ),
+            Err(JwtError::SignatureExpired(_))
+        ));
+    }
+
+    #[test]
+    fn test_unbounded_lifetime() {
+        let jwt = validated(claims(), ISSUED_AT);
+        let policy = RefreshPolicy {
+            max_lifetime: None,
+            ..policy(ISSUED_AT + 1_000_000)
+        };
+        let refreshed = policy.refresh_claims(&jwt.claims).unwrap();
+        assert_eq!(
+            refreshed.expiration(),
+            Some(NumericDate::from_seconds(ISSUED_AT + 1_000_600))
+        );
+    }
+
+    #[test]
+    fn test_token_without_iat_starts_a_new_chain() {
+        let mut claims = claims();
+        claims.remove_claim("iat");
+        let refreshed = policy(ISSUED_AT + 100).refresh_claims(&claims).unwrap();
+        assert_eq!(
+            refreshed.get_claim_value(ORIGINAL_ISSUED_AT),
+            Some(&json!(ISSUED_AT + 100))
+        );
+
+        claims.set_claim("orig_iat", "yesterday");
+        assert!(matches!(
+            policy(ISSUED_AT).refresh_claims(&claims),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_signs_with_current_key() {
+        let jwt = validated(claims(), ISSUED_AT);
+        let current = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+        let policy = RefreshPolicy {
+            header: Header {
+                alg: None,
+                kid: Some("2024-01".to_string()),
+                ..Header::default()
+            },
+            ..policy(ISSUED_AT)
+        };
+        let token = jwt
+            .refresh(&key(), &validation(ISSUED_AT), &current, &policy)
+            .unwrap();
+
+        let refreshed = JWT::from_token(&token, &current).unwrap();
+        assert_eq!(refreshed.header.alg, Some(Algorithm::EdDSA));
+        assert_eq!(refreshed.header.kid.as_deref(), Some("2024-01"));
+        assert!(matches!(
+            JWT::from_token(&token, &key()),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_with_date_time_clock() {
+        let now = DateTime::parse("2023-11-14").unwrap();
+        let policy = RefreshPolicy {
+            clock: Clock::Fixed(now),
+            max_lifetime: None,
+            ..RefreshPolicy::default()
+        };
+        let refreshed = policy.refresh_claims(&claims()).unwrap();
+        assert_eq!(
+            refreshed.issued_at(),
+            Some(NumericDate::from_seconds(1_699_920_000))
+        );
+        assert_eq!(
+            refreshed.expiration(),
+            Some(NumericDate::from_seconds(1_699_923_600))
+        );
+    }
+}
@@ -0,0 +1,246 @@
+#[cfg(test)]
+mod tests {
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::cjwt::{
+        Algorithm, Clock, HmacKey, JtiDenylist, MemoryReplayStore, ReplayStore, Validation, JWT,
+    };
+    use self::idk::jwt::JwtError;
+    use cclm::{Claims, NumericDate};
+    use std::{fs, path::PathBuf, sync::Arc};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn key() -> HmacKey {
+        HmacKey::new(Algorithm::HS256, b"secret").unwrap()
+    }
+
+    fn at(seconds: i64) -> Clock {
+        Clock::from_fn(move || NumericDate::from_seconds(seconds))
+    }
+
+    fn token(jti: Option<&str>, exp: Option<i64>) -> String {
+        let mut claims = Claims::new();
+        claims.set_subject("alice");
+        if let Some(jti) = jti {
+            claims.set_jwt_id(jti);
+        }
+        if let Some(exp) = exp {
+            claims.set_expiration(exp);
+        }
+        JWT::encode_with_key(Default::default(), claims, &key()).unwrap()
+    }
+
+    fn decode(token: &str, validation: &Validation) -> Result<String, JwtError> {
+        let mut jwt = JWT {
+            token: token.to_string(),
+            ..JWT::default()
+        };
+        jwt.decode_with_validation(&key(), validation)
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("cjwt-{name}-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_one_time_token_is_rejected_on_reuse() {
+        let store = Arc::new(MemoryReplayStore::new());
+        let validation = Validation {
+            replay_store: Some(store.clone()),
+            clock: at(NOW),
+            ..Validation::default()
+        };
+        let reset_link = token(Some("reset-1"), Some(NOW + 900));
+
+        assert!(decode(&reset_link, &validation).is_ok());
+        assert!(matches!(
+            decode(&reset_link, &validation),
+            Err(JwtError::TokenRevoked(_))
+        ));
+        assert!(decode(&token(Some("reset-2"), Some(NOW + 900)), &validation).is_ok());
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_rejected_token_is_not_recorded() {
+        let store = Arc::new(MemoryReplayStore::new());
+        let validation = Validation {
+            replay_store: Some(store.clone()),
+            issuers: vec!["https://issuer.example".to_string()],
+            clock: at(NOW),
+            ..Validation::default()
+        };
+        let token = token(Some("reset-1"), Some(NOW + 900));
+        assert!(matches!(
+            decode(&token, &validation),
+            Err(JwtError::IssuerInvalid(_))
+        ));
+        assert!(store.is_empty());
+
+        let mut jwt = JWT {
+            token: token.replace('.', ".x"),
+            ..JWT::default()
+        };
+        assert!(jwt.decode_with_validation(&key(), &validation).is_err());
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_replay_store_requires_jti_and_exp() {
+        let validation = Validation {
+            replay_store: Some(Arc::new(MemoryReplayStore::new())),
+            clock: at(NOW),
+            ..Validation::default()
+        };
+        assert!(matches!(
+            decode(&token(None, Some(NOW + 900)), &validation),
+            Err(JwtError::InvalidPayload(_))
+        ));
+        assert!(matches!(
+            decode(&token(Some("reset-1"), None), &validation),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_memory_store_forgets_expired_tokens() {
+        let store = MemoryReplayStore::new();
+        let exp = NumericDate::from_seconds(NOW + 60);
+        let now = NumericDate::from_seconds(NOW);
+        store.check_and_record("a", exp, now).unwrap();
+        store
+            .check_and_record("b", NumericDate::from_seconds(NOW + 600), now)
+            .unwrap();
+        assert!(store.check_and_record("a", exp, now).is_err());
+
+        let later = NumericDate::from_seconds(NOW + 60);
+        assert!(store.check_and_record("c", exp, later).is_ok());
+        assert_eq!(store.len(), 2);
+        assert!(matches!(
+            store.check_and_record("b", exp, later),
+            Err(JwtError::TokenRevoked(_))
+        ));
+    }
+
+    #[test]
+    fn test_expiry_uses_leeway() {
+        let store = Arc::new(MemoryReplayStore::new());
+        let token = token(Some("reset-1"), Some(NOW + 10));
+        let validation = |now| Validation {
+            replay_store: Some(store.clone()),
+            leeway: 60,
+            clock: at(now),
+            ..Validation::default()
+        };
+        assert!(decode(&token, &validation(NOW)).is_ok());
+        // Still within the leeway, so the jti must still be remembered.
+        assert!(matches!(
+            decode(&token, &validation(NOW + 60)),
+            Err(JwtError::TokenRevoked(_))
+        ));
+        assert!(matches!(
+            decode(&token, &validation(NOW + 70)),
+            Err(JwtError::SignatureExpired(_))
+        ));
+    }
+
+    #[test]
+    fn test_denylist_rejects_revoked_tokens() {
+        let path = temp_path("denylist");
+        let denylist = Arc::new(JtiDenylist::open(&path).unwrap());
+        assert!(denylist.is_empty());
+        let validation = Validation {
+            denylist: Some(denylist.clone()),
+            ..Validation::default()
+        };
+
+        let session = token(Some("session-1"), None);
+        assert!(decode(&session, &validation).is_ok());
+        assert!(decode(&session, &validation).is_ok());
+
+        denylist.revoke("session-1").unwrap();
+        denylist.revoke("session-1").unwrap();
+        assert!(matches!(
+            decode(&session, &validation),
+            Err(JwtError::TokenRevoked(_))
+        ));
+        assert!(matches!(
+            decode(&token(None, None), &validation),
+            Err(JwtError::InvalidPayload(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "c2Vzc2lvbi0x\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_denylist_file_format() {
+        let path = temp_path("denylist-format");
+        fs::write(
+            &path,
+            "# revoked sessions\nc2Vzc2lvbi0x\n\n  c2Vzc2lvbi0y  \n",
+        )
+        .unwrap();
+        let denylist = JtiDenylist::open(&path).unwrap();
+        assert_eq!(denylist.len(), 2);
+        assert!(denylist.is_revoked("session-1"));
+        assert!(denylist.is_revoked("session-2"));
+        assert!(!denylist.is_revoked("c2Vzc2lvbi0y"));
+        assert!(!denylist.is_revoked("# revoked sessions"));
+
+        // Revocations made by another process are picked up on reload.
+        JtiDenylist::open(&path)
+            .unwrap()
+            .revoke("session-3")
+            .unwrap();
+        assert!(!denylist.is_revoked("session-3"));
+        denylist.reload().unwrap();
+        assert!(denylist.is_revoked("session-3"));
+
+        // Any jti is encoded on a single line and matched exactly.
+        for jti in ["  padded  ", "# comment", "a\nb", "tab\t"] {
+            denylist.revoke(jti).unwrap();
+        }
+        let reopened = JtiDenylist::open(&path).unwrap();
+        for jti in ["  padded  ", "# comment", "a\nb", "tab\t"] {
+            assert!(reopened.is_revoked(jti), "{jti:?}");
+        }
+        assert!(!reopened.is_revoked("padded"));
+        assert!(!reopened.is_revoked("a"));
+        assert_eq!(reopened.len(), 7);
+        assert!(matches!(
+            denylist.revoke(""),
+            Err(JwtError::InvalidPayload(_))
+        ));
+
+        fs::write(&path, "c2Vzc2lvbi0x\nsession 2\n").unwrap();
+        assert!(matches!(
+            JtiDenylist::open(&path),
+            Err(JwtError::FormatInvalid(_))
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_denylist_is_checked_before_replay_store() {
+        let path = temp_path("denylist-replay");
+        let denylist = Arc::new(JtiDenylist::open(&path).unwrap());
+        denylist.revoke("reset-1").unwrap();
+        let store = Arc::new(MemoryReplayStore::new());
+        let validation = Validation {
+            denylist: Some(denylist),
+            replay_store: Some(store.clone()),
+            clock: at(NOW),
+            ..Validation::default()
+        };
+        assert!(matches!(
+            decode(&token(Some("reset-1"), Some(NOW + 900)), &validation),
+            Err(JwtError::TokenRevoked(_))
+        ));
+        assert!(store.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+#[cfg(test)]
+mod tests {
+    extern crate base64;
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use self::cjwt::{Algorithm, EcdsaKey, EdDsaKey, Header, HmacKey, Signer, Verifier, JWT};
+    use self::idk::jwt::JwtError;
+
+    const SECRET: &[u8] = b"secret";
+
+    fn header(alg: Algorithm) -> Header {
+        Header {
+            alg: Some(alg),
+            ..Header::default()
+        }
+    }
+
+    fn token<K: Signer>(key: &K, subject: &str) -> String {
+        let mut claims = JWT::claims();
+        claims.set_subject(subject);
+        JWT::encode_with_key(header(key.algorithm()), claims, key).unwrap()
+    }
+
+    fn segments(token: &str) -> (String, String, Vec<u8>) {
+        let parts: Vec<&str> = token.split('.').collect();
+        (
+            parts[0].to_string(),
+            parts[1].to_string(),
+            URL_SAFE_NO_PAD.decode(parts[2]).unwrap(),
+        )
+    }
+
+    fn join(header: &str, claims: &str, signature: &[u8]) -> String {
+        format!("{header}.{claims}.{}", URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    fn encode_json(json: &str) -> String {
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Returns well-formed tokens derived from `token` by tampering with,
+    /// truncating or re-ordering its parts. `other` is a genuine token
+    /// for a different subject signed with the same key.
+    fn forgeries(token: &str, other: &str) -> Vec<(&'static str, String)> {
+        let (header, claims, signature) = segments(token);
+        let (_, other_claims, other_signature) = segments(other);
+        let alg = JWT::decode_header(token).unwrap().alg.unwrap();
+        let len = signature.len();
+
+        let mut flipped_first = signature.clone();
+        flipped_first[0] ^= 0x01;
+        let mut flipped_last = signature.clone();
+        flipped_last[len - 1] ^= 0x80;
+        let mut extended = signature.clone();
+        extended.push(0);
+        let mut reversed = signature.clone();
+        reversed.reverse();
+        let mut rotated = signature.clone();
+        rotated.rotate_left(1);
+        let (left, right) = signature.split_at(len / 2);
+        let swapped_halves = [right, left].concat();
+
+        let tampered_header = encode_json(&format!(r#"{{"alg":"{alg}","typ":"JWT","kid":"x"}}"#));
+        let tampered_claims = encode_json(r#"{"sub":"mallory"}"#);
+
+        vec![
+            ("flipped first byte", join(&header, &claims, &flipped_first)),
+            ("flipped last byte", join(&header, &claims, &flipped_last)),
+            ("zeroed signature", join(&header, &claims, &vec![0; len])),
+            (
+                "tampered header",
+                join(&tampered_header, &claims, &signature),
+            ),
+            (
+                "tampered claims",
+                join(&header, &tampered_claims, &signature),
+            ),
+            (
+                "truncated by one byte",
+                join(&header, &claims, &signature[..len - 1]),
+            ),
+            (
+                "truncated to half",
+                join(&header, &claims, &signature[..len / 2]),
+            ),
+            (
+                "truncated to one byte",
+                join(&header, &claims, &signature[..1]),
+            ),
+            ("empty signature", join(&header, &claims, &[])),
+            ("extended signature", join(&header, &claims, &extended)),
+            ("reversed signature", join(&header, &claims, &reversed)),
+            ("rotated signature", join(&header, &claims, &rotated)),
+            (
+                "swapped signature halves",
+                join(&header, &claims, &swapped_halves),
+            ),
+            (
+                "signature of another token",
+                join(&header, &claims, &other_signature),
+            ),
+            (
+                "claims of another token",
+                join(&header, &other_claims, &signature),
+            ),
+        ]
+    }
+
+    fn assert_rejected<F>(name: &str, token: &str, other: &str, mut verify: F)
+    where
+        F: FnMut(&str) -> Result<(), JwtError>,
+    {
+        assert!(verify(token).is_ok(), "{name}: genuine token rejected");
+        assert!(verify(other).is_ok(), "{name}: genuine token rejected");
+        for (forgery, forged) in forgeries(token, other) {
+            match verify(&forged) {
+                Err(JwtError::SignatureInvalid(_)) => {}
+                result => panic!("{name}: {forgery} gave {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_forgeries() {
+        for alg in [Algorithm::HS256, Algorithm::HS384, Algorithm::HS512] {
+            let key = HmacKey::new(alg, SECRET).unwrap();
+            let name = format!("decode {alg}");
+            assert_rejected(&name, &token(&key, "alice"), &token(&key, "bob"), |token| {
+                let mut jwt = JWT {
+                    token: token.to_string(),
+                    ..JWT::default()
+                };
+                jwt.decode(SECRET).map(|_| ())
+            });
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_forgeries() {
+        for alg in [Algorithm::HS256, Algorithm::HS384, Algorithm::HS512] {
+            let key = HmacKey::new(alg, SECRET).unwrap();
+            let name = format!("validate {alg}");
+            assert_rejected(&name, &token(&key, "alice"), &token(&key, "bob"), |token| {
+                let jwt = JWT {
+                    token: token.to_string(),
+                    ..JWT::default()
+                };
+                jwt.validate(SECRET)
+            });
+        }
+    }
+
+    #[test]
+    fn test_decode_with_key_rejects_forgeries() {
+        let es256 = EcdsaKey::from_secret_bytes(Algorithm::ES256, &[7; 32]).unwrap();
+        let eddsa = EdDsaKey::from_secret_bytes(&[7; 32]).unwrap();
+        let keys: [(&str, &dyn Verifier, String, String); 2] = [
+            (
+                "ES256",
+                &es256,
+                token(&es256, "alice"),
+                token(&es256, "bob"),
+            ),
+            (
+                "EdDSA",
+                &eddsa,
+                token(&eddsa, "alice"),
+                token(&eddsa, "bob"),
+            ),
+        ];
+        for (name, key, token, other) in keys {
+            assert_rejected(name, &token, &other, |token| {
+                JWT::from_token(token, key).map(|_| ())
+            });
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_genuine_token() {
+        let jwt = JWT {
+            token: JWT::generate(SECRET).unwrap(),
+            ..JWT::default()
+        };
+        assert!(jwt.validate(SECRET).is_ok());
+        assert!(matches!(
+            jwt.validate(b"other secret"),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_checks_claims_after_signature() {
+        let mut claims = JWT::claims();
+        claims.set_expiration(1);
+        let token = JWT::encode(Header::default(), claims, SECRET).unwrap();
+        let jwt = JWT {
+            token,
+            ..JWT::default()
+        };
+        assert!(matches!(
+            jwt.validate(SECRET),
+            Err(JwtError::SignatureExpired(_))
+        ));
+        assert!(matches!(
+            jwt.validate(b"other secret"),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_hmac_verify_compares_raw_bytes() {
+        let key = HmacKey::new(Algorithm::HS256, SECRET).unwrap();
+        let signature = key.sign(b"header.claims").unwrap();
+        assert!(key.verify(b"header.claims", &signature).is_ok());
+
+        let encoded = URL_SAFE_NO_PAD.encode(&signature);
+        assert!(matches!(
+            key.verify(b"header.claims", encoded.as_bytes()),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        assert!(matches!(
+            key.verify(b"header.claims.", &signature),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+    extern crate cjwt;
+    extern crate idk;
+    extern crate serde;
+
+    use self::cjwt::{Algorithm, Header, HmacKey, TokenData, JWT};
+    use self::idk::jwt::JwtError;
+    use self::serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Session {
+        sub: String,
+        admin: bool,
+        #[serde(default)]
+        scopes: Vec<String>,
+    }
+
+    fn key() -> HmacKey {
+        HmacKey::new(Algorithm::HS512, b"secret").unwrap()
+    }
+
+    fn token() -> String {
+        let mut claims = JWT::claims();
+        claims.set_subject("alice");
+        claims.set_issuer("https://issuer.example");
+        claims.set_claim_value("admin", true);
+        let header = Header {
+            alg: Some(Algorithm::HS512),
+            kid: Some("k1".to_string()),
+            ..Header::default()
+        };
+        JWT::encode_with_key(header, claims, &key()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_with_key_populates_jwt() {
+        let token = token();
+        let mut jwt = JWT {
+            token: token.clone(),
+            ..JWT::default()
+        };
+        assert_eq!(jwt.decode_with_key(&key()).unwrap(), token);
+        assert_eq!(jwt.header.alg, Some(Algorithm::HS512));
+        assert_eq!(jwt.header.kid.as_deref(), Some("k1"));
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+        assert_eq!(jwt.claims.issuer(), Some("https://issuer.example"));
+        assert_eq!(jwt.signature.len(), 64);
+        assert_eq!(jwt.token, token);
+    }
+
+    #[test]
+    fn test_decode_with_key_leaves_jwt_untouched_on_failure() {
+        let mut jwt = JWT {
+            token: token(),
+            ..JWT::default()
+        };
+        let other = HmacKey::new(Algorithm::HS512, b"other").unwrap();
+        assert!(matches!(
+            jwt.decode_with_key(&other),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+        assert_eq!(jwt.header, JWT::default().header);
+        assert!(jwt.signature.is_empty());
+    }
+
+    #[test]
+    fn test_from_token() {
+        let token = token();
+        let jwt = JWT::from_token(&token, &key()).unwrap();
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+        assert_eq!(jwt.token, token);
+
+        let hs256 = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        assert!(matches!(
+            JWT::from_token(&token, &hs256),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_as_custom_claims() {
+        let data: TokenData<Session> = JWT::decode_as(&token(), &key()).unwrap();
+        assert_eq!(data.header.kid.as_deref(), Some("k1"));
+        assert_eq!(
+            data.claims,
+            Session {
+                sub: "alice".to_string(),
+                admin: true,
+                scopes: Vec::new(),
+            }
+        );
+        assert_eq!(data.signature.len(), 64);
+    }
+
+    #[test]
+    fn test_decode_as_rejects_mismatched_claims() {
+        #[derive(Debug, Deserialize)]
+        struct Required {
+            #[allow(dead_code)]
+            email: String,
+        }
+        assert!(matches!(
+            JWT::decode_as::<Required, _>(&token(), &key()),
+            Err(JwtError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_as_verifies_signature_before_returning_claims() {
+        let token = token();
+        let (signing_input, _) = token.rsplit_once('.').unwrap();
+        let forged = format!("{signing_input}.AAAA");
+        assert!(matches!(
+            JWT::decode_as::<Session, _>(&forged, &key()),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_insecure_decode() {
+        let token = token();
+        let (signing_input, _) = token.rsplit_once('.').unwrap();
+        let forged = format!("{signing_input}.AAAA");
+
+        let jwt = JWT::insecure_decode(&forged).unwrap();
+        assert_eq!(jwt.claims.subject(), Some("alice"));
+        assert_eq!(jwt.signature, vec![0, 0, 0]);
+        assert_eq!(jwt.token, forged);
+
+        let data = JWT::insecure_decode_as::<Session>(&forged).unwrap();
+        assert!(data.claims.admin);
+
+        assert!(matches!(
+            JWT::insecure_decode("not a token"),
+            Err(JwtError::FormatInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_header() {
+        let header = JWT::decode_header(&token()).unwrap();
+        assert_eq!(header.alg, Some(Algorithm::HS512));
+        assert_eq!(header.kid.as_deref(), Some("k1"));
+        assert_eq!(header.typ.as_deref(), Some("JWT"));
+        assert!(matches!(
+            JWT::decode_header("e30"),
+            Err(JwtError::FormatInvalid(_))
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    extern crate cjwt;
+    extern crate idk;
+
+    use self::cjwt::{Algorithm, Header, HmacKey, Unsecured, Validation, JWT};
+    use self::idk::jwt::JwtError;
+
+    const SECRET: &[u8] = b"secret";
+
+    fn unsecured_token() -> String {
+        let mut claims = JWT::claims();
+        claims.set_subject("alice");
+        let header = Header {
+            alg: Some(Algorithm::None),
+            ..Header::default()
+        };
+        JWT::encode(header, claims, SECRET).unwrap()
+    }
+
+    fn jwt(token: &str) -> JWT {
+        JWT {
+            token: token.to_string(),
+            ..JWT::default()
+        }
+    }
+
+    fn allow_unsecured() -> Validation {
+        Validation {
+            allow_unsecured: true,
+            ..Validation::default()
+        }
+    }
+
+    #[test]
+    fn test_encode_unsecured() {
+        let token = unsecured_token();
+        assert!(token.starts_with("eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0."));
+        assert!(token.ends_with('.'));
+        assert_eq!(
+            JWT::encode_with_key(Header::default(), JWT::claims(), &Unsecured).err(),
+            Some(JwtError::InvalidHeader(
+                "Header algorithm HS256 does not match key algorithm none".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unsecured_token_is_rejected_by_default() {
+        let token = unsecured_token();
+        let key = HmacKey::new(Algorithm::HS256, SECRET).unwrap();
+
+        assert!(matches!(
+            jwt(&token).decode(SECRET),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            jwt(&token).validate(SECRET),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            JWT::from_token(&token, &key),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            jwt(&token).decode_with_validation(&key, &Validation::default()),
+            Err(JwtError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            jwt(&token).decode_unsecured(&Validation::default()),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_unsecured_token_is_accepted_with_opt_in() {
+        let token = unsecured_token();
+        let key = HmacKey::new(Algorithm::HS256, SECRET).unwrap();
+
+        let mut decoded = jwt(&token);
+        assert_eq!(decoded.decode_unsecured(&allow_unsecured()).unwrap(), token);
+        assert_eq!(decoded.header.alg, Some(Algorithm::None));
+        assert_eq!(decoded.claims.subject(), Some("alice"));
+        assert!(decoded.signature.is_empty());
+
+        let mut decoded = jwt(&token);
+        assert!(decoded
+            .decode_with_validation(&key, &allow_unsecured())
+            .is_ok());
+        assert_eq!(decoded.claims.subject(), Some("alice"));
+
+        // Signed tokens are still verified when the opt-in is set.
+        let signed = JWT::encode(Header::default(), JWT::claims(), SECRET).unwrap();
+        assert!(jwt(&signed)
+            .decode_with_validation(&key, &allow_unsecured())
+            .is_ok());
+        assert!(matches!(
+            jwt(&signed).decode_unsecured(&allow_unsecured()),
+            Err(JwtError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_unsecured_token_claims_are_validated() {
+        let mut claims = JWT::claims();
+        claims.set_expiration(1);
+        let header = Header {
+            alg: Some(Algorithm::None),
+            ..Header::default()
+        };
+        let token = JWT::encode(header, claims, SECRET).unwrap();
+        assert!(matches!(
+            jwt(&token).decode_unsecured(&allow_unsecured()),
+            Err(JwtError::SignatureExpired(_))
+        ));
+    }
+
+    #[test]
+    fn test_unsecured_token_with_signature_is_rejected() {
+        let token = format!("{}c2lnbmF0dXJl", unsecured_token());
+        assert!(matches!(
+            jwt(&token).decode_unsecured(&allow_unsecured()),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+}
@@ -0,0 +1,347 @@
+#[cfg(test)]
+mod tests {
+    extern crate cjwt;
+    extern crate dtt;
+    extern crate idk;
+
+    use self::cjwt::{Algorithm, Clock, Header, HmacKey, Validation, JWT};
+    use self::dtt::DateTime;
+    use self::idk::jwt::JwtError;
+    use cclm::{Audience, Claims};
+
+    const NOW: i64 = 1_672_531_200; // 2023-01-01T00:00:00Z
+
+    fn at(seconds: i64) -> Validation {
+        Validation {
+            clock: Clock::from_fn(move || seconds.into()),
+            ..Validation::default()
+        }
+    }
+
+    fn claims_with_exp(exp: i64) -> Claims {
+        let mut claims = Claims::new();
+        claims.set_expiration(exp);
+        claims
+    }
+
+    #[test]
+    fn test_default_validation_accepts_claims_without_time_claims() {
+        assert!(Validation::default().validate(&Claims::new()).is_ok());
+        assert!(Validation::new()
+            .validate(&claims_with_exp(i64::MAX))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let validation = at(NOW);
+        assert!(validation.validate(&claims_with_exp(NOW + 1)).is_ok());
+        assert!(matches!(
+            validation.validate(&claims_with_exp(NOW)),
+            Err(JwtError::SignatureExpired(_))
+        ));
+        assert!(matches!(
+            validation.validate(&claims_with_exp(NOW - 30)),
+            Err(JwtError::SignatureExpired(_))
+        ));
+    }
+
+    #[test]
+    fn test_leeway_allows_clock_skew() {
+        let validation = Validation {
+            leeway: 60,
+            ..at(NOW)
+        };
+        assert!(validation.validate(&claims_with_exp(NOW - 30)).is_ok());
+        assert!(validation.validate(&claims_with_exp(NOW - 60)).is_err());
+
+        let mut claims = Claims::new();
+        claims.set_not_before(NOW + 30);
+        assert!(validation.validate(&claims).is_ok());
+    }
+
+    #[test]
+    fn test_validate_exp_can_be_disabled() {
+        let validation = Validation {
+            validate_exp: false,
+            ..at(NOW)
+        };
+        assert!(validation.validate(&claims_with_exp(NOW - 30)).is_ok());
+    }
+
+    #[test]
+    fn test_token_not_valid_yet() {
+        let mut claims = Claims::new();
+        claims.set_not_before(NOW + 10);
+        assert!(matches!(
+            at(NOW).validate(&claims),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+        assert!(at(NOW + 10).validate(&claims).is_ok());
+
+        let validation = Validation {
+            validate_nbf: false,
+            ..at(NOW)
+        };
+        assert!(validation.validate(&claims).is_ok());
+    }
+
+    #[test]
+    fn test_time_claims_stored_as_strings() {
+        let mut claims = Claims::new();
+        claims.set_claim("exp", &(NOW - 1).to_string());
+        assert!(matches!(
+            at(NOW).validate(&claims),
+            Err(JwtError::SignatureExpired(_))
+        ));
+
+        claims.set_claim("exp", "tomorrow");
+        assert!(matches!(
+            at(NOW).validate(&claims),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+
+        // The token decodes, and the invalid exp still fails validation
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let token = JWT::encode_with_key(Header::default(), claims, &key).unwrap();
+        let mut jwt = JWT {
+            token,
+            ..JWT::default()
+        };
+        assert!(matches!(
+            jwt.decode_with_validation(&key, &at(NOW)),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+        assert_eq!(jwt.claims.get_claim("exp").unwrap(), "tomorrow");
+    }
+
+    #[test]
+    fn test_required_claims() {
+        let validation = Validation {
+            required_claims: ["exp", "iss", "aud", "sub"].map(String::from).to_vec(),
+            ..at(NOW)
+        };
+        let mut claims = Claims::new();
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+        claims.set_expiration(NOW + 60);
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::IssuerInvalid(_))
+        ));
+        claims.set_issuer("joe");
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::AudienceInvalid(_))
+        ));
+        claims.set_audience("api");
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::InvalidPayload(_))
+        ));
+        claims.set_claim("sub", "1234567890");
+        assert!(validation.validate(&claims).is_ok());
+    }
+
+    #[test]
+    fn test_issuer() {
+        let validation = Validation {
+            issuers: vec![
+                "https://a.example".to_string(),
+                "https://b.example".to_string(),
+            ],
+            ..at(NOW)
+        };
+        let mut claims = Claims::new();
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::IssuerInvalid(_))
+        ));
+        claims.set_issuer("https://b.example");
+        assert!(validation.validate(&claims).is_ok());
+        claims.set_claim("iss", "https://a.example");
+        assert!(validation.validate(&claims).is_ok());
+        claims.set_issuer("https://c.example");
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::IssuerInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_audience() {
+        let validation = Validation {
+            audiences: vec!["api".to_string()],
+            ..at(NOW)
+        };
+        let mut claims = Claims::new();
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::AudienceInvalid(_))
+        ));
+        claims.set_audience("api");
+        assert!(validation.validate(&claims).is_ok());
+        claims.set_audience(vec!["web", "api"]);
+        assert!(validation.validate(&claims).is_ok());
+        claims.set_audience(Audience::from(vec!["web", "mobile"]));
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::AudienceInvalid(_))
+        ));
+        claims.set_claim("aud", "api");
+        assert!(validation.validate(&claims).is_ok());
+    }
+
+    #[test]
+    fn test_max_age() {
+        let validation = Validation {
+            max_age: Some(300),
+            ..at(NOW)
+        };
+        let mut claims = Claims::new();
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+        claims.set_issued_at(NOW - 300);
+        assert!(validation.validate(&claims).is_ok());
+        claims.set_issued_at(NOW - 301);
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::SignatureExpired(_))
+        ));
+        claims.set_issued_at(NOW + 5);
+        assert!(matches!(
+            validation.validate(&claims),
+            Err(JwtError::ExpirationInvalid(_))
+        ));
+        let validation = Validation {
+            leeway: 10,
+            ..validation
+        };
+        assert!(validation.validate(&claims).is_ok());
+    }
+
+    #[test]
+    fn test_fixed_date_time_clock() {
+        let clock = Clock::Fixed(DateTime::parse("2023-01-01").unwrap());
+        assert_eq!(clock.now().unwrap().seconds(), NOW);
+
+        let paris = DateTime {
+            year: 2023,
+            month: "January".to_string(),
+            day: 1,
+            hour: 2,
+            minute: 0,
+            second: 0,
+            offset: "+02:00:00".to_string(),
+            ..DateTime::default()
+        };
+        assert_eq!(Clock::Fixed(paris).now().unwrap().seconds(), NOW);
+
+        let leap_day = DateTime {
+            year: 2024,
+            month: "02".to_string(),
+            day: 29,
+            hour: 12,
+            minute: 30,
+            second: 15,
+            offset: "-05:30".to_string(),
+            ..DateTime::default()
+        };
+        assert_eq!(
+            Clock::Fixed(leap_day).now().unwrap().seconds(),
+            1_709_229_615
+        );
+
+        let validation = Validation {
+            clock: Clock::Fixed(DateTime::parse("2023-01-01").unwrap()),
+            ..Validation::default()
+        };
+        assert!(validation.validate(&claims_with_exp(NOW + 1)).is_ok());
+        assert!(validation.validate(&claims_with_exp(NOW)).is_err());
+    }
+
+    #[test]
+    fn test_fixed_clock_rejects_invalid_date_time() {
+        let clock = Clock::Fixed(DateTime::default());
+        assert!(matches!(clock.now(), Err(JwtError::ExpirationInvalid(_))));
+
+        for offset in [
+            "+9999999999999999:00",
+            "+24:00",
+            "+02:60",
+            "-02:00:60",
+            "+02:00:00:00",
+            "+02:-5",
+            "+:30",
+            "02:00",
+        ] {
+            let date_time = DateTime {
+                year: 2023,
+                month: "January".to_string(),
+                day: 1,
+                offset: offset.to_string(),
+                ..DateTime::default()
+            };
+            assert!(
+                matches!(
+                    Clock::Fixed(date_time).now(),
+                    Err(JwtError::ExpirationInvalid(_))
+                ),
+                "{offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_system_clock() {
+        let now = Clock::System.now().unwrap().seconds();
+        assert!(now > NOW);
+        assert!(Clock::from_fn(|| 42.into()).now().unwrap().seconds() == 42);
+        assert_eq!(format!("{:?}", Clock::default()), "System");
+    }
+
+    #[test]
+    fn test_decode_with_validation() {
+        let key = HmacKey::new(Algorithm::HS256, b"secret").unwrap();
+        let header = Header::default();
+        let validation = Validation {
+            issuers: vec!["joe".to_string()],
+            ..at(NOW)
+        };
+
+        let mut claims = claims_with_exp(NOW + 60);
+        claims.set_issuer("joe");
+        let token = JWT::encode_with_key(header.clone(), claims, &key).unwrap();
+        let mut jwt = JWT {
+            token: token.clone(),
+            ..JWT::default()
+        };
+        assert_eq!(
+            jwt.decode_with_validation(&key, &validation).unwrap(),
+            token
+        );
+
+        let mut claims = claims_with_exp(NOW - 60);
+        claims.set_issuer("joe");
+        let expired = JWT::encode_with_key(header, claims, &key).unwrap();
+        let mut jwt = JWT {
+            token: expired.clone(),
+            ..JWT::default()
+        };
+        assert!(matches!(
+            jwt.decode_with_validation(&key, &validation),
+            Err(JwtError::SignatureExpired(_))
+        ));
+
+        // The signature is checked before the claims.
+        let other = HmacKey::new(Algorithm::HS256, b"other").unwrap();
+        assert!(matches!(
+            jwt.decode_with_validation(&other, &validation),
+            Err(JwtError::SignatureInvalid(_))
+        ));
+    }
+}
@@ -75,7 +75,7 @@ fn main() {
     println!("🦀 Random number between 0 and 1: {}", rand_range);
 
     // Generate a random 32-bit unsigned integer within a range
-    let rand_uint = random_range!(rng, 0, u32::max_value());
+    let rand_uint = random_range!(rng, 0, u32::MAX);
     println!(
         "🦀 Random u32 between 0 and u32::max_value(): {}",
         rand_uint
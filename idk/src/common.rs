@@ -1,3 +1,40 @@
+use crate::error::Error as IdkError;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{PoisonError, RwLock, RwLockReadGuard},
+};
+
+/// The error types registered with `ErrorType::register` or
+/// `ErrorType::new_subtype`, by name
+///
+/// The registry is global to the process and registrations are never
+/// removed, so every crate sharing `idk` sees the same error types.
+static REGISTRY: RwLock<BTreeMap<String, Registration>> = RwLock::new(BTreeMap::new());
+
+/// A registered error type
+#[derive(Clone, Debug)]
+struct Registration {
+    /// The name of the parent error type, if any
+    parent: Option<String>,
+    /// The kind of error, holding the default message of the error type
+    error_type: Error,
+    /// The metadata set with `ErrorType::register_with`, if any
+    metadata: Option<Metadata>,
+}
+
+/// The metadata of a registered error type, overriding the metadata of
+/// its kind of error
+#[derive(Clone, Copy, Debug)]
+struct Metadata {
+    /// The numeric code of the error type
+    code: u16,
+    /// The severity level of the error type
+    severity: Severity,
+    /// Whether retrying the failed operation may succeed
+    retryable: bool,
+}
+
 /// ErrorType is a struct that holds a name and an Error enum instance
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct ErrorType {
@@ -67,9 +104,150 @@ impl Error {
     }
 }
 
+/// Severity is the severity level of an error
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    /// The operation failed but the program can carry on, possibly by
+    /// retrying it
+    Warning,
+    /// The operation failed and cannot succeed as it is
+    Error,
+    /// The program is in a broken state or has a bug
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    /// Display the severity
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl Error {
+    /// code returns the numeric code of the error, which is stable and
+    /// never reused for another kind of error
+    pub fn code(&self) -> u16 {
+        match self {
+            Error::Argument(_) => 1,
+            Error::Assertion(_) => 2,
+            Error::Concurrency(_) => 3,
+            Error::Data(_) => 4,
+            Error::External(_) => 5,
+            Error::Format(_) => 6,
+            Error::Implementation(_) => 7,
+            Error::Initialization(_) => 8,
+            Error::Internal(_) => 9,
+            Error::Interruption(_) => 10,
+            Error::Operation(_) => 11,
+            Error::Rejection(_) => 12,
+            Error::State(_) => 13,
+            Error::Timeout(_) => 14,
+            Error::Unknown(_) => 15,
+            Error::Version(_) => 16,
+        }
+    }
+
+    /// code_str returns the string code of the error, the name
+    /// `ErrorType::new` takes to create an error of this kind
+    pub fn code_str(&self) -> &'static str {
+        match self {
+            Error::Argument(_) => "argument",
+            Error::Assertion(_) => "assertion",
+            Error::Concurrency(_) => "concurrency",
+            Error::Data(_) => "data",
+            Error::External(_) => "external",
+            Error::Format(_) => "format",
+            Error::Implementation(_) => "implementation",
+            Error::Initialization(_) => "initialization",
+            Error::Internal(_) => "internal",
+            Error::Interruption(_) => "interruption",
+            Error::Operation(_) => "operation",
+            Error::Rejection(_) => "rejection",
+            Error::State(_) => "state",
+            Error::Timeout(_) => "timeout",
+            Error::Unknown(_) => "unknown",
+            Error::Version(_) => "version",
+        }
+    }
+
+    /// severity returns the severity level of the error
+    pub fn severity(&self) -> Severity {
+        match self {
+            Error::Concurrency(_) | Error::Interruption(_) | Error::Timeout(_) => Severity::Warning,
+            Error::Assertion(_)
+            | Error::Implementation(_)
+            | Error::Initialization(_)
+            | Error::Internal(_) => Severity::Critical,
+            _ => Severity::Error,
+        }
+    }
+
+    /// is_retryable returns true if retrying the failed operation may
+    /// succeed, as for `Timeout` and `Concurrency` errors
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Error::Concurrency(_) | Error::Timeout(_))
+    }
+
+    /// message returns the message of the error
+    pub fn message(&self) -> &str {
+        self.parts().0
+    }
+
+    /// with_message returns an error of the same kind with `message`
+    fn with_message(&self, message: &str) -> Error {
+        (self.parts().1)(message.to_owned())
+    }
+
+    /// parts returns the message of the error and the constructor of
+    /// its kind
+    fn parts(&self) -> (&str, fn(String) -> Error) {
+        match self {
+            Error::Argument(message) => (message, Error::Argument),
+            Error::Assertion(message) => (message, Error::Assertion),
+            Error::Concurrency(message) => (message, Error::Concurrency),
+            Error::Data(message) => (message, Error::Data),
+            Error::External(message) => (message, Error::External),
+            Error::Format(message) => (message, Error::Format),
+            Error::Implementation(message) => (message, Error::Implementation),
+            Error::Initialization(message) => (message, Error::Initialization),
+            Error::Internal(message) => (message, Error::Internal),
+            Error::Interruption(message) => (message, Error::Interruption),
+            Error::Operation(message) => (message, Error::Operation),
+            Error::Rejection(message) => (message, Error::Rejection),
+            Error::State(message) => (message, Error::State),
+            Error::Timeout(message) => (message, Error::Timeout),
+            Error::Unknown(message) => (message, Error::Unknown),
+            Error::Version(message) => (message, Error::Version),
+        }
+    }
+
+    /// is_transient returns true if the cause of the error is expected
+    /// to go away on its own. Every retryable error is transient, and so
+    /// is an `Interruption`, which is not retried as it was requested.
+    pub fn is_transient(&self) -> bool {
+        self.is_retryable() || matches!(self, Error::Interruption(_))
+    }
+}
+
 impl ErrorType {
     /// new creates a new ErrorType struct instance
+    ///
+    /// The error type is the kind of error named `name`, or the error
+    /// type registered as `name`, and is of the `Unknown` kind
+    /// otherwise. The registry is global to the process, so the same
+    /// name gives a different error type once it has been registered,
+    /// by this crate or any other.
     pub fn new(name: &str) -> ErrorType {
+        if let Some(registration) = registry().get(name) {
+            return ErrorType {
+                name: String::from(name),
+                error_type: registration.error_type.clone(),
+            };
+        }
         ErrorType {
             name: String::from(name),
             error_type: Error::new(ErrorType {
@@ -79,13 +257,258 @@ impl ErrorType {
         }
     }
     /// new_subtype creates a new ErrorType struct instance
-    pub fn new_subtype(&self, name: &str) -> ErrorType {
-        ErrorType {
-            name: String::from(name),
-            error_type: Error::new(ErrorType {
-                name: name.to_string(),
-                error_type: Error::Unknown(String::from(name)),
-            }),
+    ///
+    /// The subtype is registered as a child of `self` with the message
+    /// of `self` as its default message, so it keeps the kind of error
+    /// of `self`, and so its code, severity and retryability. Fails as
+    /// `ErrorType::register` does, for example if `name` is the name of
+    /// a kind of error or is already registered under another parent.
+    pub fn new_subtype(&self, name: &str) -> Result<ErrorType, IdkError> {
+        ErrorType::register(name, Some(self), self.error_type.message())
+    }
+
+    /// register registers the error type `name`, as a child of `parent`
+    /// if any, with `message` as its default message
+    ///
+    /// A child keeps the kind of error of its parent, and an error type
+    /// without parent is of the `Unknown` kind. Registering a name again
+    /// with the same parent updates its default message. Fails with an
+    /// `argument` error if `name` is empty, is the name of a kind of
+    /// error or is an ancestor of `parent`, and with a `state` error if
+    /// `name` is already registered under another parent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idk::common::ErrorType;
+    ///
+    /// let timeout = ErrorType::new("timeout");
+    /// let connect = ErrorType::register("connect_timeout", Some(&timeout), "Cannot connect")
+    ///     .unwrap();
+    /// assert!(connect.is_a(&timeout));
+    /// assert!(connect.is_retryable());
+    /// assert_eq!(connect.error_type.message(), "Cannot connect");
+    /// assert_eq!(ErrorType::lookup("connect_timeout"), Some(connect));
+    /// ```
+    pub fn register(
+        name: &str,
+        parent: Option<&ErrorType>,
+        message: &str,
+    ) -> Result<ErrorType, IdkError> {
+        insert(name, parent, message, None)
+    }
+
+    /// register_with registers the error type `name` as `register`
+    /// does, with its own `code`, `severity` and retryability
+    ///
+    /// The error type and its subtypes report this metadata rather than
+    /// the metadata of their kind of error, and their `code_str` is
+    /// `name`. Also fails with an `argument` error if `code` is the code
+    /// of a kind of error, and with a `state` error if it is the code of
+    /// another registered error type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idk::common::{ErrorType, Severity};
+    ///
+    /// let data = ErrorType::new("data");
+    /// let quota = ErrorType::register_with(
+    ///     "quota_exceeded",
+    ///     Some(&data),
+    ///     "Quota exceeded",
+    ///     1_001,
+    ///     Severity::Warning,
+    ///     true,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(quota.code(), 1_001);
+    /// assert_eq!(quota.code_str(), "quota_exceeded");
+    /// assert!(quota.is_retryable());
+    /// assert!(!data.is_retryable());
+    /// ```
+    pub fn register_with(
+        name: &str,
+        parent: Option<&ErrorType>,
+        message: &str,
+        code: u16,
+        severity: Severity,
+        retryable: bool,
+    ) -> Result<ErrorType, IdkError> {
+        let metadata = Metadata {
+            code,
+            severity,
+            retryable,
+        };
+        insert(name, parent, message, Some(metadata))
+    }
+
+    /// lookup returns the kind of error or registered error type named
+    /// `name`, if any
+    pub fn lookup(name: &str) -> Option<ErrorType> {
+        let registered = registry().contains_key(name);
+        (registered || is_kind(name)).then(|| ErrorType::new(name))
+    }
+
+    /// parent returns the parent of a registered error type, if any
+    pub fn parent(&self) -> Option<ErrorType> {
+        let parent = registry().get(&self.name)?.parent.clone()?;
+        Some(ErrorType::new(&parent))
+    }
+
+    /// is_a returns true if `other` is this error type or one of its
+    /// ancestors
+    pub fn is_a(&self, other: &ErrorType) -> bool {
+        is_a(&registry(), &self.name, &other.name)
+    }
+
+    /// code returns the numeric code of the error type, the code set
+    /// with `register_with` by the error type or its nearest ancestor,
+    /// or the code of its kind of error
+    pub fn code(&self) -> u16 {
+        metadata(&self.name).map_or_else(|| self.error_type.code(), |(_, m)| m.code)
+    }
+
+    /// code_str returns the string code of the error type, the name of
+    /// the error type whose code `code` returns, or the string code of
+    /// its kind of error
+    pub fn code_str(&self) -> String {
+        metadata(&self.name).map_or_else(|| self.error_type.code_str().to_owned(), |(name, _)| name)
+    }
+
+    /// severity returns the severity level of the error type, set with
+    /// `register_with` or of its kind of error
+    pub fn severity(&self) -> Severity {
+        metadata(&self.name).map_or_else(|| self.error_type.severity(), |(_, m)| m.severity)
+    }
+
+    /// is_retryable returns true if retrying the failed operation may
+    /// succeed, as set with `register_with` or for its kind of error
+    pub fn is_retryable(&self) -> bool {
+        metadata(&self.name).map_or_else(|| self.error_type.is_retryable(), |(_, m)| m.retryable)
+    }
+
+    /// is_transient returns true if the cause of the error is expected
+    /// to go away on its own, as when it is retryable or an
+    /// `Interruption`
+    pub fn is_transient(&self) -> bool {
+        self.is_retryable() || matches!(self.error_type, Error::Interruption(_))
+    }
+}
+
+/// Returns the registry, read-locked
+fn registry() -> RwLockReadGuard<'static, BTreeMap<String, Registration>> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Registers the error type `name` under `parent` with `message` as its
+/// default message and `metadata`, if any, overriding its kind's
+fn insert(
+    name: &str,
+    parent: Option<&ErrorType>,
+    message: &str,
+    metadata: Option<Metadata>,
+) -> Result<ErrorType, IdkError> {
+    if name.is_empty() || is_kind(name) {
+        return Err(IdkError::new(
+            &format!("Cannot register the error type {name:?}"),
+            ErrorType::new("argument"),
+        ));
+    }
+    let kind = parent.map_or(Error::Unknown(String::new()), |parent| {
+        parent.error_type.clone()
+    });
+    let registration = Registration {
+        parent: parent.map(|parent| parent.name.clone()),
+        error_type: kind.with_message(message),
+        metadata,
+    };
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(parent) = &registration.parent {
+        if is_a(&registry, parent, name) {
+            drop(registry);
+            return Err(IdkError::new(
+                &format!("The error type {name:?} is an ancestor of {parent:?}"),
+                ErrorType::new("argument"),
+            ));
+        }
+    }
+    if let Some(metadata) = metadata {
+        // The kinds of error have the codes up to the code of `Version`
+        if metadata.code <= Error::Version(String::new()).code() {
+            drop(registry);
+            return Err(IdkError::new(
+                &format!("The code {} is the code of a kind of error", metadata.code),
+                ErrorType::new("argument"),
+            ));
+        }
+        let taken = registry.iter().any(|(registered, registration)| {
+            registered != name
+                && registration
+                    .metadata
+                    .map_or(false, |other| other.code == metadata.code)
+        });
+        if taken {
+            drop(registry);
+            return Err(IdkError::new(
+                &format!("The code {} is already registered", metadata.code),
+                ErrorType::new("state"),
+            ));
+        }
+    }
+    if let Some(registered) = registry.get(name) {
+        if registered.parent != registration.parent {
+            drop(registry);
+            return Err(IdkError::new(
+                &format!("The error type {name:?} is registered under another parent"),
+                ErrorType::new("state"),
+            ));
+        }
+    }
+    let error_type = registration.error_type.clone();
+    registry.insert(name.to_owned(), registration);
+    Ok(ErrorType {
+        name: name.to_owned(),
+        error_type,
+    })
+}
+
+/// Returns the name of the error type whose metadata applies to `name`,
+/// `name` or its nearest ancestor registered with metadata, and this
+/// metadata
+fn metadata(name: &str) -> Option<(String, Metadata)> {
+    let registry = registry();
+    let mut next = Some(name);
+    while let Some(name) = next {
+        let registration = registry.get(name)?;
+        if let Some(metadata) = registration.metadata {
+            return Some((name.to_owned(), metadata));
+        }
+        next = registration.parent.as_deref();
+    }
+    None
+}
+
+/// Returns true if `name` is the name of a kind of error
+fn is_kind(name: &str) -> bool {
+    let kind = Error::new(ErrorType {
+        name: name.to_owned(),
+        error_type: Error::Unknown(String::new()),
+    });
+    kind.code_str() == name
+}
+
+/// Returns true if `ancestor` is `name` or one of its ancestors in the
+/// registry
+fn is_a(registry: &BTreeMap<String, Registration>, name: &str, ancestor: &str) -> bool {
+    let mut next = Some(name);
+    while let Some(name) = next {
+        if name == ancestor {
+            return true;
         }
+        next = registry
+            .get(name)
+            .and_then(|registration| registration.parent.as_deref());
     }
+    false
 }
@@ -0,0 +1,208 @@
+// Copyright © 2022-2023 Mini Functions. All rights reserved.
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{common::ErrorType, error::Error, jwt::JwtError};
+use std::{error::Error as StdError, fmt, io};
+
+/// A function returning `true` if an error is of a given type
+type Matcher = fn(&(dyn StdError + 'static)) -> bool;
+
+/// The error type inferred for errors of a given type
+const ERROR_TYPE_TABLE: [(Matcher, &str); 11] = [
+    (is::<std::num::ParseIntError>, "format"),
+    (is::<std::num::ParseFloatError>, "format"),
+    (is::<std::str::ParseBoolError>, "format"),
+    (is::<std::str::Utf8Error>, "format"),
+    (is::<std::string::FromUtf8Error>, "format"),
+    (is::<std::net::AddrParseError>, "format"),
+    (is::<fmt::Error>, "format"),
+    (is::<serde_json::Error>, "format"),
+    (is::<base64::DecodeError>, "format"),
+    (is::<std::env::VarError>, "initialization"),
+    (is::<JwtError>, "rejection"),
+];
+
+/// The error type inferred for I/O errors of a given kind
+const IO_ERROR_TYPE_TABLE: [(io::ErrorKind, &str); 10] = [
+    (io::ErrorKind::TimedOut, "timeout"),
+    (io::ErrorKind::Interrupted, "interruption"),
+    (io::ErrorKind::WouldBlock, "concurrency"),
+    (io::ErrorKind::InvalidInput, "argument"),
+    (io::ErrorKind::InvalidData, "data"),
+    (io::ErrorKind::UnexpectedEof, "data"),
+    (io::ErrorKind::NotFound, "data"),
+    (io::ErrorKind::PermissionDenied, "rejection"),
+    (io::ErrorKind::AlreadyExists, "state"),
+    (io::ErrorKind::Unsupported, "operation"),
+];
+
+/// The error type of errors that are not in the mapping table
+const DEFAULT_ERROR_TYPE: &str = "external";
+
+/// The error type of a missing `Option` value
+const NONE_ERROR_TYPE: &str = "data";
+
+/// Adds context to a `Result` or `Option`, turning its error into an
+/// `idk::error::Error`.
+///
+/// An error that is already an `Error` keeps its type and cause, and
+/// the context is stacked on top of its context frames. Any other error
+/// becomes the cause of a new `Error` whose message is the error
+/// message and whose type is inferred from the type of the error, or
+/// of the first error of its chain of causes found in the mapping
+/// table (`external` if none is). A missing `Option` value becomes an
+/// `Error` of type `data` whose message is the context, which is also
+/// pushed as its first context frame, as for a `Result`.
+///
+/// # Example
+///
+/// ```
+/// use idk::{common::ErrorType, context::Context, error::Error};
+///
+/// fn port(value: &str) -> Result<u16, Error> {
+///     value.parse::<u16>().context("parsing the port")
+/// }
+///
+/// fn configure(value: &str) -> Result<u16, Error> {
+///     port(value).with_context(|| format!("configuring {value}"))
+/// }
+///
+/// let err = configure("http").unwrap_err();
+/// assert_eq!(err.error_type.name, "format");
+/// assert_eq!(err.message, "invalid digit found in string");
+/// assert_eq!(err.contexts(), ["parsing the port", "configuring http"]);
+///
+/// let err = None::<u16>
+///     .context_as(ErrorType::new("argument"), "no port")
+///     .unwrap_err();
+/// assert_eq!(err.error_type.name, "argument");
+/// assert_eq!(err.message, "no port");
+/// assert_eq!(err.contexts(), ["no port"]);
+/// ```
+pub trait Context<T> {
+    /// Adds the context `ctx` to the error, with an inferred error type.
+    fn context(self, ctx: &str) -> Result<T, Error>;
+
+    /// Adds the context returned by `f` to the error, with an inferred
+    /// error type. `f` is only called on error.
+    fn with_context<C, F>(self, f: F) -> Result<T, Error>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C;
+
+    /// Adds the context `ctx` to the error and sets its type to
+    /// `error_type`.
+    fn context_as(self, error_type: ErrorType, ctx: &str) -> Result<T, Error>;
+
+    /// Adds the context returned by `f` to the error and sets its type
+    /// to `error_type`. `f` is only called on error.
+    fn with_context_as<C, F>(self, error_type: ErrorType, f: F) -> Result<T, Error>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C;
+}
+
+impl<T, E> Context<T> for Result<T, E>
+where
+    E: StdError + Send + Sync + 'static,
+{
+    fn context(self, ctx: &str) -> Result<T, Error> {
+        self.map_err(|err| into_error(err, None).with_context(ctx))
+    }
+
+    fn with_context<C, F>(self, f: F) -> Result<T, Error>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|err| into_error(err, None).with_context(&f().to_string()))
+    }
+
+    fn context_as(self, error_type: ErrorType, ctx: &str) -> Result<T, Error> {
+        self.map_err(|err| into_error(err, Some(error_type)).with_context(ctx))
+    }
+
+    fn with_context_as<C, F>(self, error_type: ErrorType, f: F) -> Result<T, Error>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|err| into_error(err, Some(error_type)).with_context(&f().to_string()))
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context(self, ctx: &str) -> Result<T, Error> {
+        self.ok_or_else(|| none_error(ErrorType::new(NONE_ERROR_TYPE), ctx))
+    }
+
+    fn with_context<C, F>(self, f: F) -> Result<T, Error>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C,
+    {
+        self.ok_or_else(|| none_error(ErrorType::new(NONE_ERROR_TYPE), &f().to_string()))
+    }
+
+    fn context_as(self, error_type: ErrorType, ctx: &str) -> Result<T, Error> {
+        self.ok_or_else(|| none_error(error_type, ctx))
+    }
+
+    fn with_context_as<C, F>(self, error_type: ErrorType, f: F) -> Result<T, Error>
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C,
+    {
+        self.ok_or_else(|| none_error(error_type, &f().to_string()))
+    }
+}
+
+/// Returns the error type inferred for `error` from the mapping tables.
+pub fn infer_error_type(error: &(dyn StdError + 'static)) -> ErrorType {
+    let mut next = Some(error);
+    while let Some(error) = next {
+        if let Some(io) = error.downcast_ref::<io::Error>() {
+            let kind = io.kind();
+            let name = IO_ERROR_TYPE_TABLE
+                .iter()
+                .find(|(k, _)| *k == kind)
+                .map_or(DEFAULT_ERROR_TYPE, |(_, name)| name);
+            return ErrorType::new(name);
+        }
+        if let Some((_, name)) = ERROR_TYPE_TABLE.iter().find(|(is, _)| is(error)) {
+            return ErrorType::new(name);
+        }
+        next = error.source();
+    }
+    ErrorType::new(DEFAULT_ERROR_TYPE)
+}
+
+/// Converts `err` into an `Error`, keeping an `Error` as it is.
+fn into_error<E>(err: E, error_type: Option<ErrorType>) -> Error
+where
+    E: StdError + Send + Sync + 'static,
+{
+    let cause: Box<dyn StdError + Send + Sync> = Box::new(err);
+    let mut err = match cause.downcast::<Error>() {
+        Ok(err) => *err,
+        Err(cause) => {
+            let inferred = infer_error_type(cause.as_ref());
+            Error::new(&cause.to_string(), inferred).with_cause(cause)
+        }
+    };
+    if let Some(error_type) = error_type {
+        err.error_type = error_type;
+    }
+    err
+}
+
+/// Returns the error of a missing `Option` value, with `ctx` as its
+/// message and its first context frame.
+fn none_error(error_type: ErrorType, ctx: &str) -> Error {
+    Error::new(ctx, error_type).with_context(ctx)
+}
+
+fn is<E: StdError + 'static>(error: &(dyn StdError + 'static)) -> bool {
+    error.is::<E>()
+}
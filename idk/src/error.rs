@@ -1,9 +1,48 @@
-use crate::common::ErrorType;
+use crate::{common::ErrorType, stacktrace::StackTrace};
 use std::error::Error as StdError;
 use std::fmt;
 
+/// The boxed error type that can be used as the cause of an `Error`
+pub type Cause = Box<dyn StdError + Send + Sync + 'static>;
+
 #[derive(Debug)]
 /// Error struct for the crate
+///
+/// An error can own the underlying error that caused it, which is
+/// returned by `source()`, and carries a stack of context frames added
+/// with `with_context`. The alternate `Display` mode (`{:#}`) prints the
+/// context frames and the whole chain of causes.
+///
+/// Since 0.0.2 an error cannot be built with a struct literal, as its
+/// context frames, cause and stack trace are private: create it with
+/// `Error::new` and add them with `with_context` and `with_cause`.
+///
+/// The stack trace of the thread creating an error is captured when
+/// stack traces are enabled by the `IDK_BACKTRACE`, `RUST_LIB_BACKTRACE`
+/// or `RUST_BACKTRACE` environment variables, see
+/// `StackTrace::capture`.
+///
+/// # Example
+///
+/// ```
+/// use idk::{common::ErrorType, error::Error};
+/// use std::error::Error as StdError;
+///
+/// let io = std::io::Error::new(std::io::ErrorKind::NotFound, "keys.json not found");
+/// let err = Error::new("Cannot load the keys", ErrorType::new("data"))
+///     .with_cause(io)
+///     .with_context("reading keys.json")
+///     .with_context("starting the server");
+///
+/// assert_eq!(err.ctx, "starting the server");
+/// assert_eq!(err.source().unwrap().to_string(), "keys.json not found");
+/// assert_eq!(
+///     format!("{err:#}"),
+///     "Cannot load the keys\n\
+///      Context:\n    starting the server\n    reading keys.json\n\
+///      Caused by:\n    0: keys.json not found"
+/// );
+/// ```
 pub struct Error {
     /// The error message
     pub message: String,
@@ -11,8 +50,19 @@ pub struct Error {
     /// The error type
     pub error_type: ErrorType,
 
-    /// The context of the error
+    /// The context of the error, the most recent context frame
     pub ctx: String,
+
+    details: Box<Details>,
+}
+
+/// The context frames, cause and stack trace of an error, boxed to keep
+/// `Error` small
+#[derive(Debug, Default)]
+struct Details {
+    contexts: Vec<String>,
+    cause: Option<Cause>,
+    stack_trace: Option<StackTrace>,
 }
 
 impl Error {
@@ -22,36 +72,122 @@ impl Error {
             message: message.to_owned(),
             error_type,
             ctx: "".to_owned(),
+            details: Box::new(Details {
+                stack_trace: StackTrace::capture(),
+                ..Details::default()
+            }),
         }
     }
 
     /// Create a new error type
     pub fn error_type(name: &str, error_type: ErrorType, ctx: &str) -> Error {
-        Error {
-            message: name.to_owned(),
-            error_type,
-            ctx: ctx.to_owned(),
-        }
+        Error::new(name, error_type).with_context(ctx)
     }
 
     /// Add context to the error
+    ///
+    /// The context is stacked on top of the context frames already
+    /// added, and becomes the `ctx` of the error.
     pub fn with_context(mut self, ctx: &str) -> Error {
+        if !ctx.is_empty() {
+            self.details.contexts.push(ctx.to_owned());
+        }
         self.ctx = ctx.to_owned();
         self
     }
+
+    /// Set the underlying error that caused this error
+    ///
+    /// If the cause is an `Error` with a stack trace, it becomes the
+    /// cause stack trace of the stack trace of this error.
+    pub fn with_cause(mut self, cause: impl Into<Cause>) -> Error {
+        let cause = cause.into();
+        if let (Some(stack_trace), Some(err)) = (
+            self.details.stack_trace.as_mut(),
+            cause.downcast_ref::<Error>(),
+        ) {
+            stack_trace.cause_stack_trace = err.stack_trace().cloned().map(Box::new);
+        }
+        self.details.cause = Some(cause);
+        self
+    }
+
+    /// Every context frame of the error, oldest first
+    pub fn contexts(&self) -> &[String] {
+        &self.details.contexts
+    }
+
+    /// The underlying error that caused this error
+    pub fn cause(&self) -> Option<&(dyn StdError + Send + Sync + 'static)> {
+        self.details.cause.as_deref()
+    }
+
+    /// The stack trace captured when the error was created, if stack
+    /// traces were enabled
+    pub fn stack_trace(&self) -> Option<&StackTrace> {
+        self.details.stack_trace.as_ref()
+    }
+
+    /// Iterate over this error and its chain of causes
+    pub fn chain(&self) -> Chain<'_> {
+        Chain { next: Some(self) }
+    }
+
+    /// The last error of the chain of causes, or this error if it has
+    /// no cause
+    pub fn root_cause(&self) -> &(dyn StdError + 'static) {
+        self.chain().last().unwrap_or(self)
+    }
+}
+
+/// An iterator over an error and its chain of causes, returned by
+/// `Error::chain`
+#[derive(Clone, Debug)]
+pub struct Chain<'a> {
+    next: Option<&'a (dyn StdError + 'static)>,
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn StdError + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let error = self.next?;
+        self.next = error.source();
+        Some(error)
+    }
 }
 
 impl StdError for Error {
     /// Source of the error
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
-        None
+        self.cause().map(|cause| cause as &(dyn StdError + 'static))
     }
 }
 
 impl fmt::Display for Error {
     /// Display the error
+    ///
+    /// The alternate mode (`{:#}`) prints the message, the context
+    /// frames from the most recent, and every cause of the chain.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}, ctx: {}", self.message, self.ctx)
+        if !f.alternate() {
+            return write!(f, "{}, ctx: {}", self.message, self.ctx);
+        }
+        write!(f, "{}", self.message)?;
+        if !self.contexts().is_empty() {
+            write!(f, "\nContext:")?;
+            for ctx in self.contexts().iter().rev() {
+                write!(f, "\n    {ctx}")?;
+            }
+        }
+        let mut causes = self.chain().skip(1).peekable();
+        if causes.peek().is_some() {
+            write!(f, "\nCaused by:")?;
+            for (i, cause) in causes.enumerate() {
+                write!(f, "\n    {i}: {cause}")?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -27,6 +27,10 @@ pub enum JwtError {
     /// Decode error.
     DecodeError(String),
 
+    /// Decryption failed, because the key is wrong or the ciphertext or
+    /// its authentication tag has been altered.
+    DecryptionFailed(String),
+
     /// Expiration is invalid.
     ExpirationInvalid(String),
 
@@ -54,6 +58,9 @@ pub enum JwtError {
     /// JWT is invalid.
     JWTInvalid(String),
 
+    /// Key is invalid or unusable for the requested operation.
+    KeyInvalid(String),
+
     /// Open SSL error.
     OpenSslError(String),
 
@@ -68,6 +75,10 @@ pub enum JwtError {
 
     /// Token not found.
     TokenNotFound(String),
+
+    /// Token has been revoked, or was already used and cannot be
+    /// replayed.
+    TokenRevoked(String),
 }
 
 impl Error for JwtError {}
@@ -159,6 +170,16 @@ impl JwtError {
         matches!(self, JwtError::IoError(_))
     }
 
+    /// Returns `true` if the error is a key error.
+    pub fn is_key_error(&self) -> bool {
+        matches!(self, JwtError::KeyInvalid(_))
+    }
+
+    /// Returns `true` if the error is a decryption error.
+    pub fn is_decryption_error(&self) -> bool {
+        matches!(self, JwtError::DecryptionFailed(_))
+    }
+
     /// Returns `true` if the error is an Open SSL error.
     pub fn is_openssl_error(&self) -> bool {
         matches!(self, JwtError::OpenSslError(_))
@@ -172,6 +193,10 @@ impl JwtError {
     pub fn is_token_not_found_error(&self) -> bool {
         matches!(self, JwtError::TokenNotFound(_))
     }
+    /// Returns `true` if the error is a revoked or replayed token error.
+    pub fn is_revoked_error(&self) -> bool {
+        matches!(self, JwtError::TokenRevoked(_))
+    }
     /// Returns `true` if the error is an invalid base 64.
     pub fn is_base64_error(&self) -> bool {
         matches!(self, JwtError::DecodeError(_))
@@ -10,7 +10,7 @@
 //!
 //! [![Rust](https://img.shields.io/badge/rust-f04041?style=for-the-badge&labelColor=c0282d&logo=rust)](https://www.rust-lang.org)
 //! [![Crates.io](https://img.shields.io/crates/v/idk.svg?style=for-the-badge&color=success&labelColor=27A006)](https://crates.io/crates/idk)
-//! [![Lib.rs](https://img.shields.io/badge/lib.rs-v0.0.1-success.svg?style=for-the-badge&color=8A48FF&labelColor=6F36E4)](https://lib.rs/crates/idk)
+//! [![Lib.rs](https://img.shields.io/badge/lib.rs-v0.0.2-success.svg?style=for-the-badge&color=8A48FF&labelColor=6F36E4)](https://lib.rs/crates/idk)
 //! [![GitHub](https://img.shields.io/badge/github-555555?style=for-the-badge&labelColor=000000&logo=github)](https://github.com/sebastienrousseau/tree/main/idk)
 //! [![License](https://img.shields.io/crates/l/idk.svg?style=for-the-badge&color=007EC6&labelColor=03589B)](http://opensource.org/licenses/MIT)
 //!
@@ -25,34 +25,52 @@
 //!
 //! ## Features
 //!
-//! The library includes multiple modules: `Common`, `Error`, `Jwt`,
-//! `Property`, `Stacktrace`, and `Traits`.
+//! The library includes multiple modules: `Common`, `Context`, `Error`,
+//! `Jwt`, `Property`, `Stacktrace`, and `Traits`.
 //!
 //! - **The common module:** This module provides a foundation of common
-//! functionalities that can be utilized throughout the entire project.
-//! These functionalities serve as a building block for the rest of the
-//! project, making it easier for developers to create and manage their
-//! code.
+//!   functionalities that can be utilized throughout the entire project.
+//!   These functionalities serve as a building block for the rest of the
+//!   project, making it easier for developers to create and manage their
+//!   code. Every kind of error has a stable numeric and string code, a
+//!   severity, and tells whether it is retryable or transient, which
+//!   subtypes created with `new_subtype` inherit. Subtypes and custom error
+//!   types are kept in a registry with links to their parent, so they can
+//!   be looked up by name and tested with `is_a` against their ancestors.
+//!   Error types registered with `register_with` set their own code,
+//!   severity and retryability, which their subtypes inherit in turn.
+//!   The registry is global to the process: once a name is registered,
+//!   `ErrorType::new` returns the registered type for it everywhere.
+//! - **The context module:** This module provides the `Context`
+//!   extension trait. Its `context` and lazy `with_context` methods turn
+//!   the error of any `Result`, or a missing `Option` value, into an
+//!   `Error` and stack context frames on it. The error type is inferred
+//!   from a mapping table, or set explicitly with `context_as`.
 //! - **The error module:** This module contains all the error types
-//! that are used in the project. By providing a centralized location
-//! for errors, developers can quickly and easily identify and resolve
-//! any issues that may arise.
+//!   that are used in the project. By providing a centralized location
+//!   for errors, developers can quickly and easily identify and resolve
+//!   any issues that may arise. An error keeps the underlying error that
+//!   caused it, so `source()` walks the whole chain of causes, and stacks
+//!   the context frames added with `with_context`. `{:#}` prints both.
 //! - **The jwt module:** This module offers the tools necessary to
-//! encode and decode JSON Web Tokens (JWT). With a simple, easy-to-use
-//! interface, developers can ensure secure communication between
-//! parties.
+//!   encode and decode JSON Web Tokens (JWT). With a simple, easy-to-use
+//!   interface, developers can ensure secure communication between
+//!   parties.
 //! - **The property module:** This module provides the functionality to
-//! create and manage properties. By utilizing this module, developers
-//! can keep track of all properties within the project and make changes
-//! as necessary.
+//!   create and manage properties. By utilizing this module, developers
+//!   can keep track of all properties within the project and make changes
+//!   as necessary.
 //! - **The stacktrace module:** This module offers the tools to create
-//! and manage stacktraces. By providing detailed information about the
-//! execution of the code, developers can quickly identify and resolve
-//! any issues that may arise.
+//!   and manage stacktraces. By providing detailed information about the
+//!   execution of the code, developers can quickly identify and resolve
+//!   any issues that may arise. Stack traces are captured with
+//!   `std::backtrace` and attached to every `Error` when `IDK_BACKTRACE`
+//!   or `RUST_BACKTRACE` is set. Their frames are only resolved to
+//!   symbols, files and lines when they are first read or displayed.
 //! - **The traits module:** This module provides functionality to
-//! create and manage traits. By utilizing this module, developers can
-//! ensure that all traits within the project are consistent and
-//! well-defined.
+//!   create and manage traits. By utilizing this module, developers can
+//!   ensure that all traits within the project are consistent and
+//!   well-defined.
 //!
 //! ## Usage
 //!
@@ -77,6 +95,10 @@
 /// throughout the entire crate.
 pub mod common;
 
+/// The context module provides an extension trait to add context to
+/// a `Result` or `Option` and turn its error into an `Error`.
+pub mod context;
+
 /// The error module contains all the error types used in the crate.
 pub mod error;
 
@@ -88,7 +110,7 @@ pub mod jwt;
 /// properties.
 pub mod property;
 
-/// The stacktrace module provides functionality to create and manage
+/// The stacktrace module provides functionality to capture and format
 /// stacktraces.
 pub mod stacktrace;
 
@@ -1,14 +1,76 @@
-use std::fmt;
+use once_cell::sync::OnceCell;
+use std::{
+    backtrace::Backtrace,
+    cmp::Ordering,
+    env, fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
 
 const STACK_TRACE_DEPTH: usize = 128;
 
+/// The environment variables enabling the capture of stack traces for
+/// errors, in order of precedence. A variable set to `0` disables the
+/// capture, any other value enables it.
+const STACK_TRACE_VARS: [&str; 3] = ["IDK_BACKTRACE", "RUST_LIB_BACKTRACE", "RUST_BACKTRACE"];
+
+/// The prefix of the symbols of the frames capturing the stack trace
+const CAPTURE_FRAME_PREFIX: &str = "idk::stacktrace::";
+
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+/// A frame of a stack trace, resolved from the debug info available.
+pub struct Frame {
+    /// The demangled name of the function, `<unknown>` if it cannot be
+    /// resolved.
+    pub symbol: String,
+    /// The source file of the frame, if known.
+    pub file: Option<String>,
+    /// The line in the source file, if known.
+    pub line: Option<u32>,
+    /// The column in the source file, if known.
+    pub column: Option<u32>,
+}
+
+impl fmt::Display for Frame {
+    /// The formatted frame, `symbol at file:line:column`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol)?;
+        if let Some(file) = &self.file {
+            write!(f, " at {file}")?;
+            if let Some(line) = self.line {
+                write!(f, ":{line}")?;
+            }
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 /// The program counter of the stack trace.
+///
+/// A stack trace is captured with `std::backtrace::Backtrace`, which
+/// does not expose the program counters, so `pc` is only filled by
+/// traces built by hand. Capturing only records the stack: its frames
+/// are resolved to symbols, and to files and lines where debug info is
+/// available, the first time they are read or displayed.
 pub struct StackTrace {
     /// The program counter of the stack trace.
     pub pc: Vec<usize>,
     /// The cause stack trace of the stack trace.
     pub cause_stack_trace: Option<Box<StackTrace>>,
+    /// The frames of the stack trace, shared by its clones
+    frames: Option<Arc<Frames>>,
+}
+
+/// The frames of a stack trace, resolved from the captured backtrace on
+/// first access
+#[derive(Debug)]
+struct Frames {
+    backtrace: Option<Backtrace>,
+    resolved: OnceCell<Vec<Frame>>,
 }
 
 impl StackTrace {
@@ -24,22 +86,105 @@ impl StackTrace {
         )
     }
 
-    /// Creates a new stack trace.
+    /// Creates a new, empty stack trace. Nothing is captured.
     pub fn new() -> StackTrace {
         let pc = Vec::with_capacity(STACK_TRACE_DEPTH);
         StackTrace {
             pc,
-            cause_stack_trace: Some(Box::new(StackTrace {
-                pc: Vec::with_capacity(STACK_TRACE_DEPTH),
-                cause_stack_trace: None,
+            cause_stack_trace: None,
+            frames: None,
+        }
+    }
+
+    /// Creates a stack trace of the given resolved frames, the most
+    /// recent first.
+    pub fn from_frames(frames: Vec<Frame>) -> StackTrace {
+        StackTrace {
+            frames: Some(Arc::new(Frames {
+                backtrace: None,
+                resolved: OnceCell::with_value(frames),
             })),
+            ..StackTrace::new()
+        }
+    }
+
+    /// Captures the stack of the current thread whatever the
+    /// environment variables. The frames are resolved lazily.
+    pub fn force_capture() -> StackTrace {
+        StackTrace {
+            frames: Some(Arc::new(Frames {
+                backtrace: Some(Backtrace::force_capture()),
+                resolved: OnceCell::new(),
+            })),
+            ..StackTrace::new()
+        }
+    }
+
+    /// Captures the stack of the current thread if stack traces are
+    /// enabled, `None` otherwise.
+    ///
+    /// Stack traces are enabled by `IDK_BACKTRACE`, then
+    /// `RUST_LIB_BACKTRACE`, then `RUST_BACKTRACE`: the first of these
+    /// variables that is set enables them unless it is `0`. Stack traces
+    /// are disabled if none is set. This is how every
+    /// `idk::error::Error` gets its stack trace.
+    pub fn capture() -> Option<StackTrace> {
+        StackTrace::is_enabled().then(StackTrace::force_capture)
+    }
+
+    /// Whether stack traces are enabled by the environment variables.
+    pub fn is_enabled() -> bool {
+        STACK_TRACE_VARS
+            .iter()
+            .find_map(env::var_os)
+            .map_or(false, |value| value != "0")
+    }
+
+    /// The resolved frames of the stack trace, the most recent first.
+    ///
+    /// The frames of a captured stack trace are resolved on the first
+    /// call, which is costly, and cached for its clones.
+    pub fn frames(&self) -> &[Frame] {
+        match &self.frames {
+            Some(frames) => frames.resolved.get_or_init(|| {
+                frames
+                    .backtrace
+                    .as_ref()
+                    .map_or_else(Vec::new, |backtrace| parse_frames(&backtrace.to_string()))
+            }),
+            None => &[],
         }
     }
+
+    /// The parts of the stack trace compared and hashed
+    fn key(&self) -> (&[usize], &[Frame], Option<&StackTrace>) {
+        (&self.pc, self.frames(), self.cause_stack_trace.as_deref())
+    }
+}
+
+impl PartialEq for StackTrace {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for StackTrace {}
+
+impl PartialOrd for StackTrace {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl Default for StackTrace {
-    fn default() -> Self {
-        Self::new()
+impl Ord for StackTrace {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for StackTrace {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
     }
 }
 
@@ -50,6 +195,7 @@ impl fmt::Display for StackTrace {
             .pc
             .iter()
             .map(|&pc| format!("{pc:#x}"))
+            .chain(self.frames().iter().map(Frame::to_string))
             .collect::<Vec<_>>();
         let formatted_stack_trace = StackTrace::format_stack_trace(stack_trace);
 
@@ -61,3 +207,67 @@ impl fmt::Display for StackTrace {
         }
     }
 }
+
+/// Parses the frames of a backtrace printed by `std`, the only place
+/// its text format is relied on.
+///
+/// `std` prints every symbol on a line, numbered if it starts a frame,
+/// followed by its location on an `at file:line:column` line if it is
+/// known. The frames up to the last one of `idk::stacktrace`, which
+/// captured the backtrace, are dropped.
+///
+/// # Example
+///
+/// ```
+/// use idk::stacktrace::parse_frames;
+///
+/// let frames = parse_frames(
+///     "   0: app::load\n             at ./src/main.rs:12:5\n   1: <unknown>\n",
+/// );
+/// assert_eq!(frames[0].to_string(), "app::load at ./src/main.rs:12:5");
+/// assert_eq!(frames[1].symbol, "<unknown>");
+/// ```
+pub fn parse_frames(backtrace: &str) -> Vec<Frame> {
+    let mut frames: Vec<Frame> = Vec::new();
+    for line in backtrace.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(frame) = frames.last_mut() {
+                set_location(frame, location);
+            }
+            continue;
+        }
+        let symbol = match line.split_once(": ") {
+            Some((index, symbol)) if index.bytes().all(|b| b.is_ascii_digit()) => symbol,
+            _ => line,
+        };
+        frames.push(Frame {
+            symbol: symbol.to_owned(),
+            file: None,
+            line: None,
+            column: None,
+        });
+    }
+    // Drop the frames of the capture itself
+    let skip = frames
+        .iter()
+        .rposition(|frame| frame.symbol.starts_with(CAPTURE_FRAME_PREFIX))
+        .map_or(0, |i| i + 1);
+    frames.split_off(skip)
+}
+
+/// Sets the `file:line:column` location of a frame.
+fn set_location(frame: &mut Frame, location: &str) {
+    let mut parts = location.rsplitn(3, ':');
+    let (column, line, file) = (parts.next(), parts.next(), parts.next());
+    match (file, line.and_then(|l| l.parse().ok())) {
+        (Some(file), Some(line)) => {
+            frame.file = Some(file.to_owned());
+            frame.line = Some(line);
+            frame.column = column.and_then(|c| c.parse().ok());
+        }
+        _ => frame.file = Some(location.to_owned()),
+    }
+}
@@ -1,9 +1,8 @@
 #[cfg(test)]
-
 // TODO: Add more tests to bring the code coverage to 100%
 mod tests {
     extern crate idk;
-    use idk::common::{Error, ErrorType};
+    use idk::common::{Error, ErrorType, Severity};
 
     #[test]
     fn test_error_type_new() {
@@ -18,7 +17,7 @@ mod tests {
     #[test]
     fn test_error_type_new_subtype() {
         let error_type = ErrorType::new("illegal_argument");
-        let subtype = error_type.new_subtype("subtype");
+        let subtype = error_type.new_subtype("subtype").unwrap();
         assert_eq!(subtype.name, "subtype");
         match subtype.error_type {
             Error::Unknown(s) => assert_eq!(s, "Unknown error"),
@@ -64,4 +63,197 @@ mod tests {
         let err = Error::new(err_type);
         assert_eq!(err, Error::Unknown(String::from("Unknown error")));
     }
+
+    #[test]
+    fn test_error_codes() {
+        let names = [
+            "argument",
+            "assertion",
+            "concurrency",
+            "data",
+            "external",
+            "format",
+            "implementation",
+            "initialization",
+            "internal",
+            "interruption",
+            "operation",
+            "rejection",
+            "state",
+            "timeout",
+            "unknown",
+            "version",
+        ];
+        for (code, name) in (1..).zip(names) {
+            let error_type = ErrorType::new(name);
+            assert_eq!(error_type.code(), code, "{name}");
+            assert_eq!(error_type.code_str(), name);
+        }
+        assert_eq!(ErrorType::new("illegal_argument").code_str(), "unknown");
+    }
+
+    #[test]
+    fn test_error_severity() {
+        assert_eq!(ErrorType::new("timeout").severity(), Severity::Warning);
+        assert_eq!(ErrorType::new("format").severity(), Severity::Error);
+        assert_eq!(ErrorType::new("internal").severity(), Severity::Critical);
+        assert_eq!(Severity::Critical.to_string(), "critical");
+        assert!(Severity::Warning < Severity::Error);
+        assert!(Severity::Error < Severity::Critical);
+    }
+
+    #[test]
+    fn test_error_retryability() {
+        for name in ["timeout", "concurrency"] {
+            assert!(ErrorType::new(name).is_retryable(), "{name}");
+            assert!(ErrorType::new(name).is_transient(), "{name}");
+        }
+        let interruption = Error::Interruption(String::from("interrupted"));
+        assert!(!interruption.is_retryable());
+        assert!(interruption.is_transient());
+        for name in ["argument", "data", "external", "rejection", "unknown"] {
+            assert!(!ErrorType::new(name).is_retryable(), "{name}");
+            assert!(!ErrorType::new(name).is_transient(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_error_subtype_metadata() {
+        let timeout = ErrorType::new("timeout");
+        let subtype = timeout.new_subtype("connect_timeout").unwrap();
+        assert_eq!(subtype.name, "connect_timeout");
+        assert_eq!(subtype.code(), timeout.code());
+        assert_eq!(subtype.severity(), Severity::Warning);
+        assert!(subtype.is_retryable());
+
+        // A kind of error cannot be a subtype
+        let err = timeout.new_subtype("rejection").unwrap_err();
+        assert_eq!(err.error_type.name, "argument");
+        assert!(!ErrorType::new("rejection").is_a(&timeout));
+    }
+
+    #[test]
+    fn test_error_type_registry() {
+        let data = ErrorType::new("data");
+        let not_found =
+            ErrorType::register("registry_not_found", Some(&data), "Not found").unwrap();
+        let no_user = not_found.new_subtype("registry_no_user").unwrap();
+        assert_eq!(no_user.error_type, Error::Data(String::from("Not found")));
+        assert_eq!(no_user.parent(), Some(not_found.clone()));
+        assert_eq!(not_found.parent(), Some(data.clone()));
+        assert_eq!(data.parent(), None);
+
+        assert!(no_user.is_a(&no_user));
+        assert!(no_user.is_a(&not_found));
+        assert!(no_user.is_a(&data));
+        assert!(!not_found.is_a(&no_user));
+        assert!(!no_user.is_a(&ErrorType::new("format")));
+
+        // Registered names are known to ErrorType::new and lookup
+        assert_eq!(ErrorType::new("registry_no_user"), no_user);
+        assert_eq!(ErrorType::lookup("registry_not_found"), Some(not_found));
+        assert_eq!(
+            ErrorType::lookup("timeout"),
+            Some(ErrorType::new("timeout"))
+        );
+        assert_eq!(ErrorType::lookup("registry_missing"), None);
+    }
+
+    #[test]
+    fn test_error_type_register_custom() {
+        let custom = ErrorType::register("registry_custom", None, "Custom failure").unwrap();
+        assert_eq!(
+            custom.error_type,
+            Error::Unknown(String::from("Custom failure"))
+        );
+        assert_eq!(custom.parent(), None);
+        assert_eq!(
+            ErrorType::new("registry_custom").error_type.message(),
+            "Custom failure"
+        );
+
+        // Registering again with the same parent updates the message
+        let custom = ErrorType::register("registry_custom", None, "Updated").unwrap();
+        assert_eq!(custom.error_type.message(), "Updated");
+        assert_eq!(ErrorType::new("registry_custom"), custom);
+    }
+
+    #[test]
+    fn test_error_type_register_with_metadata() {
+        let data = ErrorType::new("data");
+        let quota = ErrorType::register_with(
+            "registry_quota",
+            Some(&data),
+            "Quota exceeded",
+            2_001,
+            Severity::Warning,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            quota.error_type,
+            Error::Data(String::from("Quota exceeded"))
+        );
+        assert_eq!(quota.code(), 2_001);
+        assert_eq!(quota.code_str(), "registry_quota");
+        assert_eq!(quota.severity(), Severity::Warning);
+        assert!(quota.is_retryable());
+        assert!(quota.is_transient());
+        assert_eq!(ErrorType::new("registry_quota").code(), 2_001);
+
+        // Subtypes inherit the metadata of their nearest ancestor
+        let daily = quota.new_subtype("registry_daily_quota").unwrap();
+        assert_eq!(daily.code(), 2_001);
+        assert_eq!(daily.code_str(), "registry_quota");
+        assert!(daily.is_retryable());
+
+        // Error types registered without metadata report their kind's
+        let plain = ErrorType::register("registry_plain", Some(&data), "Plain").unwrap();
+        assert_eq!(plain.code(), data.code());
+        assert_eq!(plain.code_str(), "data");
+        assert_eq!(plain.severity(), Severity::Error);
+        assert!(!plain.is_retryable());
+    }
+
+    #[test]
+    fn test_error_type_register_with_code_conflicts() {
+        let state = ErrorType::new("state");
+        let register = |name: &str, code: u16| {
+            ErrorType::register_with(name, Some(&state), "m", code, Severity::Error, false)
+        };
+
+        let err = register("registry_low_code", 13).unwrap_err();
+        assert_eq!(err.error_type.name, "argument");
+
+        let _ = register("registry_coded", 2_002).unwrap();
+        let err = register("registry_recoded", 2_002).unwrap_err();
+        assert_eq!(err.error_type.name, "state");
+        assert_eq!(ErrorType::lookup("registry_recoded"), None);
+        // Registering the same error type again may keep its code
+        assert_eq!(register("registry_coded", 2_002).unwrap().code(), 2_002);
+    }
+
+    #[test]
+    fn test_error_type_register_errors() {
+        let state = ErrorType::new("state");
+        for name in ["", "timeout", "unknown"] {
+            let err = ErrorType::register(name, Some(&state), "message").unwrap_err();
+            assert_eq!(err.error_type.name, "argument", "{name}");
+        }
+
+        let parent = ErrorType::register("registry_parent", Some(&state), "Parent").unwrap();
+        let child = parent.new_subtype("registry_child").unwrap();
+        let err = ErrorType::register("registry_parent", Some(&child), "Cycle").unwrap_err();
+        assert_eq!(err.error_type.name, "argument");
+
+        let err = ErrorType::register("registry_child", None, "Moved").unwrap_err();
+        assert_eq!(err.error_type.name, "state");
+        // new_subtype reports the conflict and keeps the registration
+        let err = ErrorType::new("format")
+            .new_subtype("registry_child")
+            .unwrap_err();
+        assert_eq!(err.error_type.name, "state");
+        assert_eq!(ErrorType::new("registry_child"), child);
+        assert_eq!(child.parent(), Some(parent));
+    }
 }
@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests {
+    extern crate idk;
+    use idk::{
+        common::ErrorType,
+        context::{infer_error_type, Context},
+        error::Error,
+        jwt::JwtError,
+    };
+    use std::{error::Error as StdError, fmt, io};
+
+    fn read(kind: io::ErrorKind) -> Result<String, io::Error> {
+        Err(io::Error::new(kind, "read failed"))
+    }
+
+    #[derive(Debug)]
+    struct Wrapper(io::Error);
+
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "wrapper")
+        }
+    }
+
+    impl StdError for Wrapper {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_context_wraps_foreign_errors() {
+        let err = read(io::ErrorKind::NotFound)
+            .context("reading keys.json")
+            .unwrap_err();
+        assert_eq!(err.message, "read failed");
+        assert_eq!(err.error_type.name, "data");
+        assert_eq!(err.ctx, "reading keys.json");
+        assert_eq!(err.contexts(), ["reading keys.json"]);
+        let cause = err.cause().unwrap();
+        assert_eq!(
+            cause.downcast_ref::<io::Error>().unwrap().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(err.root_cause().to_string(), "read failed");
+    }
+
+    #[test]
+    fn test_context_stacks_frames_on_errors() {
+        fn load() -> Result<String, Error> {
+            read(io::ErrorKind::TimedOut).context("reading keys.json")
+        }
+        fn start() -> Result<String, Error> {
+            load().with_context(|| format!("starting server {}", 1))
+        }
+
+        let err = start().context("running").unwrap_err();
+        assert_eq!(err.error_type.name, "timeout");
+        assert_eq!(
+            err.contexts(),
+            ["reading keys.json", "starting server 1", "running"]
+        );
+        assert_eq!(err.ctx, "running");
+        // The idk error is not wrapped again
+        assert_eq!(err.chain().count(), 2);
+        assert_eq!(
+            format!("{err:#}"),
+            "read failed\nContext:\n    running\n    starting server 1\n    reading keys.json\nCaused by:\n    0: read failed"
+        );
+    }
+
+    #[test]
+    fn test_with_context_is_lazy() {
+        let mut called = false;
+        let value: Result<u8, io::Error> = Ok(1);
+        assert_eq!(
+            value
+                .with_context(|| {
+                    called = true;
+                    "unused"
+                })
+                .unwrap(),
+            1
+        );
+        assert!(!called);
+
+        assert_eq!(Some(2).with_context(|| -> String { panic!() }).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_option_context() {
+        let err = None::<u8>.context("missing port").unwrap_err();
+        assert_eq!(err.message, "missing port");
+        assert_eq!(err.error_type.name, "data");
+        assert!(err.cause().is_none());
+        // The context is a context frame, as for a Result
+        assert_eq!(err.ctx, "missing port");
+        assert_eq!(err.contexts(), ["missing port"]);
+
+        let err = None::<u8>
+            .with_context_as(ErrorType::new("argument"), || "no port given")
+            .unwrap_err();
+        assert_eq!(err.message, "no port given");
+        assert_eq!(err.error_type.name, "argument");
+        assert_eq!(err.contexts(), ["no port given"]);
+
+        fn port() -> Result<u8, Error> {
+            None.with_context(|| format!("reading port {}", 1))
+        }
+        let err = port().context("starting").unwrap_err();
+        assert_eq!(err.contexts(), ["reading port 1", "starting"]);
+
+        assert_eq!(Some(1).context("missing").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_explicit_error_type() {
+        let err = read(io::ErrorKind::NotFound)
+            .context_as(ErrorType::new("initialization"), "loading")
+            .unwrap_err();
+        assert_eq!(err.error_type.name, "initialization");
+        assert_eq!(err.contexts(), ["loading"]);
+
+        let err = Err::<(), _>(Error::new("message", ErrorType::new("data")))
+            .with_context_as(ErrorType::new("state"), || "checking")
+            .unwrap_err();
+        assert_eq!(err.error_type.name, "state");
+        assert_eq!(err.message, "message");
+    }
+
+    #[test]
+    fn test_inferred_error_types() {
+        let cases: Vec<(Box<dyn StdError>, &str)> = vec![
+            (Box::new("x".parse::<i32>().unwrap_err()), "format"),
+            (Box::new("x".parse::<f64>().unwrap_err()), "format"),
+            (Box::new("x".parse::<bool>().unwrap_err()), "format"),
+            (
+                Box::new(String::from_utf8(vec![0xff]).unwrap_err()),
+                "format",
+            ),
+            (
+                Box::new(serde_json::from_str::<u8>("{").unwrap_err()),
+                "format",
+            ),
+            (Box::new(std::env::VarError::NotPresent), "initialization"),
+            (
+                Box::new(JwtError::SignatureInvalid("bad".to_string())),
+                "rejection",
+            ),
+            (
+                Box::new(io::Error::new(io::ErrorKind::TimedOut, "slow")),
+                "timeout",
+            ),
+            (
+                Box::new(io::Error::new(io::ErrorKind::Interrupted, "stop")),
+                "interruption",
+            ),
+            (
+                Box::new(io::Error::new(io::ErrorKind::PermissionDenied, "no")),
+                "rejection",
+            ),
+            (
+                Box::new(io::Error::new(io::ErrorKind::Other, "other")),
+                "external",
+            ),
+            (
+                Box::new(Wrapper(io::Error::new(io::ErrorKind::InvalidInput, "bad"))),
+                "argument",
+            ),
+            (Box::new(fmt::Error), "format"),
+            (Box::<dyn StdError>::from("custom"), "external"),
+        ];
+        for (error, name) in cases {
+            assert_eq!(infer_error_type(error.as_ref()).name, name, "{error}");
+        }
+    }
+}
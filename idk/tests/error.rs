@@ -1,10 +1,9 @@
 #[cfg(test)]
-
 // TODO: Add more tests to bring the code coverage to 100%
 mod tests {
     extern crate idk;
     use idk::{common::ErrorType, error::Error as OtherError};
-    use std::error::Error as StdError;
+    use std::{error::Error as StdError, io};
 
     #[test]
     fn test_error_new() {
@@ -43,4 +42,70 @@ mod tests {
         let display = format!("{err_type}");
         assert_eq!(display, "test_error_type");
     }
+
+    #[test]
+    fn test_with_context_stacks_frames() {
+        let err = OtherError::new("message", ErrorType::new("test_error"))
+            .with_context("inner")
+            .with_context("outer");
+        assert_eq!(err.ctx, "outer");
+        assert_eq!(err.contexts(), ["inner", "outer"]);
+        assert_eq!(format!("{err}"), "message, ctx: outer");
+
+        let err = OtherError::error_type("message", ErrorType::new("test_error"), "first");
+        assert_eq!(err.with_context("second").contexts(), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_source_chain() {
+        let io = io::Error::new(io::ErrorKind::NotFound, "root cause");
+        let inner = OtherError::new("inner", ErrorType::new("data")).with_cause(io);
+        let err = OtherError::new("outer", ErrorType::new("operation")).with_cause(inner);
+
+        let source = StdError::source(&err).unwrap();
+        assert_eq!(source.to_string(), "inner, ctx: ");
+        assert_eq!(source.source().unwrap().to_string(), "root cause");
+        assert!(source.source().unwrap().source().is_none());
+
+        let chain: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+        assert_eq!(chain, ["outer, ctx: ", "inner, ctx: ", "root cause"]);
+        assert_eq!(err.root_cause().to_string(), "root cause");
+        assert!(err.root_cause().downcast_ref::<io::Error>().is_some());
+
+        let err = OtherError::new("alone", ErrorType::new("data"));
+        assert_eq!(err.chain().count(), 1);
+        assert_eq!(err.root_cause().to_string(), "alone, ctx: ");
+    }
+
+    #[test]
+    fn test_cause_from_string_and_error_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let err = OtherError::new("message", ErrorType::new("data")).with_cause("boxed message");
+        assert_send_sync(&err);
+        assert_eq!(StdError::source(&err).unwrap().to_string(), "boxed message");
+
+        let parse = "x".parse::<i32>().unwrap_err();
+        let err = OtherError::new("message", ErrorType::new("format")).with_cause(parse);
+        assert!(err.cause().unwrap().is::<std::num::ParseIntError>());
+    }
+
+    #[test]
+    fn test_display_alternate() {
+        let err = OtherError::new("Test error", ErrorType::new("test_error"));
+        assert_eq!(format!("{err:#}"), "Test error");
+
+        let err = OtherError::new("Cannot start", ErrorType::new("initialization"))
+            .with_cause(
+                OtherError::new("Cannot read keys", ErrorType::new("data"))
+                    .with_cause(io::Error::new(io::ErrorKind::Other, "disk failure")),
+            )
+            .with_context("loading configuration")
+            .with_context("starting server");
+        assert_eq!(
+            format!("{err:#}"),
+            "Cannot start\n\
+             Context:\n    starting server\n    loading configuration\n\
+             Caused by:\n    0: Cannot read keys, ctx: \n    1: disk failure"
+        );
+    }
 }
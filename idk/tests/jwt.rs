@@ -1,5 +1,4 @@
 #[cfg(test)]
-
 // TODO: Add more tests to bring the code coverage to 100%
 mod tests {
     extern crate idk;
@@ -45,5 +44,27 @@ mod tests {
         assert!(!jwt_error.is_protocol_error());
         assert!(!jwt_error.is_token_not_found_error());
         assert!(!jwt_error.is_base64_error());
+        assert!(!jwt_error.is_key_error());
+    }
+    #[test]
+    fn test_key_error() {
+        let jwt_error = JwtError::KeyInvalid("Key is invalid".to_string());
+        assert!(jwt_error.is_key_error());
+        assert!(!jwt_error.is_signature_error());
+    }
+
+    #[test]
+    fn test_decryption_error() {
+        let jwt_error = JwtError::DecryptionFailed("Decryption failed".to_string());
+        assert!(jwt_error.is_decryption_error());
+        assert!(!jwt_error.is_key_error());
+    }
+
+    #[test]
+    fn test_revoked_error() {
+        let jwt_error = JwtError::TokenRevoked("Token has been revoked".to_string());
+        assert!(jwt_error.is_revoked_error());
+        assert!(!jwt_error.is_token_not_found_error());
+        assert!(!jwt_error.is_signature_error());
     }
 }
@@ -1,9 +1,13 @@
 #[cfg(test)]
-
 // TODO: Add more tests to bring the code coverage to 100%
 mod tests {
     extern crate idk;
-    use idk::stacktrace::StackTrace;
+    use idk::{
+        common::ErrorType,
+        error::Error,
+        stacktrace::{parse_frames, Frame, StackTrace},
+    };
+    use std::env;
 
     #[test]
     fn test_stack_trace_new() {
@@ -17,9 +21,133 @@ mod tests {
         assert_eq!(stack_trace.pc.len(), 0);
     }
 
+    #[test]
+    fn test_new_stack_trace_is_empty() {
+        let stack_trace = StackTrace::new();
+        assert!(stack_trace.frames().is_empty());
+        assert_eq!(stack_trace, StackTrace::default());
+        assert_eq!(stack_trace.to_string(), "Stack trace:\n");
+    }
+
     #[test]
     fn test_display_stack_trace() {
         let test_display_stack_trace = StackTrace::new();
         assert!(!test_display_stack_trace.to_string().is_empty());
     }
+
+    #[test]
+    fn test_stack_trace_frames() {
+        let stack_trace = StackTrace::force_capture();
+        assert!(!stack_trace.frames()[0]
+            .symbol
+            .starts_with("idk::stacktrace"));
+        let frame = stack_trace
+            .frames()
+            .iter()
+            .find(|frame| frame.symbol.ends_with("test_stack_trace_frames"))
+            .unwrap();
+        assert!(frame.file.as_ref().unwrap().ends_with("stacktrace.rs"));
+        assert!(frame.line.is_some());
+        assert!(stack_trace
+            .to_string()
+            .contains("test_stack_trace_frames at "));
+        // Clones share the resolved frames
+        assert_eq!(stack_trace.clone(), stack_trace);
+    }
+
+    #[test]
+    fn test_parse_frames() {
+        // The format of std::backtrace::Backtrace, where an inlined
+        // function adds a symbol line without number to its frame
+        let backtrace = "   0: idk::stacktrace::StackTrace::force_capture
+             at ./idk/src/stacktrace.rs:90:34
+   1: idk::stacktrace::StackTrace::capture
+             at ./idk/src/stacktrace.rs:108:9
+   2: app::load
+             at ./src/main.rs:12:5
+      app::read
+             at ./src/io.rs:3
+   3: core::ops::function::FnOnce::call_once
+             at /rustc/abc/library/core/src/ops/function.rs:250:5
+   4: <unknown>
+   5: app::main
+             at src/weird
+";
+        let frames = parse_frames(backtrace);
+        let frames: Vec<_> = frames.iter().map(Frame::to_string).collect();
+        assert_eq!(
+            frames,
+            [
+                "app::load at ./src/main.rs:12:5",
+                "app::read at ./src/io.rs:3",
+                "core::ops::function::FnOnce::call_once at \
+                 /rustc/abc/library/core/src/ops/function.rs:250:5",
+                "<unknown>",
+                "app::main at src/weird",
+            ]
+        );
+        assert!(parse_frames("").is_empty());
+    }
+
+    #[test]
+    fn test_display_frames() {
+        let frame = |symbol: &str, file: Option<&str>, line, column| Frame {
+            symbol: symbol.to_string(),
+            file: file.map(str::to_string),
+            line,
+            column,
+        };
+        let mut stack_trace = StackTrace::from_frames(vec![
+            frame("app::load", Some("./src/main.rs"), Some(12), Some(5)),
+            frame("<unknown>", None, None, None),
+        ]);
+        stack_trace.pc = vec![0x1f];
+        stack_trace.cause_stack_trace = Some(Box::new(StackTrace::from_frames(vec![frame(
+            "app::read",
+            Some("./src/io.rs"),
+            Some(3),
+            None,
+        )])));
+        assert_eq!(
+            stack_trace.to_string(),
+            "Stack trace:\n\t- 0x1f\n\t- app::load at ./src/main.rs:12:5\n\t- <unknown>\n\
+             Stack trace:\n\t- app::read at ./src/io.rs:3"
+        );
+    }
+
+    // Every test reading the environment variables is in this test, as
+    // tests run in parallel.
+    #[test]
+    fn test_error_stack_trace() {
+        env::set_var("IDK_BACKTRACE", "0");
+        env::set_var("RUST_BACKTRACE", "1");
+        assert!(!StackTrace::is_enabled());
+        assert!(StackTrace::capture().is_none());
+        assert!(Error::new("message", ErrorType::new("data"))
+            .stack_trace()
+            .is_none());
+
+        env::remove_var("IDK_BACKTRACE");
+        assert!(StackTrace::is_enabled());
+        env::set_var("IDK_BACKTRACE", "full");
+        assert!(StackTrace::is_enabled());
+
+        let cause = Error::new("cause", ErrorType::new("data"));
+        let err = Error::new("message", ErrorType::new("data")).with_cause(cause);
+        let stack_trace = err.stack_trace().unwrap();
+        assert!(stack_trace
+            .frames()
+            .iter()
+            .any(|frame| frame.symbol.ends_with("test_error_stack_trace")));
+        assert_eq!(
+            stack_trace.cause_stack_trace.as_deref(),
+            err.cause()
+                .and_then(|cause| cause.downcast_ref::<Error>())
+                .and_then(Error::stack_trace)
+        );
+        assert!(stack_trace.cause_stack_trace.is_some());
+
+        env::remove_var("IDK_BACKTRACE");
+        env::remove_var("RUST_BACKTRACE");
+    }
 }